@@ -1,4 +1,9 @@
-use crate::models::agent::AgentDef;
+use tauri::State;
+
+use crate::commands::checkpoints::{auto_checkpoint, AutoTrigger};
+use crate::commands::workspace_lifecycle::now_iso8601_pub;
+use crate::models::agent::{AgentDef, AgentSession};
+use crate::state::AppState;
 
 #[tauri::command]
 pub fn list_agents() -> Vec<AgentDef> {
@@ -40,3 +45,67 @@ pub fn list_agents() -> Vec<AgentDef> {
         },
     ]
 }
+
+/// Record that an agent terminal is running in a workspace. Re-registering a
+/// session id replaces the previous entry.
+#[tauri::command]
+pub fn register_agent_session(
+    session_id: String,
+    workspace_id: String,
+    agent_id: String,
+    worktree_path: String,
+    state: State<'_, AppState>,
+) -> Result<AgentSession, String> {
+    let session = AgentSession {
+        id: session_id,
+        workspace_id,
+        agent_id,
+        worktree_path,
+        started_at: now_iso8601_pub(),
+    };
+
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    data.agent_sessions.retain(|s| s.id != session.id);
+    data.agent_sessions.push(session.clone());
+    Ok(session)
+}
+
+/// Forget an agent terminal once its PTY has exited or been killed, then take
+/// an automatic checkpoint of its workspace in the background if the
+/// workspace's policy asks for one.
+#[tauri::command]
+pub fn unregister_agent_session(
+    session_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    let Some(idx) = data.agent_sessions.iter().position(|s| s.id == session_id) else {
+        return Ok(());
+    };
+    let session = data.agent_sessions.remove(idx);
+    drop(data);
+
+    std::thread::spawn(move || {
+        let trigger = AutoTrigger::AgentExit(&session.agent_id);
+        if let Err(e) = auto_checkpoint(&app, &session.workspace_id, trigger) {
+            eprintln!("Auto checkpoint after {} exited failed: {}", session.agent_id, e);
+        }
+    });
+    Ok(())
+}
+
+/// List running agent terminals, optionally filtered by workspace.
+#[tauri::command]
+pub fn list_agent_sessions(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<AgentSession>, String> {
+    let data = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(data
+        .agent_sessions
+        .iter()
+        .filter(|s| workspace_id.as_ref().is_none_or(|id| &s.workspace_id == id))
+        .cloned()
+        .collect())
+}
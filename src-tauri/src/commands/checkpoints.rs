@@ -1,75 +1,502 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::process::Command;
+use std::time::{Duration, Instant};
 
-use tauri::State;
+use git2::build::CheckoutBuilder;
+use git2::{DiffFindOptions, IndexAddOption, Oid, Repository, ResetType, Signature};
+use tauri::{Manager, State};
+use tauri_plugin_store::StoreExt;
 
-use crate::models::checkpoint::Checkpoint;
+use crate::commands::git::file_diffs;
+use crate::commands::workspace_lifecycle::{iso8601_secs_ago, persist_workspaces, uuid_v4};
+use crate::models::checkpoint::{
+    AutoCheckpointPolicy, Checkpoint, CheckpointRetention, RestoreMode,
+};
+use crate::models::git::FileDiff;
+use crate::models::scripts::ProcessStatus;
+use crate::models::workspace::{WorkspaceConfig, WorkspaceStatus};
 use crate::state::AppState;
 
-/// Create a checkpoint (git stash-like snapshot).
-#[tauri::command]
+/// Checkpoint commits are kept reachable by refs under this namespace.
+const CHECKPOINT_REF_PREFIX: &str = "refs/heroi/checkpoints";
+/// How often `interval_minutes` policies are checked.
+const AUTO_CHECKPOINT_TICK: Duration = Duration::from_secs(60);
+
+/// Create a checkpoint of the worktree's current state, including untracked
+/// (but not ignored) files. The snapshot is a detached commit kept alive by
+/// a private `refs/heroi/checkpoints/<workspace>/<n>` ref; HEAD, the branch
+/// and the index are left untouched.
+#[tauri::command(async)]
 pub fn create_checkpoint(
     workspace_id: String,
     worktree_path: String,
     label: String,
     agent_id: Option<String>,
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<Checkpoint, String> {
-    // Stage all changes
-    let _ = Command::new("git")
-        .current_dir(&worktree_path)
-        .args(["add", "-A"])
-        .output();
-
-    // Count changed files
-    let status = Command::new("git")
-        .current_dir(&worktree_path)
-        .args(["diff", "--cached", "--name-only"])
-        .output()
-        .map_err(|e| format!("Failed to run git: {}", e))?;
-
-    let file_count = String::from_utf8_lossy(&status.stdout)
-        .lines()
-        .filter(|l| !l.is_empty())
-        .count() as u32;
-
-    // Create a commit as checkpoint
-    let msg = format!("[checkpoint] {}", label);
-    let commit = Command::new("git")
-        .current_dir(&worktree_path)
-        .args(["commit", "--allow-empty", "-m", &msg])
-        .output()
-        .map_err(|e| format!("Failed to create checkpoint commit: {}", e))?;
+    let checkpoint = snapshot_checkpoint(&workspace_id, &worktree_path, &label, agent_id)?;
+    record_checkpoint(&app, &state, checkpoint.clone())?;
+    Ok(checkpoint)
+}
 
-    if !commit.status.success() {
-        let stderr = String::from_utf8_lossy(&commit.stderr);
-        return Err(format!("Checkpoint commit failed: {}", stderr));
-    }
+/// Commit a snapshot of the worktree under the workspace's checkpoint ref
+/// namespace and return its entry, without recording it in state.
+pub(crate) fn snapshot_checkpoint(
+    workspace_id: &str,
+    worktree_path: &str,
+    label: &str,
+    agent_id: Option<String>,
+) -> Result<Checkpoint, String> {
+    let repo = Repository::open(worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let tree_id = snapshot_tree(&repo)?;
+    commit_checkpoint(&repo, workspace_id, worktree_path, tree_id, label, agent_id)
+}
 
-    // Get the commit hash
-    let rev = Command::new("git")
-        .current_dir(&worktree_path)
-        .args(["rev-parse", "HEAD"])
-        .output()
-        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+/// Commit `tree_id` on top of HEAD and point a new checkpoint ref at it.
+fn commit_checkpoint(
+    repo: &Repository,
+    workspace_id: &str,
+    worktree_path: &str,
+    tree_id: Oid,
+    label: &str,
+    agent_id: Option<String>,
+) -> Result<Checkpoint, String> {
+    let head = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let tree = repo
+        .find_tree(tree_id)
+        .map_err(|e| format!("Failed to find snapshot tree: {}", e))?;
 
-    let git_ref = String::from_utf8_lossy(&rev.stdout).trim().to_string();
+    let head_tree = head.as_ref().and_then(|c| c.tree().ok());
+    let file_count = repo
+        .diff_tree_to_tree(head_tree.as_ref(), Some(&tree), None)
+        .map_err(|e| format!("Failed to diff snapshot: {}", e))?
+        .deltas()
+        .len() as u32;
 
-    let checkpoint = Checkpoint {
-        id: format!("cp-{}", &git_ref[..8]),
-        workspace_id,
-        label,
+    let sig = repo
+        .signature()
+        .or_else(|_| Signature::now("Heroi", "heroi@localhost"))
+        .map_err(|e| format!("Failed to create signature: {}", e))?;
+    let parents: Vec<&git2::Commit> = head.iter().collect();
+    let message = format!("[checkpoint] {}", label);
+    let commit_id = repo
+        .commit(None, &sig, &sig, &message, &tree, &parents)
+        .map_err(|e| format!("Failed to create checkpoint commit: {}", e))?;
+
+    let ref_name = create_checkpoint_ref(repo, workspace_id, commit_id, &message)?;
+    let git_ref = commit_id.to_string();
+
+    Ok(Checkpoint {
+        id: format!("cp-{}", uuid_v4()),
+        workspace_id: workspace_id.to_string(),
+        worktree_path: worktree_path.to_string(),
+        label: label.to_string(),
         git_ref,
+        ref_name: Some(ref_name),
         created_at: crate::commands::workspace_lifecycle::now_iso8601_pub(),
         file_count,
         agent_id,
+        auto: false,
+        system: false,
+    })
+}
+
+/// What prompted an automatic checkpoint.
+pub(crate) enum AutoTrigger<'a> {
+    Interval,
+    /// An agent terminal exited; carries the agent id
+    AgentExit(&'a str),
+    /// A script or pipeline is about to start; carries a description of it
+    BeforeRun(&'a str),
+}
+
+/// Checkpoint a workspace if its auto-checkpoint policy covers `trigger` and
+/// the working tree differs from both HEAD and the latest checkpoint. The
+/// workspace's checkpoints are pruned afterwards.
+pub(crate) fn auto_checkpoint(
+    app: &tauri::AppHandle,
+    workspace_id: &str,
+    trigger: AutoTrigger,
+) -> Result<Option<Checkpoint>, String> {
+    let state = app.state::<AppState>();
+    let (workspace, latest) = {
+        let data = state.0.lock().map_err(|e| e.to_string())?;
+        let Some(workspace) = data
+            .workspaces
+            .iter()
+            .find(|w| w.id == workspace_id && w.status == WorkspaceStatus::Active)
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        let latest = data
+            .checkpoints
+            .iter()
+            .rev()
+            .find(|c| c.workspace_id == workspace_id)
+            .map(|c| c.git_ref.clone());
+        (workspace, latest)
+    };
+
+    let policy = &workspace.auto_checkpoints;
+    let enabled = match trigger {
+        AutoTrigger::Interval => policy.interval_minutes.is_some_and(|m| m > 0),
+        AutoTrigger::AgentExit(_) => policy.on_agent_exit,
+        AutoTrigger::BeforeRun(_) => policy.before_script,
     };
+    if !enabled || policy.max_checkpoints == 0 {
+        return Ok(None);
+    }
+
+    let repo = Repository::open(&workspace.worktree_path)
+        .map_err(|e| format!("Failed to open repo: {}", e))?;
+    let tree_id = snapshot_tree(&repo)?;
+    let head_tree = repo.head().and_then(|h| h.peel_to_tree()).ok();
+    let latest_tree =
+        latest.and_then(|r| repo.revparse_single(&r).and_then(|o| o.peel_to_tree()).ok());
+    if [head_tree, latest_tree].iter().flatten().any(|t| t.id() == tree_id) {
+        return Ok(None);
+    }
+
+    let (label, agent_id) = match trigger {
+        AutoTrigger::Interval => ("Auto-save".to_string(), None),
+        AutoTrigger::AgentExit(agent) => {
+            (format!("After {} exited", agent), Some(agent.to_string()))
+        }
+        AutoTrigger::BeforeRun(what) => (format!("Before running {}", what), None),
+    };
+    let mut checkpoint = commit_checkpoint(
+        &repo,
+        workspace_id,
+        &workspace.worktree_path,
+        tree_id,
+        &label,
+        agent_id,
+    )?;
+    checkpoint.auto = true;
+    record_checkpoint(app, &state, checkpoint.clone())?;
+    prune_workspace_checkpoints(app, &state, workspace_id)?;
 
+    Ok(Some(checkpoint))
+}
+
+/// Check `interval_minutes` policies in the background. A workspace's first
+/// interval starts counting when the app starts or the policy is first seen.
+pub fn start_auto_checkpoints(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_run: HashMap<String, Instant> = HashMap::new();
+        loop {
+            std::thread::sleep(AUTO_CHECKPOINT_TICK);
+
+            let state = app.state::<AppState>();
+            let intervals: Vec<(String, u32)> = match state.0.lock() {
+                Ok(data) => data
+                    .workspaces
+                    .iter()
+                    .filter(|w| w.status == WorkspaceStatus::Active)
+                    .filter_map(|w| Some((w.id.clone(), w.auto_checkpoints.interval_minutes?)))
+                    .filter(|(_, minutes)| *minutes > 0)
+                    .collect(),
+                Err(_) => continue,
+            };
+            last_run.retain(|id, _| intervals.iter().any(|(w, _)| w == id));
+
+            for (workspace_id, minutes) in intervals {
+                let since = *last_run
+                    .entry(workspace_id.clone())
+                    .or_insert_with(Instant::now);
+                if since.elapsed() < Duration::from_secs(u64::from(minutes) * 60) {
+                    continue;
+                }
+                last_run.insert(workspace_id.clone(), Instant::now());
+                if let Err(e) = auto_checkpoint(&app, &workspace_id, AutoTrigger::Interval) {
+                    eprintln!("Auto checkpoint of workspace '{}' failed: {}", workspace_id, e);
+                }
+            }
+        }
+    });
+}
+
+/// Change a workspace's auto-checkpoint policy. A lower `max_checkpoints`
+/// prunes existing automatic checkpoints right away.
+#[tauri::command]
+pub fn set_auto_checkpoint_policy(
+    workspace_id: String,
+    policy: AutoCheckpointPolicy,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
     let mut data = state.0.lock().map_err(|e| e.to_string())?;
-    data.checkpoints.push(checkpoint.clone());
+    let workspace = data
+        .workspaces
+        .iter_mut()
+        .find(|w| w.id == workspace_id)
+        .ok_or_else(|| format!("Workspace '{}' not found", workspace_id))?;
+    workspace.auto_checkpoints = policy;
+    let workspaces = data.workspaces.clone();
+    drop(data);
+
+    persist_workspaces(&app, &workspaces)?;
+    prune_workspace_checkpoints(&app, &state, &workspace_id).map(|_| ())
+}
+
+/// Apply a workspace's retention policy, plus the `max_checkpoints` limit on
+/// automatic checkpoints, deleting the refs of pruned checkpoints. Returns
+/// the pruned entries.
+fn prune_workspace_checkpoints(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    workspace_id: &str,
+) -> Result<Vec<Checkpoint>, String> {
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    let workspace = data
+        .workspaces
+        .iter()
+        .find(|w| w.id == workspace_id)
+        .ok_or_else(|| format!("Workspace '{}' not found", workspace_id))?;
+    let pruned_ids = checkpoints_to_prune(
+        data.checkpoints.iter().filter(|c| c.workspace_id == workspace_id),
+        &workspace.checkpoint_retention,
+        workspace.auto_checkpoints.max_checkpoints,
+    );
+    if pruned_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (pruned, kept): (Vec<Checkpoint>, Vec<Checkpoint>) = data
+        .checkpoints
+        .drain(..)
+        .partition(|c| pruned_ids.contains(&c.id));
+    data.checkpoints = kept;
+    let checkpoints = data.checkpoints.clone();
+    drop(data);
+
+    for cp in &pruned {
+        if let Err(e) = delete_checkpoint_ref(cp) {
+            eprintln!("Failed to delete checkpoint '{}': {}", cp.id, e);
+        }
+    }
+    persist_checkpoints(app, &checkpoints)?;
+    Ok(pruned)
+}
+
+/// Ids of the checkpoints (one workspace's, oldest first) that neither a
+/// retention rule keeps nor fit within `max_auto` automatic checkpoints.
+fn checkpoints_to_prune<'a>(
+    checkpoints: impl DoubleEndedIterator<Item = &'a Checkpoint>,
+    retention: &CheckpointRetention,
+    max_auto: u32,
+) -> HashSet<String> {
+    let hourly_cutoff = iso8601_secs_ago(u64::from(retention.hourly_for_hours) * 3600);
+    let daily_cutoff = iso8601_secs_ago(u64::from(retention.daily_for_days) * 86400);
+    let mut hours = HashSet::new();
+    let mut days = HashSet::new();
+    let mut auto_kept = 0;
+    let mut pruned = HashSet::new();
+
+    // Newest first, so each hour and day keeps its latest checkpoint
+    for (i, cp) in checkpoints.rev().enumerate() {
+        let created = cp.created_at.as_str();
+        let recent = i < retention.keep_last as usize;
+        let hourly = created >= hourly_cutoff.as_str()
+            && hours.insert(created.get(..13).unwrap_or(created));
+        let daily = created >= daily_cutoff.as_str()
+            && days.insert(created.get(..10).unwrap_or(created));
+        let manual = retention.keep_manual && !cp.auto && !cp.system;
+
+        let mut keep = recent || hourly || daily || manual;
+        if keep && cp.auto {
+            auto_kept += 1;
+            keep = auto_kept <= max_auto;
+        }
+        if !keep {
+            pruned.insert(cp.id.clone());
+        }
+    }
+    pruned
+}
 
+/// Apply the workspace's retention policy now and return the checkpoints that
+/// were deleted. Unreachable snapshot objects are left to `git gc`, which is
+/// given a chance to run.
+#[tauri::command]
+pub fn prune_checkpoints(
+    workspace_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<Checkpoint>, String> {
+    let pruned = prune_workspace_checkpoints(&app, &state, &workspace_id)?;
+    if let Some(cp) = pruned.first() {
+        let _ = Command::new("git")
+            .current_dir(&cp.worktree_path)
+            .args(["gc", "--auto", "--quiet"])
+            .output();
+    }
+    Ok(pruned)
+}
+
+/// Change a workspace's checkpoint retention policy and prune right away.
+#[tauri::command]
+pub fn set_checkpoint_retention(
+    workspace_id: String,
+    retention: CheckpointRetention,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<Checkpoint>, String> {
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    let workspace = data
+        .workspaces
+        .iter_mut()
+        .find(|w| w.id == workspace_id)
+        .ok_or_else(|| format!("Workspace '{}' not found", workspace_id))?;
+    workspace.checkpoint_retention = retention;
+    let workspaces = data.workspaces.clone();
+    drop(data);
+
+    persist_workspaces(&app, &workspaces)?;
+    prune_workspace_checkpoints(&app, &state, &workspace_id)
+}
+
+/// Delete all of a workspace's checkpoints and every ref under its checkpoint
+/// namespace, including refs no longer tracked in state.
+pub(crate) fn delete_workspace_checkpoints(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    workspace: &WorkspaceConfig,
+) -> Result<(), String> {
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    data.checkpoints.retain(|c| c.workspace_id != workspace.id);
+    let checkpoints = data.checkpoints.clone();
+    drop(data);
+    persist_checkpoints(app, &checkpoints)?;
+
+    let repo = Repository::open(&workspace.repo_path)
+        .map_err(|e| format!("Failed to open repo: {}", e))?;
+    let glob = format!("{}/{}/*", CHECKPOINT_REF_PREFIX, workspace.id);
+    let names: Vec<String> = repo
+        .references_glob(&glob)
+        .map_err(|e| format!("Failed to list checkpoint refs: {}", e))?
+        .flatten()
+        .filter_map(|r| r.name().map(String::from))
+        .collect();
+    for name in names {
+        if let Ok(mut reference) = repo.find_reference(&name) {
+            reference
+                .delete()
+                .map_err(|e| format!("Failed to delete checkpoint ref: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete the ref keeping a checkpoint's snapshot alive, if it has one.
+fn delete_checkpoint_ref(checkpoint: &Checkpoint) -> Result<(), String> {
+    let Some(ref_name) = &checkpoint.ref_name else {
+        return Ok(());
+    };
+    let repo = Repository::open(&checkpoint.worktree_path)
+        .map_err(|e| format!("Failed to open repo: {}", e))?;
+    let mut reference = match repo.find_reference(ref_name) {
+        Ok(reference) => reference,
+        Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Failed to find checkpoint ref: {}", e)),
+    };
+    reference
+        .delete()
+        .map_err(|e| format!("Failed to delete checkpoint ref: {}", e))
+}
+
+/// Checkpoint the workspace using `worktree_path` before a destructive git
+/// operation, so the operation can be undone by restoring it. Fails if no
+/// workspace uses the worktree.
+pub(crate) fn safety_checkpoint(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    worktree_path: &str,
+    label: &str,
+) -> Result<Checkpoint, String> {
+    let workspace_id = {
+        let data = state.0.lock().map_err(|e| e.to_string())?;
+        data.workspaces
+            .iter()
+            .find(|w| Path::new(&w.worktree_path) == Path::new(worktree_path))
+            .map(|w| w.id.clone())
+            .ok_or_else(|| {
+                format!(
+                    "No workspace uses {}; not discarding without a checkpoint",
+                    worktree_path
+                )
+            })?
+    };
+    let mut checkpoint = snapshot_checkpoint(&workspace_id, worktree_path, label, None)?;
+    checkpoint.system = true;
+    record_checkpoint(app, state, checkpoint.clone())?;
+    prune_workspace_checkpoints(app, state, &workspace_id)?;
     Ok(checkpoint)
 }
 
+/// Add a checkpoint to state and persist the list.
+pub(crate) fn record_checkpoint(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    checkpoint: Checkpoint,
+) -> Result<(), String> {
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    data.checkpoints.push(checkpoint);
+    let checkpoints = data.checkpoints.clone();
+    drop(data);
+
+    persist_checkpoints(app, &checkpoints)
+}
+
+/// Write a tree of the whole working directory as `git add -A` would stage
+/// it. The repository's index is only modified in memory and reloaded from
+/// disk afterwards, so the user's staging state survives.
+fn snapshot_tree(repo: &Repository) -> Result<Oid, String> {
+    let mut index = repo.index().map_err(|e| format!("Failed to read index: {}", e))?;
+
+    let result = index
+        .add_all(["*"], IndexAddOption::DEFAULT, None)
+        .and_then(|_| index.update_all(["*"], None))
+        .and_then(|_| index.write_tree());
+
+    // Throw away the in-memory changes
+    index
+        .read(true)
+        .map_err(|e| format!("Failed to reload index: {}", e))?;
+
+    result.map_err(|e| format!("Failed to snapshot working tree: {}", e))
+}
+
+/// Point the next free `refs/heroi/checkpoints/<workspace>/<n>` at `commit_id`.
+fn create_checkpoint_ref(
+    repo: &Repository,
+    workspace_id: &str,
+    commit_id: Oid,
+    message: &str,
+) -> Result<String, String> {
+    let prefix = format!("{}/{}/", CHECKPOINT_REF_PREFIX, workspace_id);
+    let mut next = repo
+        .references_glob(&format!("{}*", prefix))
+        .map_err(|e| format!("Failed to list checkpoint refs: {}", e))?
+        .flatten()
+        .filter_map(|r| r.name()?.strip_prefix(&prefix)?.parse::<u64>().ok())
+        .max()
+        .map_or(1, |n| n + 1);
+
+    // Another checkpoint may claim the same number concurrently
+    loop {
+        let name = format!("{}{}", prefix, next);
+        match repo.reference(&name, commit_id, false, message) {
+            Ok(_) => return Ok(name),
+            Err(e) if e.code() == git2::ErrorCode::Exists => next += 1,
+            Err(e) => return Err(format!("Failed to create checkpoint ref: {}", e)),
+        }
+    }
+}
+
 /// List checkpoints for a workspace.
 #[tauri::command]
 pub fn list_checkpoints(
@@ -86,55 +513,668 @@ pub fn list_checkpoints(
     Ok(cps)
 }
 
-/// Restore to a checkpoint (git reset).
-#[tauri::command]
+/// Restore a checkpoint in `mode` (see `RestoreMode`). The current state is
+/// first saved as a "pre-restore" checkpoint, which is returned so the
+/// restore can be undone. Refused while a script or agent is running in the
+/// workspace unless `force` is set.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
 pub fn restore_checkpoint(
+    workspace_id: String,
     worktree_path: String,
     git_ref: String,
+    mode: Option<RestoreMode>,
+    paths: Option<Vec<String>>,
+    force: Option<bool>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Checkpoint, String> {
+    let mode = mode.unwrap_or_default();
+    let paths = paths.unwrap_or_default();
+    if mode == RestoreMode::Paths && paths.is_empty() {
+        return Err("No paths given to restore".into());
+    }
+
+    if !force.unwrap_or(false) {
+        ensure_workspace_idle(&state, &workspace_id)?;
+    }
+
+    let repo = Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let snapshot = repo
+        .revparse_single(&git_ref)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| format!("Checkpoint '{}' not found: {}", git_ref, e))?;
+
+    let short: String = snapshot.id().to_string().chars().take(8).collect();
+    let mut safety = snapshot_checkpoint(
+        &workspace_id,
+        &worktree_path,
+        &format!("Before restoring {}", short),
+        None,
+    )?;
+    safety.system = true;
+    record_checkpoint(&app, &state, safety.clone())?;
+
+    restore_snapshot(&repo, &snapshot, mode, &paths)?;
+    prune_workspace_checkpoints(&app, &state, &workspace_id)?;
+    Ok(safety)
+}
+
+/// Bring the worktree back to `snapshot` in `mode`.
+fn restore_snapshot(
+    repo: &Repository,
+    snapshot: &git2::Commit,
+    mode: RestoreMode,
+    paths: &[String],
 ) -> Result<(), String> {
-    let output = Command::new("git")
-        .current_dir(&worktree_path)
-        .args(["reset", "--hard", &git_ref])
-        .output()
-        .map_err(|e| format!("Failed to restore checkpoint: {}", e))?;
+    match mode {
+        RestoreMode::WorkingTree => checkout_snapshot(repo, snapshot, &[], false),
+        RestoreMode::Paths => checkout_snapshot(repo, snapshot, paths, false),
+        RestoreMode::Hard => {
+            // Snapshot commits hang off the HEAD they were taken from, so go
+            // back to their parent; legacy checkpoints were committed onto the
+            // branch itself and are their own base.
+            let head_id = repo.head().ok().and_then(|h| h.target());
+            let on_branch = head_id.is_some_and(|head| {
+                head == snapshot.id()
+                    || repo.graph_descendant_of(head, snapshot.id()).unwrap_or(false)
+            });
+            let base = if on_branch {
+                Some(snapshot.clone())
+            } else {
+                snapshot.parent(0).ok()
+            };
+            if let Some(base) = base {
+                repo.reset(base.as_object(), ResetType::Hard, None)
+                    .map_err(|e| format!("Failed to reset branch: {}", e))?;
+            }
+            checkout_snapshot(repo, snapshot, &[], true)
+        }
+    }
+}
+
+/// Error if a script or agent is still running in the workspace; restoring
+/// underneath them would race with their writes.
+fn ensure_workspace_idle(state: &AppState, workspace_id: &str) -> Result<(), String> {
+    let data = state.0.lock().map_err(|e| e.to_string())?;
+    let mut busy: Vec<String> = data
+        .running_processes
+        .iter()
+        .filter(|p| p.workspace_id == workspace_id && p.status == ProcessStatus::Running)
+        .map(|p| format!("script '{}'", p.script_name))
+        .collect();
+    busy.extend(
+        data.agent_sessions
+            .iter()
+            .filter(|s| s.workspace_id == workspace_id)
+            .map(|s| format!("agent '{}'", s.agent_id)),
+    );
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Restore failed: {}", stderr));
+    if busy.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Workspace is busy ({}); stop them or force the restore",
+            busy.join(", ")
+        ))
     }
+}
 
-    Ok(())
+/// Write `snapshot`'s files (or just `paths`) into the working tree without
+/// touching the index, so staged changes survive and the restored files show
+/// up as unstaged changes. With `remove_untracked`, untracked files missing
+/// from the snapshot are deleted.
+pub(crate) fn checkout_snapshot(
+    repo: &Repository,
+    snapshot: &git2::Commit,
+    paths: &[String],
+    remove_untracked: bool,
+) -> Result<(), String> {
+    let tree = snapshot
+        .tree()
+        .map_err(|e| format!("Failed to read checkpoint tree: {}", e))?;
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout
+        .force()
+        .remove_untracked(remove_untracked)
+        .update_index(false);
+    for path in paths {
+        checkout.path(path);
+    }
+    repo.checkout_tree(tree.as_object(), Some(&mut checkout))
+        .map_err(|e| format!("Restore failed: {}", e))
 }
 
-/// Delete a checkpoint from state.
+/// Delete a checkpoint and the ref keeping its snapshot alive.
 #[tauri::command]
 pub fn delete_checkpoint(
     checkpoint_id: String,
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
     let mut data = state.0.lock().map_err(|e| e.to_string())?;
-    data.checkpoints.retain(|c| c.id != checkpoint_id);
+    remove_checkpoint(&mut data.checkpoints, &checkpoint_id)?;
+    let checkpoints = data.checkpoints.clone();
+    drop(data);
+
+    persist_checkpoints(&app, &checkpoints)
+}
+
+/// Drop the checkpoint with `checkpoint_id` from `checkpoints` and delete its
+/// ref.
+fn remove_checkpoint(
+    checkpoints: &mut Vec<Checkpoint>,
+    checkpoint_id: &str,
+) -> Result<(), String> {
+    if let Some(cp) = checkpoints.iter().find(|c| c.id == checkpoint_id) {
+        delete_checkpoint_ref(cp)?;
+    }
+    checkpoints.retain(|c| c.id != checkpoint_id);
     Ok(())
 }
 
-/// Get diff between two checkpoints or between checkpoint and current.
-#[tauri::command]
+/// Per-file diff between two checkpoints, or between a checkpoint and the
+/// current working tree (untracked files included, as in checkpoints).
+/// Renames are detected.
+#[tauri::command(async)]
 pub fn diff_checkpoint(
     worktree_path: String,
     from_ref: String,
     to_ref: Option<String>,
-) -> Result<String, String> {
-    let mut cmd = Command::new("git");
-    cmd.current_dir(&worktree_path);
-    cmd.args(["diff", &from_ref]);
+) -> Result<Vec<FileDiff>, String> {
+    let repo = Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let from_tree = checkpoint_tree(&repo, &from_ref)?;
+    let to_tree = match to_ref {
+        Some(to_ref) => checkpoint_tree(&repo, &to_ref)?,
+        None => {
+            let tree_id = snapshot_tree(&repo)?;
+            repo.find_tree(tree_id)
+                .map_err(|e| format!("Failed to find snapshot tree: {}", e))?
+        }
+    };
+
+    let mut diff = repo
+        .diff_tree_to_tree(Some(&from_tree), Some(&to_tree), None)
+        .map_err(|e| format!("Failed to diff checkpoint: {}", e))?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+        .map_err(|e| format!("Failed to detect renames: {}", e))?;
+    file_diffs(&diff)
+}
+
+fn checkpoint_tree<'r>(repo: &'r Repository, git_ref: &str) -> Result<git2::Tree<'r>, String> {
+    repo.revparse_single(git_ref)
+        .and_then(|obj| obj.peel_to_tree())
+        .map_err(|e| format!("Checkpoint '{}' not found: {}", git_ref, e))
+}
+
+/// Bring individual files back from a checkpoint into the workspace's
+/// working tree; shorthand for `restore_checkpoint` in `Paths` mode.
+#[tauri::command(async)]
+pub fn restore_checkpoint_paths(
+    workspace_id: String,
+    git_ref: String,
+    paths: Vec<String>,
+    force: Option<bool>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Checkpoint, String> {
+    let worktree_path = {
+        let data = state.0.lock().map_err(|e| e.to_string())?;
+        data.workspaces
+            .iter()
+            .find(|w| w.id == workspace_id)
+            .map(|w| w.worktree_path.clone())
+            .ok_or_else(|| format!("Workspace '{}' not found", workspace_id))?
+    };
+    restore_checkpoint(
+        workspace_id,
+        worktree_path,
+        git_ref,
+        Some(RestoreMode::Paths),
+        Some(paths),
+        force,
+        app,
+        state,
+    )
+}
+
+/// Load persisted checkpoints, dropping any whose `git_ref` no longer
+/// resolves in its worktree. Workspace configs must already be loaded so
+/// older entries without a `worktree_path` can be matched to theirs.
+pub fn load_checkpoints(app: &tauri::AppHandle, state: &AppState) -> Result<(), String> {
+    let store = app
+        .store("heroi-store.json")
+        .map_err(|e| e.to_string())?;
+    let Some(val) = store.get("checkpoints") else {
+        return Ok(());
+    };
+    let stored: Vec<Checkpoint> = serde_json::from_value(val.clone()).map_err(|e| e.to_string())?;
+
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    let total = stored.len();
+    let mut checkpoints = Vec::new();
+    for mut cp in stored {
+        if cp.worktree_path.is_empty() {
+            match data.workspaces.iter().find(|w| w.id == cp.workspace_id) {
+                Some(w) => cp.worktree_path = w.worktree_path.clone(),
+                None => continue,
+            }
+        }
+        let git_ref = cp.ref_name.as_deref().unwrap_or(&cp.git_ref);
+        if ref_resolves(&cp.worktree_path, git_ref) {
+            checkpoints.push(cp);
+        }
+    }
+    data.checkpoints = checkpoints.clone();
+    drop(data);
+
+    if checkpoints.len() != total {
+        persist_checkpoints(app, &checkpoints)?;
+    }
+    Ok(())
+}
+
+fn ref_resolves(worktree_path: &str, git_ref: &str) -> bool {
+    Repository::open(worktree_path)
+        .map(|repo| repo.revparse_single(git_ref).is_ok())
+        .unwrap_or(false)
+}
+
+fn persist_checkpoints(app: &tauri::AppHandle, checkpoints: &[Checkpoint]) -> Result<(), String> {
+    let store = app
+        .store("heroi-store.json")
+        .map_err(|e| e.to_string())?;
+    store.set(
+        "checkpoints",
+        serde_json::to_value(checkpoints).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A throwaway repository with `a.txt` and `b.txt` committed as "one".
+    struct TempRepo {
+        dir: PathBuf,
+        repo: Repository,
+    }
+
+    impl TempRepo {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "heroi-checkpoint-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let repo = Repository::init(&dir).unwrap();
+            let temp = TempRepo { dir, repo };
+            temp.write("a.txt", "one\n");
+            temp.write("b.txt", "one\n");
+            temp.stage("a.txt");
+            temp.stage("b.txt");
+            {
+                let mut index = temp.repo.index().unwrap();
+                let tree = temp.repo.find_tree(index.write_tree().unwrap()).unwrap();
+                let sig = Signature::now("Test", "test@example.com").unwrap();
+                temp.repo
+                    .commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+                    .unwrap();
+            }
+            temp
+        }
+
+        fn path(&self) -> &str {
+            self.dir.to_str().unwrap()
+        }
+
+        fn write(&self, file: &str, content: &str) {
+            fs::write(self.dir.join(file), content).unwrap();
+        }
 
-    if let Some(ref to) = to_ref {
-        cmd.arg(to);
+        fn read(&self, file: &str) -> String {
+            fs::read_to_string(self.dir.join(file)).unwrap()
+        }
+
+        fn stage(&self, file: &str) {
+            let mut index = self.repo.index().unwrap();
+            index.add_path(Path::new(file)).unwrap();
+            index.write().unwrap();
+        }
+
+        /// Content of `file` in the index as written to disk.
+        fn staged(&self, file: &str) -> String {
+            let mut index = self.repo.index().unwrap();
+            index.read(true).unwrap();
+            let entry = index.get_path(Path::new(file), 0).unwrap();
+            let blob = self.repo.find_blob(entry.id).unwrap();
+            String::from_utf8(blob.content().to_vec()).unwrap()
+        }
+
+        fn snapshot(&self, label: &str) -> Checkpoint {
+            snapshot_checkpoint("ws", self.path(), label, None).unwrap()
+        }
+
+        fn restore(&self, checkpoint: &Checkpoint, paths: &[String], remove_untracked: bool) {
+            let commit = self
+                .repo
+                .revparse_single(&checkpoint.git_ref)
+                .unwrap()
+                .peel_to_commit()
+                .unwrap();
+            checkout_snapshot(&self.repo, &commit, paths, remove_untracked).unwrap();
+        }
+    }
+
+    impl Drop for TempRepo {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[test]
+    fn working_tree_restore_keeps_staged_changes() {
+        let temp = TempRepo::new("staged");
+        temp.write("a.txt", "two\n");
+        let checkpoint = temp.snapshot("two");
+
+        temp.write("a.txt", "three\n");
+        temp.write("b.txt", "staged\n");
+        temp.stage("b.txt");
+
+        temp.restore(&checkpoint, &[], false);
+        assert_eq!(temp.read("a.txt"), "two\n");
+        assert_eq!(temp.staged("a.txt"), "one\n");
+        assert_eq!(temp.staged("b.txt"), "staged\n");
+    }
+
+    #[test]
+    fn path_restore_only_touches_the_given_paths() {
+        let temp = TempRepo::new("paths");
+        temp.write("a.txt", "two\n");
+        temp.write("b.txt", "two\n");
+        let checkpoint = temp.snapshot("two");
+
+        temp.write("a.txt", "three\n");
+        temp.write("b.txt", "three\n");
+        temp.stage("b.txt");
+
+        temp.restore(&checkpoint, &["a.txt".to_string()], false);
+        assert_eq!(temp.read("a.txt"), "two\n");
+        assert_eq!(temp.read("b.txt"), "three\n");
+        assert_eq!(temp.staged("b.txt"), "three\n");
+    }
+
+    fn head_id(temp: &TempRepo) -> Oid {
+        temp.repo.head().unwrap().target().unwrap()
+    }
+
+    #[test]
+    fn snapshot_keeps_untracked_files_and_leaves_head_alone() {
+        let temp = TempRepo::new("create");
+        let head = head_id(&temp);
+        temp.write("a.txt", "two\n");
+        temp.write("new.txt", "new\n");
+        let checkpoint = temp.snapshot("two");
+
+        assert_eq!(head_id(&temp), head);
+        assert_eq!(temp.staged("a.txt"), "one\n");
+        assert_eq!(checkpoint.file_count, 2);
+        let tree = temp
+            .repo
+            .find_reference(checkpoint.ref_name.as_deref().unwrap())
+            .unwrap()
+            .peel_to_tree()
+            .unwrap();
+        assert!(tree.get_path(Path::new("new.txt")).is_ok());
+    }
+
+    #[test]
+    fn identical_snapshots_get_their_own_id_and_ref() {
+        let temp = TempRepo::new("unique");
+        let first = temp.snapshot("same");
+        let second = temp.snapshot("same");
+
+        assert_ne!(first.id, second.id);
+        assert_ne!(first.ref_name, second.ref_name);
+    }
+
+    #[test]
+    fn hard_restore_moves_the_branch_back_and_drops_untracked_files() {
+        let temp = TempRepo::new("hard");
+        let base = head_id(&temp);
+        temp.write("a.txt", "two\n");
+        let checkpoint = temp.snapshot("two");
+
+        temp.write("a.txt", "three\n");
+        temp.stage("a.txt");
+        {
+            let mut index = temp.repo.index().unwrap();
+            let tree = temp.repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = temp.repo.find_commit(base).unwrap();
+            let sig = Signature::now("Test", "test@example.com").unwrap();
+            temp.repo
+                .commit(Some("HEAD"), &sig, &sig, "three", &tree, &[&parent])
+                .unwrap();
+        }
+        temp.write("new.txt", "new\n");
+
+        let snapshot = temp
+            .repo
+            .revparse_single(&checkpoint.git_ref)
+            .unwrap()
+            .peel_to_commit()
+            .unwrap();
+        restore_snapshot(&temp.repo, &snapshot, RestoreMode::Hard, &[]).unwrap();
+        assert_eq!(head_id(&temp), base);
+        assert_eq!(temp.read("a.txt"), "two\n");
+        assert!(!temp.dir.join("new.txt").exists());
+    }
+
+    #[test]
+    fn delete_removes_only_the_given_checkpoint_and_its_ref() {
+        let temp = TempRepo::new("delete");
+        let first = temp.snapshot("same");
+        let second = temp.snapshot("same");
+        let mut checkpoints = vec![first.clone(), second.clone()];
+
+        remove_checkpoint(&mut checkpoints, &first.id).unwrap();
+        assert_eq!(checkpoints.len(), 1);
+        assert_eq!(checkpoints[0].id, second.id);
+        assert!(temp
+            .repo
+            .find_reference(first.ref_name.as_deref().unwrap())
+            .is_err());
+        assert!(temp
+            .repo
+            .find_reference(second.ref_name.as_deref().unwrap())
+            .is_ok());
+
+        // Deleting again is a no-op
+        remove_checkpoint(&mut checkpoints, &first.id).unwrap();
+        assert_eq!(checkpoints.len(), 1);
+    }
+
+    /// Timestamp `minute` minutes into the `ago`-th `period` (in seconds)
+    /// before the current one, so bucket boundaries don't depend on the
+    /// time the test runs.
+    fn in_period(period: u64, ago: u64, minute: u64) -> String {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        iso8601_secs_ago(now % period + ago * period - minute * 60)
+    }
+
+    fn entry(id: &str, created_at: String, auto: bool, system: bool) -> Checkpoint {
+        Checkpoint {
+            id: id.to_string(),
+            workspace_id: "ws".to_string(),
+            worktree_path: String::new(),
+            label: id.to_string(),
+            git_ref: String::new(),
+            ref_name: None,
+            created_at,
+            file_count: 0,
+            agent_id: None,
+            auto,
+            system,
+        }
     }
 
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to diff: {}", e))?;
+    fn manual(id: &str, created_at: String) -> Checkpoint {
+        entry(id, created_at, false, false)
+    }
+
+    fn auto(id: &str, created_at: String) -> Checkpoint {
+        entry(id, created_at, true, false)
+    }
+
+    fn system(id: &str, created_at: String) -> Checkpoint {
+        entry(id, created_at, false, true)
+    }
+
+    /// Only the rules given are on.
+    fn retention(
+        keep_last: u32,
+        hourly: u32,
+        daily: u32,
+        keep_manual: bool,
+    ) -> CheckpointRetention {
+        CheckpointRetention {
+            keep_last,
+            hourly_for_hours: hourly,
+            daily_for_days: daily,
+            keep_manual,
+        }
+    }
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    fn pruned(
+        checkpoints: &[Checkpoint],
+        retention: &CheckpointRetention,
+        max_auto: u32,
+    ) -> Vec<String> {
+        let mut ids: Vec<String> = checkpoints_to_prune(checkpoints.iter(), retention, max_auto)
+            .into_iter()
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn prune_keeps_the_most_recent() {
+        let old = |minute| in_period(86400, 30, minute);
+        let checkpoints = [
+            manual("a", old(10)),
+            manual("b", old(20)),
+            manual("c", old(30)),
+            manual("d", old(40)),
+        ];
+        assert_eq!(
+            pruned(&checkpoints, &retention(2, 0, 0, false), 100),
+            ["a", "b"]
+        );
+        assert!(pruned(&checkpoints, &retention(4, 0, 0, false), 100).is_empty());
+    }
+
+    #[test]
+    fn prune_keeps_the_newest_of_each_recent_hour() {
+        let checkpoints = [
+            manual("day-old", in_period(3600, 30, 10)),
+            manual("h3", in_period(3600, 3, 10)),
+            manual("h2-a", in_period(3600, 2, 10)),
+            manual("h2-b", in_period(3600, 2, 20)),
+            manual("h2-c", in_period(3600, 2, 30)),
+        ];
+        assert_eq!(
+            pruned(&checkpoints, &retention(0, 24, 0, false), 100),
+            ["day-old", "h2-a", "h2-b"]
+        );
+    }
+
+    #[test]
+    fn prune_keeps_the_newest_of_each_recent_day() {
+        let checkpoints = [
+            manual("old", in_period(86400, 10, 60)),
+            manual("d3", in_period(86400, 3, 60)),
+            manual("d2-a", in_period(86400, 2, 60)),
+            manual("d2-b", in_period(86400, 2, 120)),
+        ];
+        assert_eq!(
+            pruned(&checkpoints, &retention(0, 0, 7, false), 100),
+            ["d2-a", "old"]
+        );
+    }
+
+    #[test]
+    fn keep_manual_spares_manual_but_not_auto_or_system_entries() {
+        let old = |minute| in_period(86400, 30, minute);
+        let checkpoints = [
+            manual("manual", old(10)),
+            auto("auto", old(20)),
+            system("system", old(30)),
+        ];
+        assert_eq!(
+            pruned(&checkpoints, &retention(0, 0, 0, true), 100),
+            ["auto", "system"]
+        );
+        assert_eq!(
+            pruned(&checkpoints, &retention(0, 0, 0, false), 100),
+            ["auto", "manual", "system"]
+        );
+        // System entries are still kept by the recency rules
+        assert_eq!(
+            pruned(&checkpoints, &retention(1, 0, 0, true), 100),
+            ["auto"]
+        );
+    }
+
+    #[test]
+    fn max_auto_caps_kept_auto_checkpoints_only() {
+        let old = |minute| in_period(86400, 30, minute);
+        let checkpoints = [
+            auto("auto-1", old(10)),
+            manual("manual-1", old(20)),
+            auto("auto-2", old(30)),
+            system("system-1", old(40)),
+            auto("auto-3", old(50)),
+            manual("manual-2", old(60)),
+        ];
+        assert_eq!(
+            pruned(&checkpoints, &retention(10, 0, 0, false), 2),
+            ["auto-1"]
+        );
+        assert_eq!(
+            pruned(&checkpoints, &retention(10, 0, 0, false), 0),
+            ["auto-1", "auto-2", "auto-3"]
+        );
+    }
+
+    #[test]
+    fn prune_keeps_what_any_rule_keeps() {
+        let checkpoints = [
+            manual("manual-old", in_period(86400, 30, 10)),
+            auto("auto-old", in_period(86400, 30, 20)),
+            auto("day", in_period(86400, 3, 60)),
+            auto("hour-a", in_period(3600, 2, 10)),
+            auto("hour-b", in_period(3600, 2, 20)),
+            system("latest", in_period(3600, 1, 10)),
+        ];
+        assert_eq!(
+            pruned(&checkpoints, &retention(1, 24, 7, true), 100),
+            ["auto-old", "hour-a"]
+        );
+        // The cap applies to auto checkpoints kept by any rule, newest first
+        assert_eq!(
+            pruned(&checkpoints, &retention(1, 24, 7, true), 1),
+            ["auto-old", "day", "hour-a"]
+        );
+    }
 }
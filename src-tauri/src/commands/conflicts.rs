@@ -0,0 +1,524 @@
+use std::fs;
+use std::path::Path;
+
+use git2::{IndexConflict, IndexEntry, Oid, Repository};
+
+use crate::models::git::{
+    ConflictEntry, ConflictFile, ConflictRegion, ConflictResolution, ConflictStage,
+};
+
+/// Conflicted paths in the index, with their base, ours and theirs stages.
+#[tauri::command]
+pub fn git_list_conflicts(worktree_path: String) -> Result<Vec<ConflictEntry>, String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    list_conflicts(&repo)
+}
+
+/// The stages of a conflicted file and its working tree copy split into
+/// common and conflicting regions.
+#[tauri::command]
+pub fn git_conflict_details(
+    worktree_path: String,
+    file_path: String,
+) -> Result<ConflictFile, String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let entry = find_conflict(&repo, &file_path)?;
+
+    let mut binary = false;
+    let mut text_of = |stage: &Option<ConflictStage>| -> Result<Option<String>, String> {
+        let Some(content) = stage_content(&repo, stage)? else {
+            return Ok(None);
+        };
+        if content.contains(&0) {
+            binary = true;
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8_lossy(&content).into_owned()))
+    };
+    let ancestor_text = text_of(&entry.ancestor)?;
+    let ours_text = text_of(&entry.ours)?;
+    let theirs_text = text_of(&entry.theirs)?;
+
+    let regions = if binary {
+        Vec::new()
+    } else {
+        read_text(&Path::new(&worktree_path).join(&file_path))
+            .map(|text| parse_conflict_regions(&text))
+            .unwrap_or_default()
+    };
+
+    Ok(ConflictFile {
+        entry,
+        binary,
+        ancestor_text,
+        ours_text,
+        theirs_text,
+        regions,
+    })
+}
+
+/// Resolve a conflicted file and stage the result. `resolutions` holds one
+/// choice per conflict block in file order, or a single choice for all of
+/// them. Files without conflict markers (binary files, delete/modify
+/// conflicts) take a single whole-file choice: `Ours` or `Theirs` writes
+/// that side's version, deleting the file if that side doesn't have it, and
+/// `Custom` writes the given text. So do files that aren't valid UTF-8, whose
+/// blocks can't be rewritten without mangling their encoding.
+#[tauri::command]
+pub fn git_resolve_conflict(
+    worktree_path: String,
+    file_path: String,
+    resolutions: Vec<ConflictResolution>,
+) -> Result<(), String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let entry = find_conflict(&repo, &file_path)?;
+    let full_path = Path::new(&worktree_path).join(&file_path);
+
+    let regions = read_text(&full_path)
+        .map(|text| parse_conflict_regions(&text))
+        .unwrap_or_default();
+    let blocks = regions
+        .iter()
+        .filter(|r| matches!(r, ConflictRegion::Conflict { .. }))
+        .count();
+
+    let content = if blocks == 0 {
+        let [resolution] = resolutions.as_slice() else {
+            return Err(format!(
+                "{} has no conflict blocks that can be resolved separately; give a single resolution",
+                file_path
+            ));
+        };
+        match resolution {
+            ConflictResolution::Ours => stage_content(&repo, &entry.ours)?,
+            ConflictResolution::Theirs => stage_content(&repo, &entry.theirs)?,
+            ConflictResolution::Both => {
+                return Err(format!("{} has no conflict blocks to combine", file_path));
+            }
+            ConflictResolution::Custom { text } => Some(text.clone().into_bytes()),
+        }
+    } else {
+        if resolutions.len() != 1 && resolutions.len() != blocks {
+            return Err(format!(
+                "{} has {} conflict blocks but {} resolutions were given",
+                file_path,
+                blocks,
+                resolutions.len()
+            ));
+        }
+        Some(apply_resolutions(&regions, &resolutions).into_bytes())
+    };
+
+    match &content {
+        Some(bytes) => {
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create directory: {}", e))?;
+            }
+            fs::write(&full_path, bytes)
+                .map_err(|e| format!("Failed to write {}: {}", file_path, e))?;
+        }
+        None => {
+            if full_path.exists() {
+                fs::remove_file(&full_path)
+                    .map_err(|e| format!("Failed to delete {}: {}", file_path, e))?;
+            }
+        }
+    }
+
+    stage_resolution(&repo, &file_path, content.is_some())
+}
+
+/// Stage a conflicted file as it is in the working tree, e.g. after editing
+/// it by hand. Refuses while conflict markers remain.
+#[tauri::command]
+pub fn git_mark_resolved(worktree_path: String, file_path: String) -> Result<(), String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    find_conflict(&repo, &file_path)?;
+    let full_path = Path::new(&worktree_path).join(&file_path);
+
+    if let Ok(bytes) = fs::read(&full_path) {
+        // Only searched for markers, so a lossy decode is fine here
+        if parse_conflict_regions(&String::from_utf8_lossy(&bytes))
+            .iter()
+            .any(|r| matches!(r, ConflictRegion::Conflict { .. }))
+        {
+            return Err(format!("{} still contains conflict markers", file_path));
+        }
+    }
+
+    stage_resolution(&repo, &file_path, full_path.exists())
+}
+
+pub(crate) fn list_conflicts(repo: &Repository) -> Result<Vec<ConflictEntry>, String> {
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let conflicts = index
+        .conflicts()
+        .map_err(|e| format!("Failed to read conflicts: {}", e))?;
+
+    conflicts
+        .map(|c| {
+            c.map(conflict_entry)
+                .map_err(|e| format!("Failed to read conflict: {}", e))
+        })
+        .collect()
+}
+
+fn find_conflict(repo: &Repository, file_path: &str) -> Result<ConflictEntry, String> {
+    list_conflicts(repo)?
+        .into_iter()
+        .find(|c| c.path == file_path)
+        .ok_or_else(|| format!("{} is not conflicted", file_path))
+}
+
+fn conflict_entry(conflict: IndexConflict) -> ConflictEntry {
+    let ancestor = conflict.ancestor.map(conflict_stage);
+    let ours = conflict.our.map(conflict_stage);
+    let theirs = conflict.their.map(conflict_stage);
+    let path = [&ours, &theirs, &ancestor]
+        .into_iter()
+        .find_map(|s| s.as_ref().map(|s| s.path.clone()))
+        .unwrap_or_default();
+
+    ConflictEntry {
+        path,
+        ancestor,
+        ours,
+        theirs,
+    }
+}
+
+fn conflict_stage(entry: IndexEntry) -> ConflictStage {
+    ConflictStage {
+        path: String::from_utf8_lossy(&entry.path).into_owned(),
+        oid: entry.id.to_string(),
+        mode: entry.mode,
+    }
+}
+
+/// Blob contents of a stage, `None` when that side doesn't have the file.
+fn stage_content(
+    repo: &Repository,
+    stage: &Option<ConflictStage>,
+) -> Result<Option<Vec<u8>>, String> {
+    let Some(stage) = stage else {
+        return Ok(None);
+    };
+    let oid = Oid::from_str(&stage.oid).map_err(|e| format!("Invalid blob id: {}", e))?;
+    let blob = repo
+        .find_blob(oid)
+        .map_err(|e| format!("Failed to read {}: {}", stage.path, e))?;
+    Ok(Some(blob.content().to_vec()))
+}
+
+/// A working tree file's text, `None` if it is missing or not valid UTF-8.
+/// Regions are written back as text, so other encodings (e.g. Latin-1) must
+/// not go through a lossy decode.
+fn read_text(path: &Path) -> Option<String> {
+    fs::read(path)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+/// Mark the file resolved in the index: add it, or remove it if the
+/// resolution deleted it. Either clears its conflict stages.
+fn stage_resolution(repo: &Repository, file_path: &str, exists: bool) -> Result<(), String> {
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    if exists {
+        index
+            .add_path(Path::new(file_path))
+            .map_err(|e| format!("Failed to stage {}: {}", file_path, e))?;
+    } else {
+        index
+            .remove_path(Path::new(file_path))
+            .map_err(|e| format!("Failed to remove {}: {}", file_path, e))?;
+    }
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))
+}
+
+/// Rebuild a file from its regions, replacing conflict block `i` according
+/// to `resolutions[i]` (or `resolutions[0]` when only one is given).
+fn apply_resolutions(regions: &[ConflictRegion], resolutions: &[ConflictResolution]) -> String {
+    let mut out = String::new();
+    let mut block = 0;
+    for region in regions {
+        match region {
+            ConflictRegion::Common { text } => out.push_str(text),
+            ConflictRegion::Conflict { ours, theirs, .. } => {
+                let resolution = &resolutions[if resolutions.len() == 1 { 0 } else { block }];
+                block += 1;
+                match resolution {
+                    ConflictResolution::Ours => out.push_str(ours),
+                    ConflictResolution::Theirs => out.push_str(theirs),
+                    ConflictResolution::Both => {
+                        out.push_str(ours);
+                        out.push_str(theirs);
+                    }
+                    ConflictResolution::Custom { text } => out.push_str(text),
+                }
+            }
+        }
+    }
+    out
+}
+
+#[derive(Default, PartialEq)]
+enum Section {
+    #[default]
+    Ours,
+    Base,
+    Theirs,
+}
+
+/// A conflict block being parsed; `raw` keeps its original text in case it
+/// is never closed.
+#[derive(Default)]
+struct OpenBlock {
+    raw: String,
+    section: Section,
+    ours_label: String,
+    ours: String,
+    base: Option<String>,
+    theirs: String,
+}
+
+/// Split text at `<<<<<<<`, `|||||||` (diff3), `=======` and `>>>>>>>`
+/// markers. An unterminated block is kept as common text.
+pub(crate) fn parse_conflict_regions(text: &str) -> Vec<ConflictRegion> {
+    let mut regions = Vec::new();
+    let mut common = String::new();
+    let mut open: Option<OpenBlock> = None;
+
+    for line in text.split_inclusive('\n') {
+        let Some(block) = open.as_mut() else {
+            match marker(line, '<') {
+                Some(label) => {
+                    open = Some(OpenBlock {
+                        raw: line.to_string(),
+                        ours_label: label,
+                        ..Default::default()
+                    });
+                }
+                None => common.push_str(line),
+            }
+            continue;
+        };
+
+        block.raw.push_str(line);
+        if block.section == Section::Ours && marker(line, '|').is_some() {
+            block.section = Section::Base;
+            block.base = Some(String::new());
+        } else if block.section != Section::Theirs && marker(line, '=').is_some() {
+            block.section = Section::Theirs;
+        } else if block.section == Section::Theirs && marker(line, '>').is_some() {
+            let block = open.take().unwrap_or_default();
+            if !common.is_empty() {
+                regions.push(ConflictRegion::Common {
+                    text: std::mem::take(&mut common),
+                });
+            }
+            regions.push(ConflictRegion::Conflict {
+                ours_label: block.ours_label,
+                theirs_label: marker(line, '>').unwrap_or_default(),
+                ours: block.ours,
+                base: block.base,
+                theirs: block.theirs,
+            });
+        } else {
+            match block.section {
+                Section::Ours => block.ours.push_str(line),
+                Section::Base => block.base.get_or_insert_with(String::new).push_str(line),
+                Section::Theirs => block.theirs.push_str(line),
+            }
+        }
+    }
+
+    if let Some(block) = open {
+        common.push_str(&block.raw);
+    }
+    if !common.is_empty() {
+        regions.push(ConflictRegion::Common { text: common });
+    }
+    regions
+}
+
+/// The label after a seven-character conflict marker made of `c`, if `line`
+/// is one.
+fn marker(line: &str, c: char) -> Option<String> {
+    let body = line.trim_end_matches(['\n', '\r']);
+    let rest = body.strip_prefix(c.to_string().repeat(7).as_str())?;
+    if rest.is_empty() {
+        Some(String::new())
+    } else {
+        rest.strip_prefix(' ').map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn common(text: &str) -> ConflictRegion {
+        ConflictRegion::Common {
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_two_way_blocks() {
+        let text = "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> feature\nb\n";
+        assert_eq!(
+            parse_conflict_regions(text),
+            [
+                common("a\n"),
+                ConflictRegion::Conflict {
+                    ours_label: "HEAD".to_string(),
+                    theirs_label: "feature".to_string(),
+                    ours: "ours\n".to_string(),
+                    base: None,
+                    theirs: "theirs\n".to_string(),
+                },
+                common("b\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_diff3_base_sections() {
+        let text = "<<<<<<< ours\nx = 1\n||||||| base\nx = 0\n=======\nx = 2\n>>>>>>> theirs\n";
+        assert_eq!(
+            parse_conflict_regions(text),
+            [ConflictRegion::Conflict {
+                ours_label: "ours".to_string(),
+                theirs_label: "theirs".to_string(),
+                ours: "x = 1\n".to_string(),
+                base: Some("x = 0\n".to_string()),
+                theirs: "x = 2\n".to_string(),
+            }]
+        );
+
+        // An empty base section is still a base
+        let text = "<<<<<<<\na\n|||||||\n=======\nb\n>>>>>>>\n";
+        let regions = parse_conflict_regions(text);
+        assert!(matches!(
+            regions.as_slice(),
+            [ConflictRegion::Conflict { base: Some(base), .. }] if base.is_empty()
+        ));
+    }
+
+    #[test]
+    fn keeps_unterminated_blocks_as_common_text() {
+        let text = "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n";
+        assert_eq!(parse_conflict_regions(text), [common(text)]);
+
+        // A complete block before the unterminated one is still parsed
+        let text = "<<<<<<<\n1\n=======\n2\n>>>>>>>\n<<<<<<<\n3\n";
+        let regions = parse_conflict_regions(text);
+        assert_eq!(regions.len(), 2);
+        assert!(matches!(regions[0], ConflictRegion::Conflict { .. }));
+        assert_eq!(regions[1], common("<<<<<<<\n3\n"));
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let text = "a\r\n<<<<<<< HEAD\r\nours\r\n=======\r\ntheirs\r\n>>>>>>> feature\r\nb";
+        let regions = parse_conflict_regions(text);
+        assert_eq!(
+            regions,
+            [
+                common("a\r\n"),
+                ConflictRegion::Conflict {
+                    ours_label: "HEAD".to_string(),
+                    theirs_label: "feature".to_string(),
+                    ours: "ours\r\n".to_string(),
+                    base: None,
+                    theirs: "theirs\r\n".to_string(),
+                },
+                common("b"),
+            ]
+        );
+        assert_eq!(
+            apply_resolutions(&regions, &[ConflictResolution::Both]),
+            "a\r\nours\r\ntheirs\r\nb"
+        );
+    }
+
+    #[test]
+    fn ignores_marker_lookalikes() {
+        let text = "<<<<<<<< eight\n======= text\n>>>>>>>x\n";
+        assert_eq!(parse_conflict_regions(text), [common(text)]);
+    }
+
+    /// Merge two branches that both changed `file.txt` from `base`.
+    fn conflicted_repo(name: &str, base: &[u8], ours: &[u8], theirs: &[u8]) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("heroi-conflict-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let repo = Repository::init(&dir).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+
+        let commit = |content: &[u8], parent: Option<git2::Oid>, update: Option<&str>| {
+            fs::write(dir.join("file.txt"), content).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("file.txt")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parents: Vec<_> = parent
+                .map(|p| repo.find_commit(p).unwrap())
+                .into_iter()
+                .collect();
+            let parents: Vec<_> = parents.iter().collect();
+            repo.commit(update, &sig, &sig, "commit", &tree, &parents)
+                .unwrap()
+        };
+        let base = commit(base, None, Some("HEAD"));
+        let theirs = commit(theirs, Some(base), None);
+        commit(ours, Some(base), Some("HEAD"));
+
+        let theirs = repo.find_annotated_commit(theirs).unwrap();
+        repo.merge(&[&theirs], None, None).unwrap();
+        dir
+    }
+
+    #[test]
+    fn resolving_non_utf8_files_keeps_their_bytes() {
+        let dir = conflicted_repo(
+            "latin1",
+            b"caf\xe9\n",
+            b"caf\xe9 ours\n",
+            b"caf\xe9 theirs\n",
+        );
+        let path = dir.to_str().unwrap().to_string();
+        assert!(fs::read(dir.join("file.txt"))
+            .unwrap()
+            .starts_with(b"<<<<<<<"));
+
+        let details = git_conflict_details(path.clone(), "file.txt".to_string()).unwrap();
+        assert!(details.regions.is_empty());
+        let per_block = vec![ConflictResolution::Ours, ConflictResolution::Theirs];
+        assert!(git_resolve_conflict(path.clone(), "file.txt".to_string(), per_block).is_err());
+
+        git_resolve_conflict(
+            path.clone(),
+            "file.txt".to_string(),
+            vec![ConflictResolution::Ours],
+        )
+        .unwrap();
+        assert_eq!(fs::read(dir.join("file.txt")).unwrap(), b"caf\xe9 ours\n");
+        assert!(git_list_conflicts(path).unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,13 +1,28 @@
-use git2::{DiffOptions, Repository, StatusOptions};
+use git2::build::CheckoutBuilder;
+use git2::{ApplyLocation, DiffFindOptions, DiffOptions, Repository, ResetType, StatusOptions};
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::process::Command;
+use tauri::State;
 
-use crate::models::git::{DiffOutput, FileState, GitFileStatus};
+use crate::commands::checkpoints::safety_checkpoint;
+use crate::commands::sync::{ahead_behind, git};
+use crate::models::checkpoint::Checkpoint;
+use crate::models::git::{
+    CommitIdentity, CommitOptions, CommitResult, DiffLine, DiffLineKind, FileDiff, FileState,
+    GitFileStatus, Hunk, WorkingTreeDiff,
+};
+use crate::state::AppState;
 
 #[tauri::command]
 pub fn git_status(worktree_path: String) -> Result<Vec<GitFileStatus>, String> {
     let repo =
         Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    repo_status(&repo)
+}
 
+/// Changed files of the repository's worktree, ignored files excluded.
+pub(crate) fn repo_status(repo: &Repository) -> Result<Vec<GitFileStatus>, String> {
     let mut opts = StatusOptions::new();
     opts.include_untracked(true)
         .recurse_untracked_dirs(true)
@@ -89,53 +104,21 @@ pub fn git_diff(worktree_path: String) -> Result<String, String> {
     Ok(diff_text)
 }
 
+/// Staged and unstaged changes to one file.
 #[tauri::command]
-pub fn git_diff_file(worktree_path: String, file_path: String) -> Result<DiffOutput, String> {
+pub fn git_diff_file(worktree_path: String, file_path: String) -> Result<WorkingTreeDiff, String> {
     let repo =
         Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
 
-    let mut opts = DiffOptions::new();
-    opts.pathspec(&file_path);
-
-    // Try index-to-workdir first (unstaged changes)
-    let diff = repo
-        .diff_index_to_workdir(None, Some(&mut opts))
-        .map_err(|e| format!("Failed to get diff: {}", e))?;
-
-    let mut diff_text = String::new();
-
-    if diff.stats().map(|s| s.files_changed()).unwrap_or(0) == 0 {
-        // Try HEAD-to-index (staged changes)
-        let head = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
-        let diff = repo
-            .diff_tree_to_index(head.as_ref(), None, Some(&mut opts))
-            .map_err(|e| format!("Failed to get staged diff: {}", e))?;
-
-        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-            let origin = line.origin();
-            if origin == '+' || origin == '-' || origin == ' ' {
-                diff_text.push(origin);
-            }
-            diff_text.push_str(&String::from_utf8_lossy(line.content()));
-            true
-        })
-        .map_err(|e| format!("Failed to print diff: {}", e))?;
-    } else {
-        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-            let origin = line.origin();
-            if origin == '+' || origin == '-' || origin == ' ' {
-                diff_text.push(origin);
-            }
-            diff_text.push_str(&String::from_utf8_lossy(line.content()));
-            true
-        })
-        .map_err(|e| format!("Failed to print diff: {}", e))?;
-    }
-
-    Ok(DiffOutput {
-        file_path,
-        diff_text,
-    })
+    let diff = working_tree_diffs(&repo, Some(&file_path))?
+        .into_iter()
+        .next()
+        .unwrap_or(WorkingTreeDiff {
+            file_path,
+            staged: None,
+            unstaged: None,
+        });
+    Ok(diff)
 }
 
 #[tauri::command]
@@ -197,6 +180,192 @@ pub fn git_unstage_file(worktree_path: String, file_path: String) -> Result<(),
     Ok(())
 }
 
+/// Stage one hunk of a file's unstaged changes. `hunk` must match the
+/// current diff exactly; otherwise the file changed since it was shown and
+/// nothing is applied.
+#[tauri::command]
+pub fn git_stage_hunk(worktree_path: String, file_path: String, hunk: Hunk) -> Result<(), String> {
+    apply_hunk(&worktree_path, &file_path, &hunk, None, HunkAction::Stage)
+}
+
+/// Unstage one hunk of a file's staged changes.
+#[tauri::command]
+pub fn git_unstage_hunk(
+    worktree_path: String,
+    file_path: String,
+    hunk: Hunk,
+) -> Result<(), String> {
+    apply_hunk(&worktree_path, &file_path, &hunk, None, HunkAction::Unstage)
+}
+
+/// Revert one hunk of a file's unstaged changes in the working tree.
+#[tauri::command]
+pub fn git_discard_hunk(
+    worktree_path: String,
+    file_path: String,
+    hunk: Hunk,
+) -> Result<(), String> {
+    apply_hunk(&worktree_path, &file_path, &hunk, None, HunkAction::Discard)
+}
+
+/// Stage only the given lines (indices into `hunk.lines`) of an unstaged hunk.
+#[tauri::command]
+pub fn git_stage_lines(
+    worktree_path: String,
+    file_path: String,
+    hunk: Hunk,
+    lines: Vec<usize>,
+) -> Result<(), String> {
+    apply_hunk(&worktree_path, &file_path, &hunk, Some(&lines), HunkAction::Stage)
+}
+
+/// Unstage only the given lines of a staged hunk.
+#[tauri::command]
+pub fn git_unstage_lines(
+    worktree_path: String,
+    file_path: String,
+    hunk: Hunk,
+    lines: Vec<usize>,
+) -> Result<(), String> {
+    apply_hunk(&worktree_path, &file_path, &hunk, Some(&lines), HunkAction::Unstage)
+}
+
+/// Revert only the given lines of an unstaged hunk in the working tree.
+#[tauri::command]
+pub fn git_discard_lines(
+    worktree_path: String,
+    file_path: String,
+    hunk: Hunk,
+    lines: Vec<usize>,
+) -> Result<(), String> {
+    apply_hunk(&worktree_path, &file_path, &hunk, Some(&lines), HunkAction::Discard)
+}
+
+/// Revert a file to HEAD in both the index and the working tree; files not
+/// in HEAD are deleted. A checkpoint is taken first and returned so the
+/// discard can be undone.
+#[tauri::command(async)]
+pub fn git_discard_file(
+    worktree_path: String,
+    file_path: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Checkpoint, String> {
+    let checkpoint = safety_checkpoint(
+        &app,
+        &state,
+        &worktree_path,
+        &format!("Before discarding {}", file_path),
+    )?;
+    discard_file(&worktree_path, &file_path)?;
+    Ok(checkpoint)
+}
+
+fn discard_file(worktree_path: &str, file_path: &str) -> Result<(), String> {
+    let repo =
+        Repository::open(worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let head = repo.head().and_then(|h| h.peel_to_commit()).ok();
+    let in_head = head
+        .as_ref()
+        .and_then(|c| c.tree().ok())
+        .is_some_and(|t| t.get_path(Path::new(file_path)).is_ok());
+
+    if let (Some(head), true) = (&head, in_head) {
+        repo.reset_default(Some(head.as_object()), [file_path])
+            .map_err(|e| format!("Failed to unstage: {}", e))?;
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force().path(file_path);
+        repo.checkout_head(Some(&mut checkout))
+            .map_err(|e| format!("Failed to discard changes: {}", e))?;
+    } else {
+        let mut index = repo
+            .index()
+            .map_err(|e| format!("Failed to get index: {}", e))?;
+        if index.get_path(Path::new(file_path), 0).is_some() {
+            index
+                .remove_path(Path::new(file_path))
+                .map_err(|e| format!("Failed to unstage: {}", e))?;
+            index
+                .write()
+                .map_err(|e| format!("Failed to write index: {}", e))?;
+        }
+        let full_path = Path::new(worktree_path).join(file_path);
+        if full_path.exists() {
+            std::fs::remove_file(&full_path)
+                .map_err(|e| format!("Failed to delete {}: {}", file_path, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Reset the index and tracked files to HEAD, and with `include_untracked`
+/// also delete untracked (but not ignored) files. A checkpoint is taken
+/// first and returned.
+#[tauri::command(async)]
+pub fn git_discard_all(
+    worktree_path: String,
+    include_untracked: Option<bool>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Checkpoint, String> {
+    let checkpoint = safety_checkpoint(
+        &app,
+        &state,
+        &worktree_path,
+        "Before discarding all changes",
+    )?;
+    discard_all(&worktree_path, include_untracked.unwrap_or(false))?;
+    Ok(checkpoint)
+}
+
+fn discard_all(worktree_path: &str, include_untracked: bool) -> Result<(), String> {
+    let repo =
+        Repository::open(worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let head = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    repo.reset(head.as_object(), ResetType::Hard, None)
+        .map_err(|e| format!("Failed to discard changes: {}", e))?;
+
+    if include_untracked {
+        clean_untracked(worktree_path)?;
+    }
+    Ok(())
+}
+
+/// Delete untracked files and directories (`git clean -fd`); ignored files
+/// are kept. A checkpoint is taken first and returned.
+#[tauri::command(async)]
+pub fn git_clean_untracked(
+    worktree_path: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Checkpoint, String> {
+    let checkpoint = safety_checkpoint(
+        &app,
+        &state,
+        &worktree_path,
+        "Before deleting untracked files",
+    )?;
+    clean_untracked(&worktree_path)?;
+    Ok(checkpoint)
+}
+
+fn clean_untracked(worktree_path: &str) -> Result<(), String> {
+    let output = Command::new("git")
+        .current_dir(worktree_path)
+        .args(["clean", "-fd"])
+        .output()
+        .map_err(|e| format!("Failed to run git clean: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git clean failed: {}", stderr));
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub fn git_stage_all(worktree_path: String) -> Result<(), String> {
     let repo =
@@ -247,262 +416,701 @@ pub fn git_unstage_all(worktree_path: String) -> Result<(), String> {
     Ok(())
 }
 
-#[tauri::command]
-pub fn git_commit(worktree_path: String, message: String) -> Result<String, String> {
+/// Commit the index with `git commit`, so the repo's hooks run and commits
+/// are signed as configured. Finishing a merge records the merged heads as
+/// parents. A hook rejecting the commit is an error carrying its output.
+#[tauri::command(async)]
+pub fn git_commit(
+    worktree_path: String,
+    message: String,
+    options: Option<CommitOptions>,
+) -> Result<CommitResult, String> {
+    let options = options.unwrap_or_default();
     let repo =
         Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
+    if index.has_conflicts() {
+        return Err("Resolve all conflicts before committing".to_string());
+    }
 
-    let sig = repo
-        .signature()
-        .map_err(|e| format!("Failed to get signature: {}", e))?;
-
-    let mut index = repo.index().map_err(|e| format!("Failed to get index: {}", e))?;
-    let tree_oid = index
-        .write_tree()
-        .map_err(|e| format!("Failed to write tree: {}", e))?;
-    let tree = repo
-        .find_tree(tree_oid)
-        .map_err(|e| format!("Failed to find tree: {}", e))?;
+    let mut args = vec!["commit".to_string()];
+    if options.amend {
+        args.push("--amend".to_string());
+    }
+    if options.amend && message.trim().is_empty() {
+        args.push("--no-edit".to_string());
+    } else {
+        args.push("--message".to_string());
+        args.push(message);
+    }
+    if options.signoff {
+        args.push("--signoff".to_string());
+    }
+    if let Some(author) = &options.author {
+        args.push(format!("--author={}", identity(author)?));
+    }
+    for co_author in &options.co_authors {
+        args.push(format!(
+            "--trailer=Co-authored-by: {}",
+            identity(co_author)?
+        ));
+    }
+    if options.no_verify {
+        args.push("--no-verify".to_string());
+    }
+    match options.sign {
+        Some(true) => args.push("--gpg-sign".to_string()),
+        Some(false) => args.push("--no-gpg-sign".to_string()),
+        None => {}
+    }
 
-    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
-    let parents: Vec<&git2::Commit> = parent.as_ref().map(|p| vec![p]).unwrap_or_default();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = git(&worktree_path, &args)?;
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if !output.status.success() {
+        // "nothing to commit" and friends go to stdout
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let details = [stderr.as_str(), stdout.trim()]
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        return Err(format!("Failed to commit: {}", details));
+    }
 
     let oid = repo
-        .commit(Some("HEAD"), &sig, &sig, &message, &tree, &parents)
-        .map_err(|e| format!("Failed to commit: {}", e))?;
-
-    Ok(oid.to_string())
+        .head()
+        .ok()
+        .and_then(|h| h.target())
+        .ok_or_else(|| "Failed to resolve the new commit".to_string())?;
+    Ok(CommitResult {
+        oid: oid.to_string(),
+        signed: repo.extract_signature(&oid, None).is_ok(),
+        hook_output: stderr,
+    })
 }
 
-#[tauri::command]
-pub fn git_push(worktree_path: String) -> Result<(), String> {
-    let output = Command::new("git")
-        .arg("push")
-        .current_dir(&worktree_path)
-        .output()
-        .map_err(|e| format!("Failed to run git push: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("git push failed: {}", stderr));
+/// `Name <email>`, rejecting values that would break the header or trailer.
+fn identity(person: &CommitIdentity) -> Result<String, String> {
+    let name = person.name.trim();
+    let email = person.email.trim();
+    let invalid = |s: &str| s.is_empty() || s.contains(['<', '>', '\n']);
+    if invalid(name) || invalid(email) {
+        return Err(format!("Invalid identity: {} <{}>", name, email));
     }
-
-    Ok(())
+    Ok(format!("{} <{}>", name, email))
 }
 
+/// Commits on HEAD that its upstream lacks (0 without an upstream).
 #[tauri::command]
 pub fn git_ahead_count(worktree_path: String) -> Result<usize, String> {
     let repo =
         Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    Ok(ahead_behind(&repo, None)?.ahead)
+}
 
-    let head = repo.head().map_err(|e| format!("Failed to get HEAD: {}", e))?;
-    let local_oid = head
-        .target()
-        .ok_or_else(|| "HEAD has no target".to_string())?;
-
-    let branch_name = head
-        .shorthand()
-        .ok_or_else(|| "Could not get branch name".to_string())?;
-
-    let upstream_ref = format!("refs/remotes/origin/{}", branch_name);
-    let upstream = match repo.find_reference(&upstream_ref) {
-        Ok(r) => r,
-        Err(_) => return Ok(0), // No upstream tracked
-    };
-
-    let upstream_oid = upstream
-        .target()
-        .ok_or_else(|| "Upstream has no target".to_string())?;
-
-    let (ahead, _behind) = repo
-        .graph_ahead_behind(local_oid, upstream_oid)
-        .map_err(|e| format!("Failed to compute ahead/behind: {}", e))?;
-
-    Ok(ahead)
+/// Staged and unstaged changes to every changed file, sorted by path.
+#[tauri::command]
+pub fn git_diff_all(worktree_path: String) -> Result<Vec<WorkingTreeDiff>, String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    working_tree_diffs(&repo, None)
 }
 
+/// Changes on HEAD since it forked from `base_branch` (`git diff base...HEAD`).
 #[tauri::command]
-pub fn git_diff_all(worktree_path: String) -> Result<Vec<DiffOutput>, String> {
+pub fn git_diff_base(
+    worktree_path: String,
+    base_branch: String,
+) -> Result<Vec<FileDiff>, String> {
     let repo =
         Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
 
-    let head_tree = repo
+    let base = repo
+        .revparse_single(&base_branch)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| format!("Failed to resolve '{}': {}", base_branch, e))?;
+    let head = repo
         .head()
-        .ok()
-        .and_then(|h| h.peel_to_tree().ok());
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| format!("Failed to resolve HEAD: {}", e))?;
+    let merge_base = repo
+        .merge_base(base.id(), head.id())
+        .and_then(|id| repo.find_commit(id))
+        .map_err(|e| format!("Failed to find merge base with '{}': {}", base_branch, e))?;
+
+    let base_tree = merge_base
+        .tree()
+        .map_err(|e| format!("Failed to read tree: {}", e))?;
+    let head_tree = head.tree().map_err(|e| format!("Failed to read tree: {}", e))?;
+    let mut diff = repo
+        .diff_tree_to_tree(Some(&base_tree), Some(&head_tree), None)
+        .map_err(|e| format!("Failed to diff against '{}': {}", base_branch, e))?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+        .map_err(|e| format!("Failed to detect renames: {}", e))?;
+    file_diffs(&diff)
+}
 
-    // Get all changed files (staged + unstaged)
-    let mut changed_files: std::collections::HashSet<String> = std::collections::HashSet::new();
+#[tauri::command]
+pub fn git_file_content(
+    worktree_path: String,
+    file_path: String,
+    ref_name: Option<String>,
+) -> Result<String, String> {
+    match ref_name {
+        Some(ref_name) => {
+            // Read file at specific ref
+            let output = Command::new("git")
+                .current_dir(&worktree_path)
+                .arg("show")
+                .arg(format!("{}:{}", ref_name, file_path))
+                .output()
+                .map_err(|e| format!("Failed to run git show: {}", e))?;
 
-    // Unstaged changes
-    let unstaged_diff = repo
-        .diff_index_to_workdir(None, Some(&mut DiffOptions::new()))
-        .map_err(|e| format!("Failed to get unstaged diff: {}", e))?;
+            if !output.status.success() {
+                // File might not exist at that ref
+                return Ok(String::new());
+            }
 
-    for delta in unstaged_diff.deltas() {
-        if let Some(path) = delta.new_file().path().and_then(|p| p.to_str()) {
-            changed_files.insert(path.to_string());
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
         }
-        if let Some(path) = delta.old_file().path().and_then(|p| p.to_str()) {
-            changed_files.insert(path.to_string());
+        None => {
+            // Read current file content from disk
+            let full_path = std::path::Path::new(&worktree_path).join(&file_path);
+            std::fs::read_to_string(&full_path)
+                .map_err(|e| format!("Failed to read file: {}", e))
         }
     }
+}
+
+/// Staged (HEAD to index) and unstaged (index to working tree, untracked
+/// files included) changes, merged per path and sorted. `path` limits the
+/// diff to one exact path.
+fn working_tree_diffs(
+    repo: &Repository,
+    path: Option<&str>,
+) -> Result<Vec<WorkingTreeDiff>, String> {
+    let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+
+    let mut opts = DiffOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    if let Some(path) = path {
+        opts.pathspec(path).disable_pathspec_match(true);
+    }
 
-    // Staged changes (HEAD to index)
-    let staged_diff = repo
-        .diff_tree_to_index(head_tree.as_ref(), None, Some(&mut DiffOptions::new()))
+    let staged = repo
+        .diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))
         .map_err(|e| format!("Failed to get staged diff: {}", e))?;
+    let unstaged = repo
+        .diff_index_to_workdir(None, Some(&mut opts))
+        .map_err(|e| format!("Failed to get unstaged diff: {}", e))?;
 
-    for delta in staged_diff.deltas() {
-        if let Some(path) = delta.new_file().path().and_then(|p| p.to_str()) {
-            changed_files.insert(path.to_string());
-        }
-        if let Some(path) = delta.old_file().path().and_then(|p| p.to_str()) {
-            changed_files.insert(path.to_string());
+    let mut by_path: BTreeMap<String, WorkingTreeDiff> = BTreeMap::new();
+    for (file, is_staged) in file_diffs(&staged)?
+        .into_iter()
+        .map(|f| (f, true))
+        .chain(file_diffs(&unstaged)?.into_iter().map(|f| (f, false)))
+    {
+        let file_path = file
+            .new_path
+            .clone()
+            .or_else(|| file.old_path.clone())
+            .unwrap_or_default();
+        let entry = by_path
+            .entry(file_path.clone())
+            .or_insert_with(|| WorkingTreeDiff {
+                file_path,
+                staged: None,
+                unstaged: None,
+            });
+        if is_staged {
+            entry.staged = Some(file);
+        } else {
+            entry.unstaged = Some(file);
         }
     }
+    Ok(by_path.into_values().collect())
+}
 
-    // Get diff text for each file
+/// Convert every file in `diff` into a `FileDiff` with its hunks, line
+/// numbers and stats.
+pub(crate) fn file_diffs(diff: &git2::Diff) -> Result<Vec<FileDiff>, String> {
     let mut results = Vec::new();
-    for file_path in &changed_files {
-        let mut opts = DiffOptions::new();
-        opts.pathspec(file_path);
-
-        let mut diff_text = String::new();
-
-        // Try unstaged first
-        let diff = repo
-            .diff_index_to_workdir(None, Some(&mut opts))
-            .map_err(|e| format!("Failed to get diff: {}", e))?;
-
-        let has_unstaged = diff.stats().map(|s| s.files_changed()).unwrap_or(0) > 0;
-
-        if has_unstaged {
-            diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-                let origin = line.origin();
-                if origin == '+' || origin == '-' || origin == ' ' {
-                    diff_text.push(origin);
-                }
-                diff_text.push_str(&String::from_utf8_lossy(line.content()));
-                true
-            })
-            .ok();
+    for idx in 0..diff.deltas().len() {
+        let Some(patch) =
+            git2::Patch::from_diff(diff, idx).map_err(|e| format!("Failed to read diff: {}", e))?
+        else {
+            continue;
+        };
+        results.push(file_diff(&patch)?);
+    }
+    Ok(results)
+}
+
+fn file_diff(patch: &git2::Patch) -> Result<FileDiff, String> {
+    let delta = patch.delta();
+    let status = file_state(delta.status());
+    let path = |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().to_string());
+    let old_path = match status {
+        FileState::Added | FileState::Untracked => None,
+        _ => path(delta.old_file()),
+    };
+    let new_path = match status {
+        FileState::Deleted => None,
+        _ => path(delta.new_file()),
+    };
+
+    let (_, additions, deletions) = patch
+        .line_stats()
+        .map_err(|e| format!("Failed to count diff lines: {}", e))?;
+
+    let mut hunks = Vec::new();
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch
+            .hunk(hunk_idx)
+            .map_err(|e| format!("Failed to read hunk: {}", e))?;
+        let mut lines = Vec::with_capacity(line_count);
+        for line_idx in 0..line_count {
+            let line = patch
+                .line_in_hunk(hunk_idx, line_idx)
+                .map_err(|e| format!("Failed to read diff line: {}", e))?;
+            let kind = match line.origin() {
+                '+' => DiffLineKind::Added,
+                '-' => DiffLineKind::Removed,
+                '=' | '>' | '<' => DiffLineKind::NoNewline,
+                _ => DiffLineKind::Context,
+            };
+            let text = String::from_utf8_lossy(line.content());
+            let (text, old_no, new_no) = match kind {
+                DiffLineKind::NoNewline => (text.trim().to_string(), None, None),
+                _ => (
+                    text.strip_suffix('\n').unwrap_or(&text).to_string(),
+                    line.old_lineno(),
+                    line.new_lineno(),
+                ),
+            };
+            lines.push(DiffLine {
+                kind,
+                old_no,
+                new_no,
+                text,
+            });
         }
+        hunks.push(Hunk {
+            header: String::from_utf8_lossy(hunk.header())
+                .trim_end()
+                .to_string(),
+            old_start: hunk.old_start(),
+            old_lines: hunk.old_lines(),
+            new_start: hunk.new_start(),
+            new_lines: hunk.new_lines(),
+            lines,
+        });
+    }
 
-        // Also check staged
-        let mut opts2 = DiffOptions::new();
-        opts2.pathspec(file_path);
-        let staged = repo
-            .diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts2))
-            .map_err(|e| format!("Failed to get staged diff: {}", e))?;
+    Ok(FileDiff {
+        old_path,
+        new_path,
+        status,
+        binary: delta.flags().is_binary(),
+        additions: additions as u32,
+        deletions: deletions as u32,
+        hunks,
+    })
+}
 
-        let has_staged = staged.stats().map(|s| s.files_changed()).unwrap_or(0) > 0;
+#[derive(Clone, Copy, PartialEq)]
+enum HunkAction {
+    Stage,
+    Unstage,
+    Discard,
+}
 
-        if has_staged {
-            if has_unstaged {
-                diff_text.push_str("\n--- Staged changes ---\n");
-            }
-            staged.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-                let origin = line.origin();
-                if origin == '+' || origin == '-' || origin == ' ' {
-                    diff_text.push(origin);
-                }
-                diff_text.push_str(&String::from_utf8_lossy(line.content()));
-                true
-            })
-            .ok();
+/// Apply `hunk` (or just its `lines`) of `file_path` to the index or the
+/// working tree. The hunk is first looked up in a freshly computed diff so a
+/// stale view, e.g. while an agent keeps editing the file, is rejected
+/// instead of patching the wrong lines.
+fn apply_hunk(
+    worktree_path: &str,
+    file_path: &str,
+    hunk: &Hunk,
+    lines: Option<&[usize]>,
+    action: HunkAction,
+) -> Result<(), String> {
+    let repo =
+        Repository::open(worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+
+    let mut opts = DiffOptions::new();
+    opts.pathspec(file_path).disable_pathspec_match(true);
+    let current = match action {
+        HunkAction::Stage | HunkAction::Discard => {
+            repo.diff_index_to_workdir(None, Some(&mut opts))
         }
+        HunkAction::Unstage => {
+            let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+            repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))
+        }
+    }
+    .map_err(|e| format!("Failed to get diff: {}", e))?;
+
+    let file = file_diffs(&current)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No changes to {}", file_path))?;
+    if file.binary || file.status != FileState::Modified {
+        return Err(format!(
+            "Only modified text files can be changed hunk by hunk; {} is {:?}",
+            file_path, file.status
+        ));
+    }
+    if !file.hunks.contains(hunk) {
+        return Err(format!(
+            "{} changed since the diff was loaded; refresh and try again",
+            file_path
+        ));
+    }
 
-        if !diff_text.is_empty() {
-            results.push(DiffOutput {
-                file_path: file_path.clone(),
-                diff_text,
-            });
+    // Staging moves the change forward into the index; unstaging and
+    // discarding undo it in the index or working tree respectively.
+    let reverse = action != HunkAction::Stage;
+    let patch = hunk_patch(file_path, hunk, lines, reverse)
+        .ok_or_else(|| "No changed lines selected".to_string())?;
+    let diff = git2::Diff::from_buffer(patch.as_bytes())
+        .map_err(|e| format!("Failed to build patch: {}", e))?;
+    let location = match action {
+        HunkAction::Stage | HunkAction::Unstage => ApplyLocation::Index,
+        HunkAction::Discard => ApplyLocation::WorkDir,
+    };
+    repo.apply(&diff, location, None)
+        .map_err(|e| format!("Failed to apply patch: {}", e))
+}
+
+/// Render a one-hunk patch for `path` holding the changes of `hunk`, limited
+/// to `selected` line indices when given. Unselected removals become context
+/// and unselected additions are dropped, so the patch still applies to the
+/// file's current content. With `reverse` the patch undoes the changes.
+/// Returns `None` if no changed line is selected.
+fn hunk_patch(
+    path: &str,
+    hunk: &Hunk,
+    selected: Option<&[usize]>,
+    reverse: bool,
+) -> Option<String> {
+    let (old_start, add, remove) = if reverse {
+        (hunk.new_start, '-', '+')
+    } else {
+        (hunk.old_start, '+', '-')
+    };
+
+    let mut body = String::new();
+    // The current run of changed lines, kept apart so removals precede
+    // additions as in a forward diff even when `reverse` swaps them
+    let (mut removed, mut added) = (String::new(), String::new());
+    let (mut old_lines, mut new_lines, mut changes) = (0u32, 0u32, 0);
+    // Where the previous line went, if it made it into the patch, for
+    // "\ No newline"
+    let mut last = None;
+    for (idx, line) in hunk.lines.iter().enumerate() {
+        let is_selected = selected.is_none_or(|s| s.contains(&idx));
+        let marker = match line.kind {
+            DiffLineKind::Context => ' ',
+            DiffLineKind::Added => add,
+            DiffLineKind::Removed => remove,
+            DiffLineKind::NoNewline => {
+                let target = match last {
+                    Some('-') => &mut removed,
+                    Some('+') => &mut added,
+                    Some(_) => &mut body,
+                    None => continue,
+                };
+                target.push_str("\\ No newline at end of file\n");
+                continue;
+            }
+        };
+        let marker = match marker {
+            '+' | '-' if is_selected => {
+                changes += 1;
+                marker
+            }
+            '+' => {
+                last = None;
+                continue;
+            }
+            '-' => ' ',
+            _ => marker,
+        };
+        if marker != '+' {
+            old_lines += 1;
         }
+        if marker != '-' {
+            new_lines += 1;
+        }
+        let target = match marker {
+            '-' => &mut removed,
+            '+' => &mut added,
+            _ => {
+                body.push_str(&std::mem::take(&mut removed));
+                body.push_str(&std::mem::take(&mut added));
+                &mut body
+            }
+        };
+        target.push(marker);
+        target.push_str(&line.text);
+        target.push('\n');
+        last = Some(marker);
+    }
+    body.push_str(&removed);
+    body.push_str(&added);
+    if changes == 0 {
+        return None;
     }
 
-    results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
-    Ok(results)
+    // An empty side's start refers to the line before the hunk
+    let new_start = old_start + u32::from(old_lines == 0) - u32::from(new_lines == 0);
+    Some(format!(
+        "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n@@ -{},{} +{},{} @@\n{}",
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        body,
+        path = path
+    ))
 }
 
-#[tauri::command]
-pub fn git_diff_base(
-    worktree_path: String,
-    base_branch: String,
-) -> Result<Vec<DiffOutput>, String> {
-    // Use git CLI for merge-base diff (git diff base...HEAD)
-    let output = Command::new("git")
-        .current_dir(&worktree_path)
-        .arg("diff")
-        .arg("--name-only")
-        .arg(format!("{}...HEAD", base_branch))
-        .output()
-        .map_err(|e| format!("Failed to run git diff: {}", e))?;
+fn file_state(delta: git2::Delta) -> FileState {
+    match delta {
+        git2::Delta::Added | git2::Delta::Copied => FileState::Added,
+        git2::Delta::Deleted => FileState::Deleted,
+        git2::Delta::Modified => FileState::Modified,
+        git2::Delta::Renamed => FileState::Renamed,
+        git2::Delta::Typechange => FileState::Typechange,
+        git2::Delta::Untracked => FileState::Untracked,
+        git2::Delta::Conflicted => FileState::Conflicted,
+        _ => FileState::Unmodified,
+    }
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("git diff failed: {}", stderr));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::checkpoints::{checkout_snapshot, snapshot_checkpoint};
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A throwaway repository with `file.txt` committed as `committed`.
+    struct TempRepo {
+        dir: PathBuf,
+        repo: Repository,
     }
 
-    let file_list = String::from_utf8_lossy(&output.stdout);
-    let mut results = Vec::new();
+    impl TempRepo {
+        fn new(name: &str, committed: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "heroi-hunk-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let repo = Repository::init(&dir).unwrap();
+            fs::write(dir.join("file.txt"), committed).unwrap();
+            {
+                let mut index = repo.index().unwrap();
+                index.add_path(Path::new("file.txt")).unwrap();
+                index.write().unwrap();
+                let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+                let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+                repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+                    .unwrap();
+            }
+            TempRepo { dir, repo }
+        }
 
-    for file_path in file_list.lines() {
-        let file_path = file_path.trim();
-        if file_path.is_empty() {
-            continue;
+        fn path(&self) -> &str {
+            self.dir.to_str().unwrap()
         }
 
-        let diff_output = Command::new("git")
-            .current_dir(&worktree_path)
-            .arg("diff")
-            .arg(format!("{}...HEAD", base_branch))
-            .arg("--")
-            .arg(file_path)
-            .output()
-            .map_err(|e| format!("Failed to get file diff: {}", e))?;
-
-        let diff_text = String::from_utf8_lossy(&diff_output.stdout).to_string();
-
-        if !diff_text.is_empty() {
-            results.push(DiffOutput {
-                file_path: file_path.to_string(),
-                diff_text,
-            });
+        fn write(&self, content: &str) {
+            fs::write(self.dir.join("file.txt"), content).unwrap();
         }
-    }
 
-    Ok(results)
-}
+        fn workdir(&self) -> String {
+            fs::read_to_string(self.dir.join("file.txt")).unwrap()
+        }
 
-#[tauri::command]
-pub fn git_file_content(
-    worktree_path: String,
-    file_path: String,
-    ref_name: Option<String>,
-) -> Result<String, String> {
-    match ref_name {
-        Some(ref_name) => {
-            // Read file at specific ref
-            let output = Command::new("git")
-                .current_dir(&worktree_path)
-                .arg("show")
-                .arg(format!("{}:{}", ref_name, file_path))
-                .output()
-                .map_err(|e| format!("Failed to run git show: {}", e))?;
+        fn index(&self) -> String {
+            // apply_hunk writes through its own handle, so reload from disk
+            let mut index = self.repo.index().unwrap();
+            index.read(true).unwrap();
+            let entry = index.get_path(Path::new("file.txt"), 0).unwrap();
+            let blob = self.repo.find_blob(entry.id).unwrap();
+            String::from_utf8(blob.content().to_vec()).unwrap()
+        }
 
-            if !output.status.success() {
-                // File might not exist at that ref
-                return Ok(String::new());
-            }
+        fn stage_all(&self) {
+            let mut index = self.repo.index().unwrap();
+            index.add_path(Path::new("file.txt")).unwrap();
+            index.write().unwrap();
+        }
 
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        /// The single hunk of the unstaged (or staged) diff.
+        fn hunk(&self, staged: bool) -> Hunk {
+            let diff = if staged {
+                let head = self.repo.head().unwrap().peel_to_tree().unwrap();
+                self.repo.diff_tree_to_index(Some(&head), None, None)
+            } else {
+                self.repo.diff_index_to_workdir(None, None)
+            }
+            .unwrap();
+            let mut files = file_diffs(&diff).unwrap();
+            assert_eq!(files.len(), 1);
+            assert_eq!(files[0].hunks.len(), 1);
+            files.remove(0).hunks.remove(0)
         }
-        None => {
-            // Read current file content from disk
-            let full_path = std::path::Path::new(&worktree_path).join(&file_path);
-            std::fs::read_to_string(&full_path)
-                .map_err(|e| format!("Failed to read file: {}", e))
+    }
+
+    impl Drop for TempRepo {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
         }
     }
+
+    /// Indices of the changed lines whose text is in `texts`.
+    fn changed_lines(hunk: &Hunk, texts: &[&str]) -> Vec<usize> {
+        hunk.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| l.kind == DiffLineKind::Added || l.kind == DiffLineKind::Removed)
+            .filter(|(_, l)| texts.contains(&l.text.as_str()))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    #[test]
+    fn stages_selected_lines_only() {
+        let t = TempRepo::new("stage", "a\nb\nc\nd\n");
+        t.write("a\nB\nc\nD\nE\n");
+        let hunk = t.hunk(false);
+        let lines = changed_lines(&hunk, &["b", "B", "E"]);
+
+        let patch = hunk_patch("file.txt", &hunk, Some(&lines), false).unwrap();
+        assert!(patch.contains("@@ -1,4 +1,5 @@\n a\n-b\n+B\n c\n d\n+E\n"), "{}", patch);
+
+        apply_hunk(t.path(), "file.txt", &hunk, Some(&lines), HunkAction::Stage).unwrap();
+        assert_eq!(t.index(), "a\nB\nc\nd\nE\n");
+        assert_eq!(t.workdir(), "a\nB\nc\nD\nE\n");
+    }
+
+    #[test]
+    fn unstages_selected_lines_only() {
+        let t = TempRepo::new("unstage", "a\nb\nc\nd\n");
+        t.write("a\nB\nc\nD\n");
+        t.stage_all();
+        let hunk = t.hunk(true);
+        let lines = changed_lines(&hunk, &["d", "D"]);
+
+        apply_hunk(t.path(), "file.txt", &hunk, Some(&lines), HunkAction::Unstage).unwrap();
+        assert_eq!(t.index(), "a\nB\nc\nd\n");
+        assert_eq!(t.workdir(), "a\nB\nc\nD\n");
+    }
+
+    #[test]
+    fn discards_selected_lines_only() {
+        let t = TempRepo::new("discard", "a\nb\nc\nd\n");
+        t.write("a\nB\nc\nD\nE\n");
+        let hunk = t.hunk(false);
+        let lines = changed_lines(&hunk, &["d", "D", "E"]);
+
+        apply_hunk(t.path(), "file.txt", &hunk, Some(&lines), HunkAction::Discard).unwrap();
+        assert_eq!(t.workdir(), "a\nB\nc\nd\n");
+        assert_eq!(t.index(), "a\nb\nc\nd\n");
+    }
+
+    #[test]
+    fn rejects_selection_without_changes() {
+        let t = TempRepo::new("empty", "a\nb\n");
+        t.write("a\nB\n");
+        let hunk = t.hunk(false);
+        assert!(hunk_patch("file.txt", &hunk, Some(&[0]), false).is_none());
+        assert!(apply_hunk(t.path(), "file.txt", &hunk, Some(&[0]), HunkAction::Stage).is_err());
+    }
+
+    #[test]
+    fn keeps_missing_newline_of_kept_lines() {
+        // Old side lacks the final newline: fixing it is selectable on its own
+        let t = TempRepo::new("nonl-old", "a\nb");
+        t.write("a\nb\nc\n");
+        let hunk = t.hunk(false);
+        let lines = changed_lines(&hunk, &["b"]);
+
+        let patch = hunk_patch("file.txt", &hunk, Some(&lines), false).unwrap();
+        assert!(patch.contains("-b\n\\ No newline at end of file\n+b\n"), "{}", patch);
+        apply_hunk(t.path(), "file.txt", &hunk, Some(&lines), HunkAction::Stage).unwrap();
+        assert_eq!(t.index(), "a\nb\n");
+
+        // New side lacks it: staging, then discarding, the unterminated line
+        let t = TempRepo::new("nonl-new", "a\nb\n");
+        t.write("a\nb\nc");
+        let hunk = t.hunk(false);
+        let lines = changed_lines(&hunk, &["c"]);
+        apply_hunk(t.path(), "file.txt", &hunk, Some(&lines), HunkAction::Stage).unwrap();
+        assert_eq!(t.index(), "a\nb\nc");
+
+        t.write("a\nb\nc\nd");
+        let hunk = t.hunk(false);
+        apply_hunk(t.path(), "file.txt", &hunk, None, HunkAction::Discard).unwrap();
+        assert_eq!(t.workdir(), "a\nb\nc");
+    }
+
+    /// Restore `checkpoint` into the working tree, as a working-tree restore does.
+    fn restore(t: &TempRepo, checkpoint: &Checkpoint) {
+        let snapshot = t
+            .repo
+            .revparse_single(&checkpoint.git_ref)
+            .and_then(|o| o.peel_to_commit())
+            .unwrap();
+        checkout_snapshot(&t.repo, &snapshot, &[], false).unwrap();
+    }
+
+    #[test]
+    fn discarded_file_is_recoverable_from_checkpoint() {
+        let t = TempRepo::new("discard-file", "a\n");
+        t.write("b\n");
+        fs::write(t.dir.join("new.txt"), "new\n").unwrap();
+        let checkpoint = snapshot_checkpoint("ws", t.path(), "Before discarding", None).unwrap();
+
+        discard_file(t.path(), "file.txt").unwrap();
+        discard_file(t.path(), "new.txt").unwrap();
+        assert_eq!(t.workdir(), "a\n");
+        assert!(!t.dir.join("new.txt").exists());
+
+        restore(&t, &checkpoint);
+        assert_eq!(t.workdir(), "b\n");
+        assert_eq!(fs::read_to_string(t.dir.join("new.txt")).unwrap(), "new\n");
+    }
+
+    #[test]
+    fn discard_all_is_recoverable_from_checkpoint() {
+        let t = TempRepo::new("discard-all", "a\n");
+        t.write("b\n");
+        t.stage_all();
+        fs::create_dir_all(t.dir.join("dir")).unwrap();
+        fs::write(t.dir.join("dir/new.txt"), "new\n").unwrap();
+        let checkpoint = snapshot_checkpoint("ws", t.path(), "Before discarding", None).unwrap();
+
+        discard_all(t.path(), true).unwrap();
+        assert_eq!(t.workdir(), "a\n");
+        assert_eq!(t.index(), "a\n");
+        assert!(!t.dir.join("dir").exists());
+
+        restore(&t, &checkpoint);
+        assert_eq!(t.workdir(), "b\n");
+        assert_eq!(
+            fs::read_to_string(t.dir.join("dir/new.txt")).unwrap(),
+            "new\n"
+        );
+    }
 }
@@ -0,0 +1,420 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use git2::Repository;
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, PathsMut, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{Emitter, State};
+
+use crate::commands::git::repo_status;
+use crate::models::git::{GitFileStatus, GitStatusEvent};
+use crate::state::AppState;
+
+/// Quiet period after the last relevant change before status is recomputed
+const DEBOUNCE: Duration = Duration::from_millis(250);
+/// Longest a steady stream of changes can hold back an update
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// A filesystem watcher on one worktree, shared by all its subscribers.
+pub struct GitWatcher {
+    /// Dropping the watcher disconnects its channel, ending the debounce thread
+    /// (which only holds a weak reference to add watches for new directories)
+    _watcher: Arc<Mutex<RecommendedWatcher>>,
+    subscribers: usize,
+}
+
+/// Where a worktree's status can change: the working tree itself, and the
+/// index, HEAD and refs in its git directory (shared refs live in the common
+/// directory for linked worktrees).
+struct WatchedPaths {
+    workdir: PathBuf,
+    git_dir: PathBuf,
+    refs_dir: PathBuf,
+}
+
+impl WatchedPaths {
+    /// `None` for bare repositories.
+    fn new(repo: &Repository) -> Option<Self> {
+        // Event paths are canonical on some platforms (e.g. /private/var on macOS)
+        let canonical = |p: &Path| std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+        Some(WatchedPaths {
+            workdir: canonical(repo.workdir()?),
+            git_dir: canonical(repo.path()),
+            refs_dir: canonical(&repo.commondir().join("refs")),
+        })
+    }
+}
+
+/// Start emitting `git-status-changed` events for a worktree and return its
+/// current status. Subscriptions are counted, so every call must be paired
+/// with `unsubscribe_git_status`.
+#[tauri::command]
+pub fn subscribe_git_status(
+    worktree_path: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<GitFileStatus>, String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let files = repo_status(&repo)?;
+
+    {
+        let mut data = state.0.lock().map_err(|e| e.to_string())?;
+        if let Some(watcher) = data.git_watchers.get_mut(&worktree_path) {
+            watcher.subscribers += 1;
+            return Ok(files);
+        }
+    }
+
+    // Registering a watch per directory can take a while on large trees, so
+    // it happens outside the lock
+    let watcher = start_watcher(app, repo, worktree_path.clone(), files.clone())?;
+
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    match data.git_watchers.get_mut(&worktree_path) {
+        // Lost a race with another subscriber; the new watcher is dropped
+        Some(existing) => existing.subscribers += 1,
+        None => {
+            data.git_watchers.insert(
+                worktree_path,
+                GitWatcher {
+                    _watcher: watcher,
+                    subscribers: 1,
+                },
+            );
+        }
+    }
+    Ok(files)
+}
+
+/// Drop one subscription; the watcher stops with the last one.
+#[tauri::command]
+pub fn unsubscribe_git_status(
+    worktree_path: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    if let Some(watcher) = data.git_watchers.get_mut(&worktree_path) {
+        watcher.subscribers = watcher.subscribers.saturating_sub(1);
+        if watcher.subscribers == 0 {
+            data.git_watchers.remove(&worktree_path);
+        }
+    }
+    Ok(())
+}
+
+fn start_watcher(
+    app: tauri::AppHandle,
+    repo: Repository,
+    worktree_path: String,
+    initial: Vec<GitFileStatus>,
+) -> Result<Arc<Mutex<RecommendedWatcher>>, String> {
+    let paths =
+        WatchedPaths::new(&repo).ok_or_else(|| format!("{} has no working tree", worktree_path))?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(tx).map_err(|e| format!("Failed to create watcher: {}", e))?;
+    {
+        let mut batch = watcher.paths_mut();
+        watch_tree(&mut *batch, &repo, &paths.workdir, &paths.workdir)
+            .map_err(|e| format!("Failed to watch {}: {}", worktree_path, e))?;
+        // The git directory is skipped by the walk (its objects would need a
+        // watch per fan-out directory), so only its top level and refs are
+        // watched
+        batch
+            .add(&paths.git_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch git directory: {}", e))?;
+        if paths.refs_dir.is_dir() {
+            batch
+                .add(&paths.refs_dir, RecursiveMode::Recursive)
+                .map_err(|e| format!("Failed to watch refs: {}", e))?;
+        }
+        batch
+            .commit()
+            .map_err(|e| format!("Failed to watch {}: {}", worktree_path, e))?;
+    }
+    let watcher = Arc::new(Mutex::new(watcher));
+    let handle = Arc::downgrade(&watcher);
+
+    thread::spawn(move || {
+        let mut last = initial;
+        // Ends once the watcher, and with it the sender, is dropped
+        while let Ok(event) = rx.recv() {
+            watch_new_dirs(&handle, &repo, &paths, &event);
+            if !is_relevant(&repo, &paths, &event) {
+                continue;
+            }
+
+            let deadline = Instant::now() + MAX_DELAY;
+            let mut quiet_at = Instant::now() + DEBOUNCE;
+            loop {
+                let now = Instant::now();
+                let until = quiet_at.min(deadline);
+                if now >= until {
+                    break;
+                }
+                match rx.recv_timeout(until - now) {
+                    Ok(event) => {
+                        watch_new_dirs(&handle, &repo, &paths, &event);
+                        if is_relevant(&repo, &paths, &event) {
+                            quiet_at = Instant::now() + DEBOUNCE;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+
+            // The worktree may be gone; the next change will try again
+            let Ok(files) = repo_status(&repo) else {
+                continue;
+            };
+            if files != last {
+                let _ = app.emit(
+                    "git-status-changed",
+                    GitStatusEvent {
+                        worktree_path: worktree_path.clone(),
+                        files: files.clone(),
+                    },
+                );
+                last = files;
+            }
+        }
+    });
+
+    Ok(watcher)
+}
+
+/// Add a non-recursive watch on `dir` and every directory below it, skipping
+/// `.git` and ignored directories such as node_modules or target. inotify
+/// needs one watch per directory, so watching those recursively could
+/// exhaust `max_user_watches`.
+fn watch_tree(
+    watcher: &mut dyn PathsMut,
+    repo: &Repository,
+    workdir: &Path,
+    dir: &Path,
+) -> notify::Result<()> {
+    if let Err(e) = watcher.add(dir, RecursiveMode::NonRecursive) {
+        // Deleted since it was listed
+        return if dir.exists() { Err(e) } else { Ok(()) };
+    }
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        // Symlinked directories are not followed
+        if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        let path = entry.path();
+        if !is_skipped_dir(repo, workdir, &path) {
+            watch_tree(watcher, repo, workdir, &path)?;
+        }
+    }
+    Ok(())
+}
+
+fn is_skipped_dir(repo: &Repository, workdir: &Path, path: &Path) -> bool {
+    match path.strip_prefix(workdir) {
+        Ok(rel) => rel.starts_with(".git") || repo.is_path_ignored(rel).unwrap_or(false),
+        Err(_) => true,
+    }
+}
+
+/// Extend the watch to directories created or moved into the working tree.
+fn watch_new_dirs(
+    watcher: &Weak<Mutex<RecommendedWatcher>>,
+    repo: &Repository,
+    paths: &WatchedPaths,
+    event: &notify::Result<Event>,
+) {
+    let Ok(event) = event else {
+        return;
+    };
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+    ) {
+        return;
+    }
+
+    for path in &event.paths {
+        if !path.is_dir() || is_skipped_dir(repo, &paths.workdir, path) {
+            continue;
+        }
+        let Some(watcher) = watcher.upgrade() else {
+            return;
+        };
+        let Ok(mut watcher) = watcher.lock() else {
+            return;
+        };
+        let mut batch = watcher.paths_mut();
+        let result = watch_tree(&mut *batch, repo, &paths.workdir, path);
+        if let Err(e) = result.and_then(|_| batch.commit()) {
+            eprintln!("Failed to watch {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Whether an event can change the status: in the git directory only the
+/// index, HEAD and refs count (object writes never do), and in the working
+/// tree ignored paths are skipped.
+fn is_relevant(repo: &Repository, paths: &WatchedPaths, event: &notify::Result<Event>) -> bool {
+    let event = match event {
+        Ok(event) => event,
+        // Dropped events mean changes may have been missed
+        Err(_) => return true,
+    };
+    if event.need_rescan() {
+        return true;
+    }
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+
+    event.paths.iter().any(|path| {
+        if let Ok(rel) = path.strip_prefix(&paths.git_dir) {
+            return rel == Path::new("index")
+                || rel == Path::new("HEAD")
+                || rel.starts_with("refs");
+        }
+        if path.starts_with(&paths.refs_dir) {
+            return true;
+        }
+        match path.strip_prefix(&paths.workdir) {
+            // `.git` here is the main git directory (handled above) or a
+            // linked worktree's gitfile
+            Ok(rel) if rel.starts_with(".git") => false,
+            Ok(rel) => !repo.is_path_ignored(rel).unwrap_or(false),
+            Err(_) => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, DataChange};
+    use std::fs;
+
+    /// A repository ignoring `node_modules/` and `*.log`, with one commit
+    /// and a linked worktree `wt` on branch `wt`.
+    struct TempRepo {
+        dir: PathBuf,
+        repo: Repository,
+    }
+
+    impl TempRepo {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("heroi-watcher-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(dir.join("main/src")).unwrap();
+            fs::create_dir_all(dir.join("main/node_modules/pkg")).unwrap();
+            fs::write(dir.join("main/.gitignore"), "node_modules/\n*.log\n").unwrap();
+
+            let repo = Repository::init(dir.join("main")).unwrap();
+            {
+                let mut index = repo.index().unwrap();
+                index.add_path(Path::new(".gitignore")).unwrap();
+                index.write().unwrap();
+                let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+                let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+                repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+                    .unwrap();
+            }
+            repo.worktree("wt", &dir.join("wt"), None).unwrap();
+            TempRepo { dir, repo }
+        }
+
+        fn linked(&self) -> Repository {
+            Repository::open(self.dir.join("wt")).unwrap()
+        }
+    }
+
+    impl Drop for TempRepo {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn modified(path: PathBuf) -> notify::Result<Event> {
+        Ok(Event::new(EventKind::Modify(ModifyKind::Data(DataChange::Content))).add_path(path))
+    }
+
+    #[test]
+    fn git_directory_events_count_only_for_index_head_and_refs() {
+        let temp = TempRepo::new("gitdir");
+        let paths = WatchedPaths::new(&temp.repo).unwrap();
+        let relevant =
+            |rel: &str| is_relevant(&temp.repo, &paths, &modified(paths.git_dir.join(rel)));
+
+        assert!(relevant("index"));
+        assert!(relevant("HEAD"));
+        assert!(relevant("refs/heads/main"));
+        assert!(!relevant("objects/ab/cdef0123"));
+        assert!(!relevant("index.lock"));
+        assert!(!relevant("logs/HEAD"));
+    }
+
+    #[test]
+    fn linked_worktrees_watch_their_own_git_dir_and_shared_refs() {
+        let temp = TempRepo::new("linked");
+        let repo = temp.linked();
+        let paths = WatchedPaths::new(&repo).unwrap();
+        assert!(!paths.git_dir.starts_with(&paths.workdir));
+        let relevant = |path: PathBuf| is_relevant(&repo, &paths, &modified(path));
+
+        assert!(relevant(paths.git_dir.join("index")));
+        assert!(relevant(paths.git_dir.join("HEAD")));
+        assert!(relevant(paths.refs_dir.join("heads/wt")));
+        // The gitfile pointing at the git directory
+        assert!(!relevant(paths.workdir.join(".git")));
+        assert!(relevant(paths.workdir.join("src/lib.rs")));
+    }
+
+    #[test]
+    fn working_tree_events_skip_ignored_paths() {
+        let temp = TempRepo::new("workdir");
+        let paths = WatchedPaths::new(&temp.repo).unwrap();
+        let relevant =
+            |rel: &str| is_relevant(&temp.repo, &paths, &modified(paths.workdir.join(rel)));
+
+        assert!(relevant("src/main.rs"));
+        assert!(relevant(".github/workflows/ci.yml"));
+        assert!(!relevant("debug.log"));
+        assert!(!relevant("node_modules/pkg/index.js"));
+        assert!(!relevant(".git/objects/ab/cdef0123"));
+
+        let access = Event::new(EventKind::Access(AccessKind::Any))
+            .add_path(paths.workdir.join("src/main.rs"));
+        assert!(!is_relevant(&temp.repo, &paths, &Ok(access)));
+        let outside = Event::new(EventKind::Create(CreateKind::File)).add_path(temp.dir.join("x"));
+        assert!(!is_relevant(&temp.repo, &paths, &Ok(outside)));
+        // Errors may hide changes
+        let error = Err(notify::Error::generic("overflow"));
+        assert!(is_relevant(&temp.repo, &paths, &error));
+    }
+
+    #[test]
+    fn registration_skips_git_and_ignored_directories() {
+        let temp = TempRepo::new("skip");
+        let paths = WatchedPaths::new(&temp.repo).unwrap();
+        let skipped =
+            |rel: &str| is_skipped_dir(&temp.repo, &paths.workdir, &paths.workdir.join(rel));
+
+        assert!(skipped(".git"));
+        assert!(skipped(".git/objects"));
+        assert!(skipped("node_modules"));
+        assert!(skipped("node_modules/pkg"));
+        assert!(!skipped("src"));
+        assert!(!skipped(".github"));
+        assert!(is_skipped_dir(&temp.repo, &paths.workdir, &temp.dir));
+    }
+}
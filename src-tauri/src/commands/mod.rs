@@ -1,12 +1,16 @@
 pub mod agents;
 pub mod checkpoints;
+pub mod conflicts;
 pub mod files;
 pub mod git;
+pub mod git_watcher;
 pub mod github;
 pub mod ide;
+pub mod pipelines;
 pub mod repos;
 pub mod scripts;
 pub mod settings;
+pub mod sync;
 pub mod workspace_lifecycle;
 pub mod workspaces;
 pub mod worktrees;
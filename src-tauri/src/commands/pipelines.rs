@@ -0,0 +1,544 @@
+use std::collections::{HashMap, HashSet};
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+use tauri::{Emitter, Manager, State};
+
+use crate::commands::checkpoints::{auto_checkpoint, AutoTrigger};
+use crate::commands::scripts::{
+    load_heroi_config, spawn_script, stop_running_process, workspace_script_env,
+};
+use crate::commands::workspace_lifecycle::uuid_v4;
+use crate::interpolation::resolve_script;
+use crate::models::scripts::{
+    PipelineEvent, PipelineRun, PipelineStepState, PortValue, ProcessStatus, ScriptDef,
+};
+use crate::state::AppState;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const PORT_PROBE_TIMEOUT: Duration = Duration::from_millis(200);
+/// Readiness timeout for scripts with `wait_for_port`/`wait_for_log` and no
+/// explicit `ready_timeout_secs`.
+const DEFAULT_READY_TIMEOUT_SECS: u64 = 60;
+
+/// Bring up heroi.json `run` scripts in dependency order. `targets` names the
+/// scripts or parallel groups to start (all `run` scripts when empty); their
+/// dependencies are pulled in automatically.
+///
+/// A script counts as ready once its `wait_for_port`/`wait_for_log` checks
+/// pass, or, without checks, once it exits successfully. A script that
+/// doesn't become ready in time is stopped, and dependents of a script that
+/// fails are skipped. Progress is reported through
+/// `pipeline-progress` events; this command returns as soon as the plan is
+/// validated.
+#[tauri::command(async)]
+pub fn run_pipeline(
+    workspace_id: String,
+    worktree_path: String,
+    targets: Option<Vec<String>>,
+    extra_env: HashMap<String, String>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<PipelineRun, String> {
+    let config = load_heroi_config(worktree_path.clone())?;
+    let plan = plan_pipeline(&config.run, &targets.unwrap_or_default())?;
+    let env = workspace_script_env(&state, &workspace_id, &extra_env)?;
+
+    let run = PipelineRun {
+        id: uuid_v4(),
+        workspace_id,
+        stages: plan.stages.clone(),
+    };
+
+    // Resolve placeholders up front so a typo fails the whole run instead of
+    // surfacing halfway through.
+    let scripts: Vec<ScriptDef> = config
+        .run
+        .iter()
+        .filter(|s| plan.deps.contains_key(&s.name))
+        .map(|s| resolve_script(s, &env))
+        .collect::<Result<_, _>>()?;
+
+    if let Err(e) = auto_checkpoint(&app, &run.workspace_id, AutoTrigger::BeforeRun("pipeline")) {
+        eprintln!("Auto checkpoint before pipeline failed: {}", e);
+    }
+
+    let pipeline = run.clone();
+    std::thread::spawn(move || {
+        execute_pipeline(&app, &pipeline, &scripts, &plan.deps, &worktree_path, &env)
+    });
+
+    Ok(run)
+}
+
+/// Validate the dependency graph of heroi.json `run` scripts without starting
+/// anything. Returns the stages `run_pipeline` would use.
+#[tauri::command]
+pub fn plan_script_pipeline(
+    worktree_path: String,
+    targets: Option<Vec<String>>,
+) -> Result<Vec<Vec<String>>, String> {
+    let config = load_heroi_config(worktree_path)?;
+    let plan = plan_pipeline(&config.run, &targets.unwrap_or_default())?;
+    Ok(plan.stages)
+}
+
+/// Check that `run` scripts form a valid dependency graph.
+pub(crate) fn check_pipeline(scripts: &[ScriptDef]) -> Result<(), String> {
+    plan_pipeline(scripts, &[]).map(|_| ())
+}
+
+struct PipelinePlan {
+    /// Selected scripts mapped to the scripts they wait for.
+    deps: HashMap<String, Vec<String>>,
+    stages: Vec<Vec<String>>,
+}
+
+/// Resolve `depends_on`/`parallel` into a per-script dependency list for the
+/// selected targets and group them into stages, rejecting unknown names,
+/// duplicate script names and cycles.
+///
+/// Members of a parallel group wait for the union of the group's external
+/// dependencies so that they start together.
+fn plan_pipeline(scripts: &[ScriptDef], targets: &[String]) -> Result<PipelinePlan, String> {
+    let mut names: HashSet<&str> = HashSet::new();
+    for script in scripts {
+        if !names.insert(script.name.as_str()) {
+            return Err(format!("Duplicate script name '{}'", script.name));
+        }
+    }
+
+    let mut groups: HashMap<&str, Vec<&str>> = HashMap::new();
+    for script in scripts {
+        if let Some(ref group) = script.parallel {
+            groups.entry(group.as_str()).or_default().push(script.name.as_str());
+        }
+    }
+
+    // A name in depends_on/targets refers to a script first, then a group
+    let expand = |name: &str| -> Option<Vec<String>> {
+        if names.contains(name) {
+            Some(vec![name.to_string()])
+        } else {
+            groups
+                .get(name)
+                .map(|members| members.iter().map(|m| m.to_string()).collect())
+        }
+    };
+
+    let mut direct: HashMap<String, Vec<String>> = HashMap::new();
+    for script in scripts {
+        let mut deps = Vec::new();
+        for dep in &script.depends_on {
+            let expanded = expand(dep).ok_or_else(|| {
+                format!(
+                    "Script '{}' depends on unknown script or group '{}'",
+                    script.name, dep
+                )
+            })?;
+            deps.extend(expanded);
+        }
+        direct.insert(script.name.clone(), deps);
+    }
+
+    let mut resolved: HashMap<String, Vec<String>> = HashMap::new();
+    for script in scripts {
+        let mut deps: Vec<String> = Vec::new();
+        let siblings: Vec<&str> = match script.parallel {
+            Some(ref group) => groups[group.as_str()].clone(),
+            None => vec![script.name.as_str()],
+        };
+        for member in &siblings {
+            for dep in &direct[*member] {
+                // A lone script depending on itself is a cycle, reported below
+                if script.parallel.is_some() && siblings.contains(&dep.as_str()) {
+                    return Err(format!(
+                        "Script '{}' cannot depend on '{}' in its own parallel group",
+                        member, dep
+                    ));
+                }
+                if !deps.contains(dep) {
+                    deps.push(dep.clone());
+                }
+            }
+        }
+        resolved.insert(script.name.clone(), deps);
+    }
+
+    // Select the targets plus everything they transitively depend on
+    let mut selected: HashSet<String> = HashSet::new();
+    let mut queue: Vec<String> = Vec::new();
+    if targets.is_empty() {
+        queue.extend(scripts.iter().map(|s| s.name.clone()));
+    } else {
+        for target in targets {
+            let expanded = expand(target)
+                .ok_or_else(|| format!("Unknown script or group '{}'", target))?;
+            queue.extend(expanded);
+        }
+    }
+    while let Some(name) = queue.pop() {
+        if selected.insert(name.clone()) {
+            queue.extend(resolved[&name].iter().cloned());
+        }
+    }
+
+    let mut depths: HashMap<String, usize> = HashMap::new();
+    for script in scripts {
+        if selected.contains(&script.name) {
+            let mut path = Vec::new();
+            depth_of(&script.name, &resolved, &mut depths, &mut path)?;
+        }
+    }
+
+    let stage_count = depths.values().map(|d| d + 1).max().unwrap_or(0);
+    let mut stages: Vec<Vec<String>> = vec![Vec::new(); stage_count];
+    for script in scripts {
+        if let Some(&depth) = depths.get(&script.name) {
+            stages[depth].push(script.name.clone());
+        }
+    }
+
+    resolved.retain(|name, _| selected.contains(name));
+    Ok(PipelinePlan {
+        deps: resolved,
+        stages,
+    })
+}
+
+/// Longest dependency chain below `name`, memoized in `depths`. `path` holds
+/// the scripts currently being visited so cycles can be reported.
+fn depth_of(
+    name: &str,
+    deps: &HashMap<String, Vec<String>>,
+    depths: &mut HashMap<String, usize>,
+    path: &mut Vec<String>,
+) -> Result<usize, String> {
+    if let Some(&depth) = depths.get(name) {
+        return Ok(depth);
+    }
+    if let Some(pos) = path.iter().position(|p| p == name) {
+        let mut cycle = path[pos..].to_vec();
+        cycle.push(name.to_string());
+        return Err(format!("Dependency cycle: {}", cycle.join(" -> ")));
+    }
+
+    path.push(name.to_string());
+    let mut depth = 0;
+    for dep in &deps[name] {
+        depth = depth.max(depth_of(dep, deps, depths, path)? + 1);
+    }
+    path.pop();
+
+    depths.insert(name.to_string(), depth);
+    Ok(depth)
+}
+
+enum Node {
+    Pending,
+    Started(StartedNode),
+    Ready,
+    Failed,
+    Skipped,
+}
+
+struct StartedNode {
+    process_id: String,
+    since: Instant,
+    log_offset: u64,
+    log_seen: bool,
+}
+
+fn execute_pipeline(
+    app: &tauri::AppHandle,
+    run: &PipelineRun,
+    scripts: &[ScriptDef],
+    deps: &HashMap<String, Vec<String>>,
+    worktree_path: &str,
+    env: &HashMap<String, String>,
+) {
+    let state = app.state::<AppState>();
+    let mut nodes: HashMap<String, Node> = scripts
+        .iter()
+        .map(|s| (s.name.clone(), Node::Pending))
+        .collect();
+
+    loop {
+        // Start (or skip) every pending script whose dependencies are settled
+        for script in scripts {
+            if !matches!(nodes[&script.name], Node::Pending) {
+                continue;
+            }
+            let script_deps = &deps[&script.name];
+            let blocked = script_deps
+                .iter()
+                .any(|d| matches!(nodes[d], Node::Failed | Node::Skipped));
+            let ready = script_deps.iter().all(|d| matches!(nodes[d], Node::Ready));
+
+            if blocked {
+                nodes.insert(script.name.clone(), Node::Skipped);
+                emit_progress(app, run, Some(&script.name), PipelineStepState::Skipped, None, None);
+            } else if ready {
+                match spawn_script(app, &state, &run.workspace_id, script, worktree_path, env) {
+                    Ok(process) => {
+                        emit_progress(
+                            app,
+                            run,
+                            Some(&script.name),
+                            PipelineStepState::Started,
+                            Some(&process.id),
+                            None,
+                        );
+                        nodes.insert(
+                            script.name.clone(),
+                            Node::Started(StartedNode {
+                                process_id: process.id,
+                                since: Instant::now(),
+                                log_offset: 0,
+                                log_seen: false,
+                            }),
+                        );
+                    }
+                    Err(e) => {
+                        nodes.insert(script.name.clone(), Node::Failed);
+                        emit_progress(app, run, Some(&script.name), PipelineStepState::Failed, None, Some(e));
+                    }
+                }
+            }
+        }
+
+        // Check readiness of everything in flight
+        for script in scripts {
+            let Some(Node::Started(node)) = nodes.get_mut(&script.name) else {
+                continue;
+            };
+            let process_id = node.process_id.clone();
+            match check_ready(&state, script, node) {
+                Some(Ok(())) => {
+                    nodes.insert(script.name.clone(), Node::Ready);
+                    emit_progress(app, run, Some(&script.name), PipelineStepState::Ready, Some(&process_id), None);
+                }
+                Some(Err(mut e)) => {
+                    // A script that timed out is still running; stop it
+                    // rather than leave it behind with its dependents skipped
+                    if let Err(stop) = stop_running_process(&state, &process_id) {
+                        e = format!("{}; failed to stop it: {}", e, stop);
+                    }
+                    nodes.insert(script.name.clone(), Node::Failed);
+                    emit_progress(app, run, Some(&script.name), PipelineStepState::Failed, Some(&process_id), Some(e));
+                }
+                None => {}
+            }
+        }
+
+        if !nodes
+            .values()
+            .any(|n| matches!(n, Node::Pending | Node::Started(_)))
+        {
+            break;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    let all_ready = nodes.values().all(|n| matches!(n, Node::Ready));
+    let final_state = if all_ready {
+        PipelineStepState::Ready
+    } else {
+        PipelineStepState::Failed
+    };
+    emit_progress(app, run, None, final_state, None, None);
+}
+
+/// `Some(Ok)` once the script is ready, `Some(Err)` if it can no longer
+/// become ready, `None` while still waiting.
+fn check_ready(state: &AppState, script: &ScriptDef, node: &mut StartedNode) -> Option<Result<(), String>> {
+    let process = {
+        let data = state.0.lock().ok()?;
+        if let (Some(pattern), Some(log)) = (
+            script.wait_for_log.as_ref(),
+            data.process_logs.get(&node.process_id),
+        ) {
+            let chunk = log.since(node.log_offset);
+            node.log_offset = chunk.next_offset;
+            if chunk.lines.iter().any(|l| l.text.contains(pattern.as_str())) {
+                node.log_seen = true;
+            }
+        }
+        data.running_processes
+            .iter()
+            .find(|p| p.id == node.process_id)
+            .cloned()?
+    };
+
+    let has_checks = script.wait_for_port.is_some() || script.wait_for_log.is_some();
+    let elapsed = node.since.elapsed().as_secs();
+
+    if has_checks {
+        let log_ok = script.wait_for_log.is_none() || node.log_seen;
+        let port_ok = script
+            .wait_for_port
+            .as_ref()
+            .and_then(PortValue::as_number)
+            .is_none_or(is_port_open);
+        if log_ok && port_ok {
+            return Some(Ok(()));
+        }
+        if process.status != ProcessStatus::Running {
+            return Some(Err("Exited before becoming ready".into()));
+        }
+        let timeout = script.ready_timeout_secs.unwrap_or(DEFAULT_READY_TIMEOUT_SECS);
+        if elapsed >= timeout {
+            return Some(Err(format!("Not ready after {}s", timeout)));
+        }
+        return None;
+    }
+
+    match process.status {
+        ProcessStatus::Exited if !process.stopped_by_user => Some(Ok(())),
+        ProcessStatus::Exited => Some(Err("Stopped before finishing".into())),
+        ProcessStatus::Failed => Some(Err(match (process.exit_code, process.signal) {
+            (_, Some(signal)) => format!("Killed by signal {}", signal),
+            (Some(code), _) => format!("Exited with code {}", code),
+            _ => "Exited abnormally".into(),
+        })),
+        ProcessStatus::Running => match script.ready_timeout_secs {
+            Some(timeout) if elapsed >= timeout => {
+                Some(Err(format!("Did not finish within {}s", timeout)))
+            }
+            _ => None,
+        },
+    }
+}
+
+fn is_port_open(port: u16) -> bool {
+    TcpStream::connect_timeout(&SocketAddr::from(([127, 0, 0, 1], port)), PORT_PROBE_TIMEOUT).is_ok()
+}
+
+fn emit_progress(
+    app: &tauri::AppHandle,
+    run: &PipelineRun,
+    script: Option<&str>,
+    state: PipelineStepState,
+    process_id: Option<&str>,
+    message: Option<String>,
+) {
+    let _ = app.emit(
+        "pipeline-progress",
+        PipelineEvent {
+            pipeline_id: run.id.clone(),
+            workspace_id: run.workspace_id.clone(),
+            script: script.map(|s| s.to_string()),
+            state,
+            process_id: process_id.map(|p| p.to_string()),
+            message,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(name: &str, depends_on: &[&str], parallel: Option<&str>) -> ScriptDef {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "command": "true",
+            "depends_on": depends_on,
+            "parallel": parallel,
+        }))
+        .unwrap()
+    }
+
+    fn plan(scripts: &[ScriptDef], targets: &[&str]) -> Result<PipelinePlan, String> {
+        let targets: Vec<String> = targets.iter().map(|t| t.to_string()).collect();
+        plan_pipeline(scripts, &targets)
+    }
+
+    #[test]
+    fn orders_dependency_chain_into_stages() {
+        let scripts = [
+            script("web", &["api"], None),
+            script("api", &["db"], None),
+            script("db", &[], None),
+        ];
+        let plan = plan(&scripts, &[]).unwrap();
+        assert_eq!(plan.stages, vec![vec!["db"], vec!["api"], vec!["web"]]);
+        assert_eq!(plan.deps["web"], vec!["api"]);
+    }
+
+    #[test]
+    fn parallel_group_starts_together() {
+        let scripts = [
+            script("db", &[], None),
+            script("worker", &["db"], Some("backend")),
+            script("api", &[], Some("backend")),
+            script("web", &["backend"], None),
+        ];
+        let plan = plan(&scripts, &[]).unwrap();
+        // `api` waits for `db` too, so it starts alongside `worker`
+        assert_eq!(
+            plan.stages,
+            vec![vec!["db"], vec!["worker", "api"], vec!["web"]]
+        );
+        assert_eq!(plan.deps["api"], vec!["db"]);
+        assert_eq!(plan.deps["web"], vec!["worker", "api"]);
+    }
+
+    #[test]
+    fn targets_pull_in_only_their_dependencies() {
+        let scripts = [
+            script("db", &[], None),
+            script("api", &["db"], None),
+            script("web", &["api"], None),
+            script("docs", &[], None),
+        ];
+        let plan = plan(&scripts, &["api"]).unwrap();
+        assert_eq!(plan.stages, vec![vec!["db"], vec!["api"]]);
+        assert!(!plan.deps.contains_key("web"));
+        assert!(!plan.deps.contains_key("docs"));
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let scripts = [
+            script("a", &["b"], None),
+            script("b", &["c"], None),
+            script("c", &["a"], None),
+        ];
+        let err = plan(&scripts, &[]).err().unwrap();
+        assert_eq!(err, "Dependency cycle: a -> b -> c -> a");
+
+        let scripts = [script("a", &["a"], None)];
+        assert_eq!(plan(&scripts, &[]).err().unwrap(), "Dependency cycle: a -> a");
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        let scripts = [script("api", &["db"], None)];
+        assert_eq!(
+            plan(&scripts, &[]).err().unwrap(),
+            "Script 'api' depends on unknown script or group 'db'"
+        );
+
+        let scripts = [script("api", &[], None)];
+        assert_eq!(
+            plan(&scripts, &["web"]).err().unwrap(),
+            "Unknown script or group 'web'"
+        );
+    }
+
+    #[test]
+    fn rejects_duplicates_and_dependencies_inside_a_group() {
+        let scripts = [script("api", &[], None), script("api", &[], None)];
+        assert_eq!(
+            plan(&scripts, &[]).err().unwrap(),
+            "Duplicate script name 'api'"
+        );
+
+        let scripts = [
+            script("api", &["worker"], Some("backend")),
+            script("worker", &[], Some("backend")),
+        ];
+        assert!(plan(&scripts, &[]).is_err());
+    }
+}
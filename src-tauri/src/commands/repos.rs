@@ -3,6 +3,7 @@ use std::path::Path;
 use tauri::State;
 use tauri_plugin_store::StoreExt;
 
+use crate::commands::workspace_lifecycle::validate_port_settings;
 use crate::models::repo::RepoEntry;
 use crate::state::AppState;
 
@@ -28,6 +29,8 @@ pub fn add_repo(
     let entry = RepoEntry {
         path: path.clone(),
         name,
+        port_start: None,
+        port_range_size: None,
     };
 
     let mut data = state.0.lock().map_err(|e| e.to_string())?;
@@ -62,6 +65,36 @@ pub fn remove_repo(
     Ok(())
 }
 
+/// Set where workspace port blocks for a repo start and how many ports each
+/// block has. `None` restores the default. Existing workspaces keep their
+/// block until reassigned.
+#[tauri::command]
+pub fn set_repo_port_settings(
+    path: String,
+    port_start: Option<u16>,
+    port_range_size: Option<u16>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<RepoEntry, String> {
+    validate_port_settings(port_start, port_range_size)?;
+
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    let repo = data
+        .repos
+        .iter_mut()
+        .find(|r| r.path == path)
+        .ok_or_else(|| format!("Repository '{}' not found", path))?;
+    repo.port_start = port_start;
+    repo.port_range_size = port_range_size;
+
+    let entry = repo.clone();
+    let repos = data.repos.clone();
+    drop(data);
+
+    persist_repos(&app, &repos)?;
+    Ok(entry)
+}
+
 #[tauri::command]
 pub fn list_repos(state: State<'_, AppState>) -> Result<Vec<RepoEntry>, String> {
     let data = state.0.lock().map_err(|e| e.to_string())?;
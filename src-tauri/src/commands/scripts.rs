@@ -1,103 +1,299 @@
 use std::collections::HashMap;
-use std::path::Path;
-use std::process::Command;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use tauri::State;
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_store::StoreExt;
 
-use crate::models::scripts::{HeroiConfig, ProcessStatus, RunningProcess, ScriptDef};
+use crate::commands::checkpoints::{auto_checkpoint, AutoTrigger};
+use crate::commands::pipelines::check_pipeline;
+use crate::commands::workspace_lifecycle::now_iso8601_pub;
+use crate::heroi_config;
+use crate::interpolation::{resolve_env, resolve_script};
+use crate::models::scripts::{
+    ConfigDiagnostic, DiagnosticSeverity, HeroiConfig, LogStream, ProcessLog, ProcessLogChunk,
+    ProcessOutputEvent, ProcessStatus, ResolvedScript, RestartPolicy, RunningProcess, ScriptDef,
+    ScriptPhase, ScriptRunLog, ScriptStepEvent, ScriptStepResult,
+};
 use crate::state::AppState;
 
-/// Load heroi.json from a workspace's worktree path.
+/// How long `stop_process` waits after SIGTERM before escalating to SIGKILL.
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+/// How long to wait for the process group to disappear after SIGKILL.
+const KILL_WAIT: Duration = Duration::from_secs(2);
+/// Restart attempts allowed for `restart: on-failure` unless overridden.
+const DEFAULT_MAX_RESTARTS: u32 = 3;
+/// Delay before a restart, multiplied by the attempt number.
+const RESTART_BACKOFF: Duration = Duration::from_secs(1);
+/// How much of a setup/archive step's output is kept in its result log.
+const STEP_OUTPUT_TAIL_BYTES: usize = 16 * 1024;
+/// How long a setup/archive step may run unless `timeout_secs` overrides it.
+const DEFAULT_STEP_TIMEOUT_SECS: u64 = 600;
+
+/// The setup or archive step a workspace is currently running.
+pub struct RunningStep {
+    /// Process group leader of the step
+    pub pid: u32,
+    /// Set by `stop_script_steps` so the step is reported as stopped
+    pub stop_requested: bool,
+}
+
+/// Load heroi.json from a workspace's worktree path, migrated to the current
+/// format version and with heroi.local.json merged on top.
 #[tauri::command]
 pub fn load_heroi_config(worktree_path: String) -> Result<HeroiConfig, String> {
-    let config_path = Path::new(&worktree_path).join("heroi.json");
-    if !config_path.exists() {
-        return Ok(HeroiConfig::default());
+    let loaded = heroi_config::load(Path::new(&worktree_path));
+    match loaded.config {
+        Some(config) => Ok(config),
+        None => {
+            let errors: Vec<String> = loaded
+                .diagnostics
+                .iter()
+                .filter(|d| d.severity == DiagnosticSeverity::Error)
+                .map(heroi_config::format_diagnostic)
+                .collect();
+            Err(format!("Invalid heroi.json: {}", errors.join("; ")))
+        }
     }
-    let content =
-        std::fs::read_to_string(&config_path).map_err(|e| format!("Failed to read heroi.json: {}", e))?;
-    let config: HeroiConfig =
-        serde_json::from_str(&content).map_err(|e| format!("Invalid heroi.json: {}", e))?;
-    Ok(config)
 }
 
-/// Save heroi.json to a workspace's worktree path.
+/// Save heroi.json to a workspace's worktree path. heroi.local.json is left
+/// untouched.
 #[tauri::command]
-pub fn save_heroi_config(worktree_path: String, config: HeroiConfig) -> Result<(), String> {
-    let config_path = Path::new(&worktree_path).join("heroi.json");
+pub fn save_heroi_config(worktree_path: String, mut config: HeroiConfig) -> Result<(), String> {
+    let config_path = Path::new(&worktree_path).join(heroi_config::CONFIG_FILE);
+    config.version = heroi_config::CURRENT_VERSION;
     let content =
         serde_json::to_string_pretty(&config).map_err(|e| format!("Failed to serialize config: {}", e))?;
     std::fs::write(&config_path, content).map_err(|e| format!("Failed to write heroi.json: {}", e))?;
     Ok(())
 }
 
-/// Run a script in the background. Returns a RunningProcess entry.
+/// Check heroi.json and heroi.local.json without running anything. Reports
+/// syntax and type errors, unknown keys (as warnings), named ports that map
+/// to the same `HEROI_PORT_<NAME>` var and invalid `run` script
+/// dependencies, each with its file and position.
+#[tauri::command]
+pub fn validate_heroi_config(worktree_path: String) -> Vec<ConfigDiagnostic> {
+    let loaded = heroi_config::load(Path::new(&worktree_path));
+    let mut diagnostics = loaded.diagnostics;
+
+    if let Some(config) = loaded.config {
+        if let Err(e) = check_pipeline(&config.run) {
+            diagnostics.push(heroi_config::diagnostic(
+                DiagnosticSeverity::Error,
+                heroi_config::CONFIG_FILE.to_string(),
+                "run".to_string(),
+                None,
+                e,
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+/// The JSON Schema for heroi.json, generated from `HeroiConfig`.
 #[tauri::command]
+pub fn get_heroi_config_schema() -> Result<serde_json::Value, String> {
+    serde_json::to_value(schemars::schema_for!(HeroiConfig))
+        .map_err(|e| format!("Failed to serialize schema: {}", e))
+}
+
+/// Run a script in the background. Returns a RunningProcess entry.
+/// `${...}` placeholders in the script and in `extra_env` are resolved
+/// against the workspace's variables first, and the workspace is
+/// checkpointed beforehand if its auto-checkpoint policy asks for it.
+#[tauri::command(async)]
 pub fn run_script(
     workspace_id: String,
     script: ScriptDef,
     worktree_path: String,
     extra_env: HashMap<String, String>,
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<RunningProcess, String> {
-    let (cmd, args) = resolve_platform_command(&script);
+    let env = workspace_script_env(&state, &workspace_id, &extra_env)?;
+    let script = resolve_script(&script, &env)?;
+
+    let what = format!("'{}'", script.name);
+    if let Err(e) = auto_checkpoint(&app, &workspace_id, AutoTrigger::BeforeRun(&what)) {
+        eprintln!("Auto checkpoint before {} failed: {}", what, e);
+    }
+    spawn_script(&app, &state, &workspace_id, &script, &worktree_path, &env)
+}
+
+/// Show how a script would be run in a workspace: command, args, working
+/// directory and environment with every placeholder resolved.
+#[tauri::command]
+pub fn preview_script(
+    workspace_id: String,
+    script: ScriptDef,
+    state: State<'_, AppState>,
+) -> Result<ResolvedScript, String> {
+    let worktree_path = {
+        let data = state.0.lock().map_err(|e| e.to_string())?;
+        data.workspaces
+            .iter()
+            .find(|w| w.id == workspace_id)
+            .map(|w| w.worktree_path.clone())
+            .ok_or_else(|| format!("Workspace '{}' not found", workspace_id))?
+    };
 
-    let working_dir = if let Some(ref cwd) = script.cwd {
-        Path::new(&worktree_path).join(cwd)
-    } else {
-        Path::new(&worktree_path).to_path_buf()
+    let config = load_heroi_config(worktree_path.clone())?;
+    let env = workspace_script_env(&state, &workspace_id, &config.env)?;
+    let script = resolve_script(&script, &env)?;
+    let (command, args) = resolve_platform_command(&script);
+
+    Ok(ResolvedScript {
+        name: script.name.clone(),
+        command_line: format_command_line(&command, &args),
+        cwd: working_dir(&script, &worktree_path).to_string_lossy().to_string(),
+        command,
+        args,
+        env,
+    })
+}
+
+/// A workspace's `env_vars` with `overlay` (heroi.json `env`, caller-supplied
+/// variables) interpolated against them and merged on top.
+pub(crate) fn workspace_script_env(
+    state: &AppState,
+    workspace_id: &str,
+    overlay: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    let base = {
+        let data = state.0.lock().map_err(|e| e.to_string())?;
+        data.workspaces
+            .iter()
+            .find(|w| w.id == workspace_id)
+            .map(|w| w.env_vars.clone())
+            .unwrap_or_default()
     };
+    resolve_env(&base, overlay)
+}
+
+/// Spawn a script as a supervised background process: output is captured
+/// into its log buffer and its exit (and any `restart: on-failure` respawn)
+/// is tracked on the returned `RunningProcess`. `script` and `env` must
+/// already have their placeholders resolved.
+pub(crate) fn spawn_script(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    workspace_id: &str,
+    script: &ScriptDef,
+    worktree_path: &str,
+    env: &HashMap<String, String>,
+) -> Result<RunningProcess, String> {
+    let (cmd, args) = resolve_platform_command(script);
+    let working_dir = working_dir(script, worktree_path);
 
     if !working_dir.exists() {
         return Err(format!("Working directory does not exist: {}", working_dir.display()));
     }
 
-    let child = Command::new(&cmd)
-        .args(&args)
-        .current_dir(&working_dir)
-        .envs(&extra_env)
-        .spawn()
-        .map_err(|e| format!("Failed to spawn '{}': {}", cmd, e))?;
+    let spec = SpawnSpec {
+        cmd,
+        args,
+        working_dir,
+        env: env.clone(),
+        restart: script.restart.clone(),
+        max_restarts: script.max_restarts.unwrap_or(DEFAULT_MAX_RESTARTS),
+    };
 
+    let mut child = spec.spawn()?;
     let pid = child.id();
+    let process_id = format!("{}-{}", workspace_id, pid);
+    attach_log_readers(app, &process_id, &mut child);
 
     let process = RunningProcess {
-        id: format!("{}-{}", workspace_id, pid),
-        workspace_id,
-        script_name: script.name,
+        id: process_id.clone(),
+        workspace_id: workspace_id.to_string(),
+        script_name: script.name.clone(),
         pid,
         status: ProcessStatus::Running,
+        started_at: now_iso8601_pub(),
+        ended_at: None,
+        duration_ms: None,
+        exit_code: None,
+        signal: None,
+        stopped_by_user: false,
+        restarts: 0,
     };
 
     let mut data = state.0.lock().map_err(|e| e.to_string())?;
     data.running_processes.push(process.clone());
+    drop(data);
+
+    supervise_process(app.clone(), process_id, child, spec);
 
     Ok(process)
 }
 
-/// Stop a running process by its ID.
-#[tauri::command]
+/// Everything needed to (re)spawn a script's process.
+struct SpawnSpec {
+    cmd: String,
+    args: Vec<String>,
+    working_dir: PathBuf,
+    env: HashMap<String, String>,
+    restart: RestartPolicy,
+    max_restarts: u32,
+}
+
+impl SpawnSpec {
+    fn spawn(&self) -> Result<Child, String> {
+        let mut command = Command::new(&self.cmd);
+        command
+            .args(&self.args)
+            .current_dir(&self.working_dir)
+            .envs(&self.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // Make the script the leader of its own process group so stopping it
+        // reaches every descendant (e.g. the node/vite children of `npm run dev`).
+        set_new_process_group(&mut command);
+
+        command
+            .spawn()
+            .map_err(|e| format!("Failed to spawn '{}': {}", self.cmd, e))
+    }
+}
+
+/// Stop a running process and all of its descendants. Sends a graceful
+/// termination request to the process group, escalates to a forced kill
+/// after `STOP_GRACE_PERIOD`, and only returns once the group is gone.
+#[tauri::command(async)]
 pub fn stop_process(process_id: String, state: State<'_, AppState>) -> Result<(), String> {
-    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+    stop_running_process(&state, &process_id)
+}
 
-    let process = data
-        .running_processes
-        .iter()
-        .find(|p| p.id == process_id)
-        .cloned()
-        .ok_or_else(|| format!("Process '{}' not found", process_id))?;
+/// `stop_process` for internal callers; a process that already exited is
+/// left alone.
+pub(crate) fn stop_running_process(state: &AppState, process_id: &str) -> Result<(), String> {
+    let pid = {
+        let mut data = state.0.lock().map_err(|e| e.to_string())?;
 
-    // Kill the process
-    kill_process(process.pid)?;
+        let process = data
+            .running_processes
+            .iter_mut()
+            .find(|p| p.id == process_id)
+            .ok_or_else(|| format!("Process '{}' not found", process_id))?;
 
-    // Update status
-    for p in data.running_processes.iter_mut() {
-        if p.id == process_id {
-            p.status = ProcessStatus::Exited;
+        if process.status != ProcessStatus::Running {
+            return Ok(());
         }
-    }
 
-    Ok(())
+        // Mark the stop as intentional before signalling; the supervisor
+        // records the final status once the process has actually exited.
+        process.stopped_by_user = true;
+        process.pid
+    };
+
+    terminate_process_trees(&[pid])
 }
 
 /// List running processes, optionally filtered by workspace.
@@ -106,14 +302,7 @@ pub fn list_running_processes(
     workspace_id: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<Vec<RunningProcess>, String> {
-    let mut data = state.0.lock().map_err(|e| e.to_string())?;
-
-    // Check which processes are still alive and update status
-    for p in data.running_processes.iter_mut() {
-        if p.status == ProcessStatus::Running && !is_process_alive(p.pid) {
-            p.status = ProcessStatus::Exited;
-        }
-    }
+    let data = state.0.lock().map_err(|e| e.to_string())?;
 
     let processes: Vec<RunningProcess> = match workspace_id {
         Some(ref wid) => data
@@ -128,15 +317,627 @@ pub fn list_running_processes(
     Ok(processes)
 }
 
-/// Clean up exited processes from state.
+/// Clean up exited processes (and their captured output) from state.
 #[tauri::command]
 pub fn cleanup_processes(state: State<'_, AppState>) -> Result<(), String> {
     let mut data = state.0.lock().map_err(|e| e.to_string())?;
     data.running_processes
         .retain(|p| p.status == ProcessStatus::Running);
+    let live: Vec<String> = data.running_processes.iter().map(|p| p.id.clone()).collect();
+    data.process_logs.retain(|id, _| live.contains(id));
+    Ok(())
+}
+
+/// Get captured output of a process, starting at `since_offset` (or the
+/// oldest retained line when omitted).
+#[tauri::command]
+pub fn get_process_logs(
+    process_id: String,
+    since_offset: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<ProcessLogChunk, String> {
+    let data = state.0.lock().map_err(|e| e.to_string())?;
+    if !data.running_processes.iter().any(|p| p.id == process_id) {
+        return Err(format!("Process '{}' not found", process_id));
+    }
+    let chunk = match data.process_logs.get(&process_id) {
+        Some(log) => log.since(since_offset.unwrap_or(0)),
+        None => ProcessLogChunk {
+            lines: Vec::new(),
+            next_offset: 0,
+            truncated: false,
+        },
+    };
+    Ok(chunk)
+}
+
+/// Stop every running process that belongs to a workspace.
+pub(crate) fn stop_workspace_processes(state: &AppState, workspace_id: &str) -> Result<(), String> {
+    let pids: Vec<u32> = {
+        let mut data = state.0.lock().map_err(|e| e.to_string())?;
+        data.running_processes
+            .iter_mut()
+            .filter(|p| p.workspace_id == workspace_id && p.status == ProcessStatus::Running)
+            .map(|p| {
+                p.stopped_by_user = true;
+                p.pid
+            })
+            .collect()
+    };
+
+    terminate_process_trees(&pids)
+}
+
+/// Stop the setup or archive step a workspace is running. The step is
+/// reported as failed, so the remaining steps are skipped.
+#[tauri::command(async)]
+pub fn stop_script_steps(workspace_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let pid = {
+        let mut data = state.0.lock().map_err(|e| e.to_string())?;
+        match data.running_steps.get_mut(&workspace_id) {
+            Some(step) => {
+                step.stop_requested = true;
+                step.pid
+            }
+            None => return Ok(()),
+        }
+    };
+
+    terminate_process_trees(&[pid])
+}
+
+/// Get the last persisted setup or archive log for a workspace.
+#[tauri::command]
+pub fn get_script_log(
+    workspace_id: String,
+    phase: ScriptPhase,
+    app: tauri::AppHandle,
+) -> Result<Option<ScriptRunLog>, String> {
+    let store = app
+        .store("heroi-store.json")
+        .map_err(|e| e.to_string())?;
+    match store.get(script_log_key(&workspace_id, phase)) {
+        Some(val) => serde_json::from_value(val.clone())
+            .map(Some)
+            .map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+/// Run heroi.json steps one after another in `worktree_path`, stopping at the
+/// first failure. Placeholders in each step are resolved against `env`; a step
+/// that fails to resolve counts as failed. Emits a `script-step-progress` event before and after each
+/// step, then persists and returns the per-step result log.
+///
+/// Each step runs in its own process group and is killed with its children
+/// once it exceeds its timeout or is stopped through `stop_script_steps`.
+pub(crate) fn run_script_steps(
+    app: &tauri::AppHandle,
+    workspace_id: &str,
+    phase: ScriptPhase,
+    steps: &[ScriptDef],
+    worktree_path: &str,
+    env: &HashMap<String, String>,
+) -> ScriptRunLog {
+    let started_at = now_iso8601_pub();
+    let mut results = Vec::new();
+    let mut success = true;
+
+    for (index, step) in steps.iter().enumerate() {
+        let _ = app.emit(
+            "script-step-progress",
+            ScriptStepEvent {
+                workspace_id: workspace_id.to_string(),
+                phase,
+                index,
+                total: steps.len(),
+                name: step.name.clone(),
+                result: None,
+            },
+        );
+
+        let result = run_step(app, workspace_id, step, worktree_path, env);
+        let failed = !result.success;
+
+        let _ = app.emit(
+            "script-step-progress",
+            ScriptStepEvent {
+                workspace_id: workspace_id.to_string(),
+                phase,
+                index,
+                total: steps.len(),
+                name: step.name.clone(),
+                result: Some(result.clone()),
+            },
+        );
+
+        results.push(result);
+        if failed {
+            success = false;
+            break;
+        }
+    }
+
+    let log = ScriptRunLog {
+        workspace_id: workspace_id.to_string(),
+        phase,
+        started_at,
+        finished_at: now_iso8601_pub(),
+        success,
+        steps: results,
+    };
+
+    if let Err(e) = persist_script_log(app, &log) {
+        eprintln!("Failed to persist {} log: {}", phase.as_key(), e);
+    }
+
+    log
+}
+
+/// Record a setup or archive run that could not start at all, e.g. because
+/// heroi.json or its env failed to load, as a failed single-step log.
+pub(crate) fn record_script_error(
+    app: &tauri::AppHandle,
+    workspace_id: &str,
+    phase: ScriptPhase,
+    error: String,
+) -> ScriptRunLog {
+    let now = now_iso8601_pub();
+    let result = ScriptStepResult {
+        name: "heroi.json".to_string(),
+        command_line: String::new(),
+        success: false,
+        exit_code: None,
+        duration_ms: 0,
+        output: String::new(),
+        error: Some(error),
+    };
+
+    let _ = app.emit(
+        "script-step-progress",
+        ScriptStepEvent {
+            workspace_id: workspace_id.to_string(),
+            phase,
+            index: 0,
+            total: 1,
+            name: result.name.clone(),
+            result: Some(result.clone()),
+        },
+    );
+
+    let log = ScriptRunLog {
+        workspace_id: workspace_id.to_string(),
+        phase,
+        started_at: now.clone(),
+        finished_at: now,
+        success: false,
+        steps: vec![result],
+    };
+    if let Err(e) = persist_script_log(app, &log) {
+        eprintln!("Failed to persist {} log: {}", phase.as_key(), e);
+    }
+    log
+}
+
+fn run_step(
+    app: &tauri::AppHandle,
+    workspace_id: &str,
+    step: &ScriptDef,
+    worktree_path: &str,
+    env: &HashMap<String, String>,
+) -> ScriptStepResult {
+    let step = match resolve_script(step, env) {
+        Ok(step) => step,
+        Err(e) => {
+            return ScriptStepResult {
+                name: step.name.clone(),
+                command_line: step.command.clone(),
+                success: false,
+                exit_code: None,
+                duration_ms: 0,
+                output: String::new(),
+                error: Some(e),
+            }
+        }
+    };
+    let (cmd, args) = resolve_platform_command(&step);
+    let command_line = format_command_line(&cmd, &args);
+    let working_dir = working_dir(&step, worktree_path);
+    let timeout = Duration::from_secs(step.timeout_secs.unwrap_or(DEFAULT_STEP_TIMEOUT_SECS));
+
+    let mut command = Command::new(&cmd);
+    command
+        .args(&args)
+        .current_dir(&working_dir)
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    set_new_process_group(&mut command);
+
+    let started = Instant::now();
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            return ScriptStepResult {
+                name: step.name.clone(),
+                command_line,
+                success: false,
+                exit_code: None,
+                duration_ms: 0,
+                output: String::new(),
+                error: Some(format!(
+                    "Failed to spawn '{}' in {}: {}",
+                    cmd,
+                    working_dir.display(),
+                    e
+                )),
+            }
+        }
+    };
+    let pid = child.id();
+    let readers: Vec<_> = [
+        child.stdout.take().map(spawn_tail_reader),
+        child.stderr.take().map(spawn_tail_reader),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let state = app.state::<AppState>();
+    if let Ok(mut data) = state.0.lock() {
+        data.running_steps.insert(
+            workspace_id.to_string(),
+            RunningStep {
+                pid,
+                stop_requested: false,
+            },
+        );
+    }
+
+    // Reap the leader on its own thread so the group can disappear while
+    // this one enforces the timeout.
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(child.wait());
+    });
+
+    let mut error = None;
+    let status = match rx.recv_timeout(timeout) {
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            let reason = format!("Timed out after {}s", timeout.as_secs());
+            error = Some(match terminate_process_trees(&[pid]) {
+                Ok(()) => reason,
+                Err(e) => format!("{}; {}", reason, e),
+            });
+            rx.recv().ok()
+        }
+        result => result.ok(),
+    }
+    .and_then(|result| result.ok());
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    if let Ok(mut data) = state.0.lock() {
+        if let Some(running) = data.running_steps.remove(workspace_id) {
+            if running.stop_requested && error.is_none() {
+                error = Some("Stopped".to_string());
+            }
+        }
+    }
+
+    // Background processes left behind by the step may still hold the
+    // pipes open; don't wait on them for more than a moment.
+    let deadline = Instant::now() + KILL_WAIT;
+    while readers.iter().any(|(handle, _)| !handle.is_finished()) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let mut text = String::new();
+    for (_, buf) in &readers {
+        if let Ok(buf) = buf.lock() {
+            text.push_str(&String::from_utf8_lossy(&buf));
+        }
+    }
+
+    ScriptStepResult {
+        name: step.name.clone(),
+        command_line,
+        success: error.is_none() && status.is_some_and(|s| s.success()),
+        exit_code: status.and_then(|s| s.code()),
+        duration_ms,
+        output: tail(&text, STEP_OUTPUT_TAIL_BYTES).to_string(),
+        error,
+    }
+}
+
+/// Read a step's output stream on a background thread, keeping only its last
+/// `STEP_OUTPUT_TAIL_BYTES` bytes.
+fn spawn_tail_reader<R: Read + Send + 'static>(
+    mut reader: R,
+) -> (std::thread::JoinHandle<()>, Arc<Mutex<Vec<u8>>>) {
+    let buf = Arc::new(Mutex::new(Vec::new()));
+    let shared = Arc::clone(&buf);
+    let handle = std::thread::spawn(move || {
+        let mut chunk = [0u8; 8192];
+        loop {
+            let n = match reader.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            let mut buf = match shared.lock() {
+                Ok(buf) => buf,
+                Err(_) => break,
+            };
+            buf.extend_from_slice(&chunk[..n]);
+            if buf.len() > 2 * STEP_OUTPUT_TAIL_BYTES {
+                let excess = buf.len() - STEP_OUTPUT_TAIL_BYTES;
+                buf.drain(..excess);
+            }
+        }
+    });
+    (handle, buf)
+}
+
+fn working_dir(script: &ScriptDef, worktree_path: &str) -> PathBuf {
+    match script.cwd {
+        Some(ref cwd) => Path::new(worktree_path).join(cwd),
+        None => Path::new(worktree_path).to_path_buf(),
+    }
+}
+
+/// Render a command and its args as a single shell-like line, quoting args
+/// that contain whitespace or quotes.
+fn format_command_line(cmd: &str, args: &[String]) -> String {
+    std::iter::once(cmd)
+        .chain(args.iter().map(|a| a.as_str()))
+        .map(|part| {
+            if part.is_empty() || part.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+                format!("'{}'", part.replace('\'', "'\\''"))
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Last `max` bytes of `text`, moved forward to a char boundary.
+fn tail(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut start = text.len() - max;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+fn script_log_key(workspace_id: &str, phase: ScriptPhase) -> String {
+    format!("script_log_{}_{}", phase.as_key(), workspace_id)
+}
+
+fn persist_script_log(app: &tauri::AppHandle, log: &ScriptRunLog) -> Result<(), String> {
+    let store = app
+        .store("heroi-store.json")
+        .map_err(|e| e.to_string())?;
+    store.set(
+        script_log_key(&log.workspace_id, log.phase),
+        serde_json::to_value(log).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())?;
     Ok(())
 }
 
+/// Own a spawned child on a background thread: reap it when it exits, record
+/// exit code/signal and timing on its `RunningProcess`, and emit a
+/// `process-status-changed` event with the updated entry.
+///
+/// With `restart: on-failure` a failed exit is followed by a respawn (with a
+/// growing backoff) until `max_restarts` is reached. The entry stays
+/// `Running` in between, keeps its id and log, and gets the new pid.
+fn supervise_process(app: tauri::AppHandle, process_id: String, mut child: Child, spec: SpawnSpec) {
+    std::thread::spawn(move || loop {
+        let started = Instant::now();
+        let result = child.wait();
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        let (exit_code, signal, success) = match result {
+            Ok(status) => (status.code(), exit_signal(&status), status.success()),
+            Err(_) => (None, None, false),
+        };
+
+        let state = app.state::<AppState>();
+        let updated = match state.0.lock() {
+            Ok(mut data) => data
+                .running_processes
+                .iter_mut()
+                .find(|p| p.id == process_id)
+                .map(|p| {
+                    let restart = !success
+                        && !p.stopped_by_user
+                        && spec.restart == RestartPolicy::OnFailure
+                        && p.restarts < spec.max_restarts;
+                    p.status = if restart {
+                        ProcessStatus::Running
+                    } else if success || p.stopped_by_user {
+                        ProcessStatus::Exited
+                    } else {
+                        ProcessStatus::Failed
+                    };
+                    p.ended_at = Some(now_iso8601_pub());
+                    p.duration_ms = Some(duration_ms);
+                    p.exit_code = exit_code;
+                    p.signal = signal;
+                    if restart {
+                        p.restarts += 1;
+                    }
+                    (p.clone(), restart)
+                }),
+            Err(_) => None,
+        };
+
+        let Some((process, restart)) = updated else {
+            return;
+        };
+        let _ = app.emit("process-status-changed", process.clone());
+        if !restart {
+            return;
+        }
+
+        std::thread::sleep(RESTART_BACKOFF * process.restarts);
+
+        // A stop requested during the backoff wins over the restart
+        let respawn = match state.0.lock() {
+            Ok(data) => data
+                .running_processes
+                .iter()
+                .any(|p| p.id == process_id && !p.stopped_by_user),
+            Err(_) => false,
+        };
+        let mut next = if respawn { spec.spawn() } else { Err(String::new()) };
+
+        // Recording the new pid and checking for a stop share one lock
+        // section: a stop that landed while spawning signalled the old,
+        // already dead group, so the new one must be terminated here.
+        let mut stopped = false;
+        let updated = match state.0.lock() {
+            Ok(mut data) => data
+                .running_processes
+                .iter_mut()
+                .find(|p| p.id == process_id)
+                .map(|p| {
+                    match next {
+                        Ok(_) if p.stopped_by_user => {
+                            stopped = true;
+                            p.status = ProcessStatus::Exited;
+                        }
+                        Ok(ref c) => {
+                            p.pid = c.id();
+                            p.started_at = now_iso8601_pub();
+                            p.ended_at = None;
+                            p.duration_ms = None;
+                        }
+                        Err(_) if p.stopped_by_user => p.status = ProcessStatus::Exited,
+                        Err(_) => p.status = ProcessStatus::Failed,
+                    }
+                    p.clone()
+                }),
+            Err(_) => None,
+        };
+        if let Some(process) = updated {
+            let _ = app.emit("process-status-changed", process);
+        }
+
+        if stopped {
+            if let Ok(c) = next.as_mut() {
+                if let Err(e) = terminate_process_trees(&[c.id()]) {
+                    push_log_line(&app, &process_id, LogStream::Stderr, e);
+                }
+                let _ = c.wait();
+            }
+            return;
+        }
+
+        match next {
+            Ok(mut c) => {
+                attach_log_readers(&app, &process_id, &mut c);
+                child = c;
+            }
+            Err(e) => {
+                if !e.is_empty() {
+                    push_log_line(&app, &process_id, LogStream::Stderr, format!("Restart failed: {}", e));
+                }
+                return;
+            }
+        }
+    });
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &std::process::ExitStatus) -> Option<i32> {
+    None
+}
+
+fn attach_log_readers(app: &tauri::AppHandle, process_id: &str, child: &mut Child) {
+    if let Some(stdout) = child.stdout.take() {
+        spawn_log_reader(app.clone(), process_id.to_string(), LogStream::Stdout, stdout);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        spawn_log_reader(app.clone(), process_id.to_string(), LogStream::Stderr, stderr);
+    }
+}
+
+/// Read a child's output stream line by line on a background thread, storing
+/// each line in the process's ring buffer and emitting a `process-output` event.
+fn spawn_log_reader<R: Read + Send + 'static>(
+    app: tauri::AppHandle,
+    process_id: String,
+    stream: LogStream,
+    reader: R,
+) {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut buf = Vec::new();
+        while let Some(text) = read_log_entry(&mut reader, &mut buf) {
+            if !push_log_line(&app, &process_id, stream, text) {
+                break;
+            }
+        }
+    });
+}
+
+/// Read the next log entry: a line without its line ending, or the first
+/// `ProcessLog::MAX_LINE_BYTES` of a longer one, so output without newlines
+/// (e.g. a progress bar) can't grow without bound. A multi-byte character cut
+/// off by the cap is left in `buf` for the next entry.
+fn read_log_entry<R: BufRead>(reader: &mut R, buf: &mut Vec<u8>) -> Option<String> {
+    let limit = (ProcessLog::MAX_LINE_BYTES - buf.len()) as u64;
+    let eof = !matches!(reader.by_ref().take(limit).read_until(b'\n', buf), Ok(n) if n > 0);
+    if buf.is_empty() {
+        return None;
+    }
+
+    let keep = match std::str::from_utf8(buf) {
+        Err(e) if !eof && e.error_len().is_none() => buf.len() - e.valid_up_to(),
+        _ => 0,
+    };
+    let rest = buf.split_off(buf.len() - keep);
+    let text = String::from_utf8_lossy(buf)
+        .trim_end_matches(['\r', '\n'])
+        .to_string();
+    *buf = rest;
+    Some(text)
+}
+
+/// Append a line to a process's log and emit it as a `process-output` event.
+/// Returns false if the state lock is poisoned.
+fn push_log_line(app: &tauri::AppHandle, process_id: &str, stream: LogStream, text: String) -> bool {
+    let state = app.state::<AppState>();
+    let line = match state.0.lock() {
+        Ok(mut data) => data
+            .process_logs
+            .entry(process_id.to_string())
+            .or_default()
+            .push(stream, text),
+        Err(_) => return false,
+    };
+
+    let _ = app.emit(
+        "process-output",
+        ProcessOutputEvent {
+            process_id: process_id.to_string(),
+            line,
+        },
+    );
+    true
+}
+
 fn resolve_platform_command(script: &ScriptDef) -> (String, Vec<String>) {
     #[cfg(target_os = "windows")]
     {
@@ -156,26 +957,85 @@ fn resolve_platform_command(script: &ScriptDef) -> (String, Vec<String>) {
     }
 }
 
-fn kill_process(pid: u32) -> Result<(), String> {
+#[cfg(target_os = "windows")]
+fn set_new_process_group(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(target_os = "windows"))]
+fn set_new_process_group(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+}
+
+/// Terminate scripts' whole process trees: graceful first, forced after the
+/// grace period. Errors if any group survives the forced kill.
+fn terminate_process_trees(pids: &[u32]) -> Result<(), String> {
+    for &pid in pids {
+        signal_process_tree(pid, false)?;
+    }
+    let deadline = Instant::now() + STOP_GRACE_PERIOD;
+    let survivors: Vec<u32> = pids
+        .iter()
+        .copied()
+        .filter(|&pid| !wait_for_tree_exit(pid, deadline))
+        .collect();
+
+    for &pid in &survivors {
+        signal_process_tree(pid, true)?;
+    }
+    let deadline = Instant::now() + KILL_WAIT;
+    for &pid in &survivors {
+        if !wait_for_tree_exit(pid, deadline) {
+            return Err(format!("Process {} did not exit after being killed", pid));
+        }
+    }
+
+    Ok(())
+}
+
+fn wait_for_tree_exit(pid: u32, deadline: Instant) -> bool {
+    while Instant::now() < deadline {
+        if !is_process_tree_alive(pid) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    !is_process_tree_alive(pid)
+}
+
+fn signal_process_tree(pid: u32, force: bool) -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
-        Command::new("taskkill")
-            .args(["/F", "/T", "/PID", &pid.to_string()])
+        let mut cmd = Command::new("taskkill");
+        if force {
+            cmd.arg("/F");
+        }
+        cmd.args(["/T", "/PID", &pid.to_string()])
             .output()
             .map_err(|e| format!("Failed to kill process {}: {}", pid, e))?;
         Ok(())
     }
     #[cfg(not(target_os = "windows"))]
     {
-        // Send SIGTERM to process group
-        unsafe {
-            libc::kill(-(pid as i32), libc::SIGTERM);
+        let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+        // The script was spawned as a group leader, so -pid addresses the
+        // whole group including grandchildren.
+        let rc = unsafe { libc::kill(-(pid as i32), signal) };
+        if rc != 0 {
+            let err = std::io::Error::last_os_error();
+            // ESRCH: the group is already gone
+            if err.raw_os_error() != Some(libc::ESRCH) {
+                return Err(format!("Failed to signal process {}: {}", pid, err));
+            }
         }
         Ok(())
     }
 }
 
-fn is_process_alive(pid: u32) -> bool {
+fn is_process_tree_alive(pid: u32) -> bool {
     #[cfg(target_os = "windows")]
     {
         Command::new("tasklist")
@@ -189,6 +1049,68 @@ fn is_process_alive(pid: u32) -> bool {
     }
     #[cfg(not(target_os = "windows"))]
     {
-        unsafe { libc::kill(pid as i32, 0) == 0 }
+        // Signal 0 to -pid succeeds while any member of the group exists
+        unsafe { libc::kill(-(pid as i32), 0) == 0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_entries(input: &[u8]) -> Vec<String> {
+        let mut reader = BufReader::new(input);
+        let mut buf = Vec::new();
+        std::iter::from_fn(|| read_log_entry(&mut reader, &mut buf)).collect()
+    }
+
+    #[test]
+    fn log_lines_are_split_at_the_byte_limit() {
+        let max = ProcessLog::MAX_LINE_BYTES;
+        let mut input = vec![b'a'; max * 2 + 10];
+        input.extend_from_slice(b"\r\nnext\n\nlast");
+
+        let entries = log_entries(&input);
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries[0].len(), max);
+        assert_eq!(entries[1].len(), max);
+        assert_eq!(entries[2], "a".repeat(10));
+        assert_eq!(entries[3..], ["next", "", "last"]);
+    }
+
+    #[test]
+    fn log_line_split_keeps_multibyte_characters_whole() {
+        let max = ProcessLog::MAX_LINE_BYTES;
+        let input = format!("{}é\n", "a".repeat(max - 1));
+
+        let entries = log_entries(input.as_bytes());
+        assert_eq!(entries, ["a".repeat(max - 1), "é".to_string()]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn terminate_process_trees_kills_every_descendant() {
+        use std::os::unix::process::CommandExt;
+
+        let mut child = Command::new("sh")
+            .args(["-c", "sleep 100 & sleep 100"])
+            .process_group(0)
+            .spawn()
+            .expect("spawn sh");
+        let pgid = child.id();
+        // Reap the leader as the supervisor would, so it doesn't linger as a
+        // zombie member of the group
+        let waiter = std::thread::spawn(move || child.wait());
+        std::thread::sleep(Duration::from_millis(200));
+
+        terminate_process_trees(&[pgid]).expect("terminate");
+        waiter.join().unwrap().expect("wait");
+
+        let rc = unsafe { libc::kill(-(pgid as i32), 0) };
+        assert_eq!(rc, -1);
+        assert_eq!(
+            std::io::Error::last_os_error().raw_os_error(),
+            Some(libc::ESRCH)
+        );
     }
 }
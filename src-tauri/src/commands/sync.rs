@@ -0,0 +1,952 @@
+use std::io::{BufReader, Read};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+
+use git2::{BranchType, Oid, Repository, RepositoryState};
+use tauri::Emitter;
+
+use crate::commands::conflicts::list_conflicts;
+use crate::commands::git::repo_status;
+use crate::models::git::{
+    AheadBehind, FileState, PushOptions, PushProgress, PushRefStatus, PushRefUpdate, PushResult,
+    SyncResult, SyncStatus, SyncStrategy,
+};
+
+/// Fetch `base_branch` and rebase the worktree's branch onto it, or merge it
+/// in. Local `main` and remote-tracking `origin/main` both work as the base;
+/// the remote-tracking branch is used when it exists. If the fetch fails the
+/// last fetched (or local) base is used and the error is reported in the
+/// result. Stopping on conflicts is not an error: the result lists them and
+/// the operation stays in progress for `git_sync_continue` or
+/// `git_sync_abort`. The base must name a branch.
+#[tauri::command(async)]
+pub fn git_sync_with_base(
+    worktree_path: String,
+    base_branch: String,
+    strategy: SyncStrategy,
+) -> Result<SyncResult, String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+
+    if !git2::Branch::name_is_valid(&base_branch).unwrap_or(false) {
+        return Err(format!("'{}' is not a valid branch name", base_branch));
+    }
+    if let Some(op) = operation_in_progress(&repo) {
+        return Err(format!(
+            "A {} is already in progress; continue or abort it first",
+            op.as_str()
+        ));
+    }
+    let dirty = repo_status(&repo)?
+        .iter()
+        .any(|f| f.staged != FileState::Unmodified || f.unstaged != FileState::Untracked);
+    if dirty {
+        return Err("Commit or discard your changes before syncing".to_string());
+    }
+
+    let (base_ref, fetch_error) = fetch_base(&repo, &worktree_path, &base_branch);
+    let base_full_ref = branch_ref(&repo, &base_ref)?;
+    let base = repo
+        .revparse_single(&base_full_ref)
+        .and_then(|o| o.peel_to_commit())
+        .map_err(|e| format!("Failed to resolve '{}': {}", base_ref, e))?;
+    let head = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+
+    let up_to_date = head.id() == base.id()
+        || repo
+            .merge_base(head.id(), base.id())
+            .is_ok_and(|oid| oid == base.id());
+    if up_to_date {
+        return Ok(SyncResult {
+            status: SyncStatus::UpToDate,
+            in_progress: None,
+            conflicts: Vec::new(),
+            base_ref: Some(base_ref),
+            fetch_error,
+            head: head.id().to_string(),
+        });
+    }
+
+    let output = match strategy {
+        SyncStrategy::Rebase => git(&worktree_path, &["rebase", &base_full_ref])?,
+        SyncStrategy::Merge => git(&worktree_path, &["merge", "--no-edit", &base_full_ref])?,
+    };
+    let mut result = sync_result(&worktree_path, strategy.as_str(), &output)?;
+    result.base_ref = Some(base_ref);
+    result.fetch_error = fetch_error;
+    Ok(result)
+}
+
+/// Continue a rebase or merge once its conflicts are resolved. If conflicts
+/// remain nothing is run and they are returned; a rebase can also stop again
+/// on a later commit.
+#[tauri::command(async)]
+pub fn git_sync_continue(worktree_path: String) -> Result<SyncResult, String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let op =
+        operation_in_progress(&repo).ok_or_else(|| "No rebase or merge in progress".to_string())?;
+
+    let conflicts = list_conflicts(&repo)?;
+    if !conflicts.is_empty() {
+        return Ok(SyncResult {
+            status: SyncStatus::Conflicted,
+            in_progress: Some(op),
+            conflicts,
+            base_ref: None,
+            fetch_error: None,
+            head: head_id(&repo),
+        });
+    }
+
+    let output = match op {
+        SyncStrategy::Rebase => git(&worktree_path, &["rebase", "--continue"])?,
+        // Committing concludes a merge
+        SyncStrategy::Merge => git(&worktree_path, &["commit", "--no-edit"])?,
+    };
+    sync_result(&worktree_path, op.as_str(), &output)
+}
+
+/// Abort the rebase or merge in progress, restoring the branch.
+#[tauri::command(async)]
+pub fn git_sync_abort(worktree_path: String) -> Result<(), String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let op =
+        operation_in_progress(&repo).ok_or_else(|| "No rebase or merge in progress".to_string())?;
+
+    let output = git(&worktree_path, &[op.as_str(), "--abort"])?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {} --abort failed: {}", op.as_str(), stderr));
+    }
+    Ok(())
+}
+
+/// The rebase or merge left in progress in a worktree, if any.
+#[tauri::command]
+pub fn git_sync_in_progress(worktree_path: String) -> Result<Option<SyncStrategy>, String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    Ok(operation_in_progress(&repo))
+}
+
+/// Fetch `remote`, or every remote when none is given, pruning remote
+/// branches that were deleted. A named remote must be configured.
+#[tauri::command(async)]
+pub fn git_fetch(repo_path: String, remote: Option<String>) -> Result<(), String> {
+    if let Some(name) = &remote {
+        let repo =
+            Repository::open(&repo_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+        repo.find_remote(name)
+            .map_err(|e| format!("Unknown remote '{}': {}", name, e))?;
+    }
+    let target = remote.as_deref().unwrap_or("--all");
+    let output = git(&repo_path, &["fetch", "--prune", target])?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git fetch failed: {}", stderr));
+    }
+    Ok(())
+}
+
+/// Push the current branch to the chosen remote: `options.remote`, else the
+/// upstream's remote, else `origin`. On the upstream's remote it updates the
+/// configured upstream branch (`branch.<name>.merge`), which may be named
+/// differently; elsewhere the same-named branch. The branch starts tracking
+/// the pushed branch when asked or when it has no upstream yet. Emits
+/// `git-push-progress` events while objects are sent. Refs the remote
+/// rejects are reported in the result; failures before any ref is pushed
+/// (authentication, a pre-push hook) are errors.
+#[tauri::command(async)]
+pub fn git_push(
+    worktree_path: String,
+    options: Option<PushOptions>,
+    app: tauri::AppHandle,
+) -> Result<PushResult, String> {
+    let options = options.unwrap_or_default();
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let head = repo
+        .head()
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let branch = head
+        .shorthand()
+        .filter(|_| head.is_branch())
+        .ok_or_else(|| "HEAD is detached; check out a branch to push".to_string())?;
+
+    let target = push_target(&repo, branch, options.remote.as_deref())?;
+    let (remote, refspec) = (target.remote, target.refspec);
+    let set_upstream = options.set_upstream || !target.has_upstream;
+
+    let mut args = vec!["push", "--porcelain", "--progress"];
+    if set_upstream {
+        args.push("--set-upstream");
+    }
+    if options.force_with_lease {
+        args.push("--force-with-lease");
+    }
+    if options.tags {
+        args.push("--follow-tags");
+    }
+    args.extend([remote.as_str(), refspec.as_str()]);
+
+    let mut child = Command::new("git")
+        .current_dir(&worktree_path)
+        .args(&args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git push: {}", e))?;
+
+    // Porcelain ref lines arrive on stdout, progress and messages on stderr
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "Failed to capture git push output".to_string())?;
+    let stdout_reader = thread::spawn(move || {
+        let mut text = String::new();
+        let _ = stdout.read_to_string(&mut text);
+        text
+    });
+
+    let mut messages = Vec::new();
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| "Failed to capture git push progress".to_string())?;
+    for line in progress_lines(stderr) {
+        match parse_progress(&line) {
+            Some((phase, percent)) => {
+                let _ = app.emit(
+                    "git-push-progress",
+                    PushProgress {
+                        worktree_path: worktree_path.clone(),
+                        phase,
+                        percent,
+                    },
+                );
+            }
+            None if line.starts_with("Total ") || line.ends_with(", done.") => {}
+            None => messages.push(line),
+        }
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to wait for git push: {}", e))?;
+    let refs = parse_push_porcelain(&stdout_reader.join().unwrap_or_default());
+
+    if !status.success() && refs.is_empty() {
+        return Err(format!("git push failed: {}", messages.join("\n")));
+    }
+    let success = status.success() && refs.iter().all(|r| r.status != PushRefStatus::Rejected);
+    Ok(PushResult {
+        remote,
+        success,
+        refs,
+        messages,
+        upstream_set: set_upstream && success,
+    })
+}
+
+/// Where `git_push` sends a branch.
+struct PushTarget {
+    remote: String,
+    refspec: String,
+    /// Whether the branch already tracks a remote branch
+    has_upstream: bool,
+}
+
+/// Pick the remote and refspec for pushing `branch`; see `git_push`. A
+/// requested remote must be configured, so it can't be taken for an option.
+fn push_target(
+    repo: &Repository,
+    branch: &str,
+    requested: Option<&str>,
+) -> Result<PushTarget, String> {
+    if let Some(name) = requested {
+        repo.find_remote(name)
+            .map_err(|e| format!("Unknown remote '{}': {}", name, e))?;
+    }
+
+    let config = repo
+        .config()
+        .map_err(|e| format!("Failed to read config: {}", e))?;
+    let upstream_remote = config
+        .get_string(&format!("branch.{}.remote", branch))
+        .ok()
+        .filter(|r| r != ".");
+    let upstream_ref = config.get_string(&format!("branch.{}.merge", branch)).ok();
+    let remote = requested
+        .map(str::to_string)
+        .or_else(|| upstream_remote.clone())
+        .or_else(|| {
+            repo.find_remote("origin")
+                .ok()
+                .map(|_| "origin".to_string())
+        })
+        .ok_or_else(|| "No remote to push to".to_string())?;
+    let refspec = match upstream_ref {
+        Some(merge) if upstream_remote.as_deref() == Some(remote.as_str()) => {
+            format!("HEAD:{}", merge)
+        }
+        _ => "HEAD".to_string(),
+    };
+
+    Ok(PushTarget {
+        remote,
+        refspec,
+        has_upstream: upstream_remote.is_some(),
+    })
+}
+
+/// Pull the current branch's upstream, either fast-forward only or merging
+/// when the branches have diverged. Merge conflicts are reported like
+/// `git_sync_with_base`'s and finished with `git_sync_continue` or
+/// `git_sync_abort`.
+#[tauri::command(async)]
+pub fn git_pull(worktree_path: String, ff_only: bool) -> Result<SyncResult, String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    if let Some(op) = operation_in_progress(&repo) {
+        return Err(format!(
+            "A {} is already in progress; continue or abort it first",
+            op.as_str()
+        ));
+    }
+    let (upstream, _) = upstream(&repo)?
+        .ok_or_else(|| "The current branch has no upstream to pull from".to_string())?;
+
+    let before = head_id(&repo);
+    let mode = if ff_only { "--ff-only" } else { "--no-rebase" };
+    let output = git(&worktree_path, &["pull", mode])?;
+    let mut result = sync_result(&worktree_path, "pull", &output)?;
+    if result.status == SyncStatus::Updated && result.head == before {
+        result.status = SyncStatus::UpToDate;
+    }
+    result.base_ref = Some(upstream);
+    Ok(result)
+}
+
+/// Commits ahead of and behind the current branch's upstream (whatever
+/// remote it tracks) and, when `base_branch` is given, the base branch.
+#[tauri::command]
+pub fn git_ahead_behind(
+    worktree_path: String,
+    base_branch: Option<String>,
+) -> Result<AheadBehind, String> {
+    let repo =
+        Repository::open(&worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    ahead_behind(&repo, base_branch.as_deref())
+}
+
+pub(crate) fn ahead_behind(
+    repo: &Repository,
+    base_branch: Option<&str>,
+) -> Result<AheadBehind, String> {
+    let head = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let mut result = AheadBehind::default();
+
+    if let Some((name, oid)) = upstream(repo)? {
+        let (ahead, behind) = repo
+            .graph_ahead_behind(head.id(), oid)
+            .map_err(|e| format!("Failed to compute ahead/behind: {}", e))?;
+        result.upstream = Some(name);
+        result.ahead = ahead;
+        result.behind = behind;
+    }
+
+    if let Some(base_branch) = base_branch {
+        let base = repo
+            .revparse_single(base_branch)
+            .and_then(|o| o.peel_to_commit())
+            .map_err(|e| format!("Failed to resolve '{}': {}", base_branch, e))?;
+        let (ahead, behind) = repo
+            .graph_ahead_behind(head.id(), base.id())
+            .map_err(|e| format!("Failed to compute ahead/behind: {}", e))?;
+        result.base = Some(base_branch.to_string());
+        result.base_ahead = ahead;
+        result.base_behind = behind;
+    }
+
+    Ok(result)
+}
+
+/// Short name and target of the current branch's upstream, from its
+/// `branch.<name>.remote`/`merge` config. `None` when HEAD is detached, no
+/// upstream is configured or it hasn't been fetched.
+fn upstream(repo: &Repository) -> Result<Option<(String, Oid)>, String> {
+    let head = repo
+        .head()
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let Some(name) = head.shorthand().filter(|_| head.is_branch()) else {
+        return Ok(None);
+    };
+    let branch = repo
+        .find_branch(name, BranchType::Local)
+        .map_err(|e| format!("Failed to find branch '{}': {}", name, e))?;
+    let Ok(upstream) = branch.upstream() else {
+        return Ok(None);
+    };
+    let upstream_name = upstream.name().ok().flatten().map(str::to_string);
+    Ok(upstream_name.zip(upstream.get().target()))
+}
+
+/// Run git in the worktree. Editors and credential prompts are disabled so
+/// commands never block waiting for input.
+pub(crate) fn git(worktree_path: &str, args: &[&str]) -> Result<Output, String> {
+    Command::new("git")
+        .current_dir(worktree_path)
+        .args(args)
+        .env("GIT_EDITOR", "true")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| format!("Failed to run git {}: {}", args[0], e))
+}
+
+fn operation_in_progress(repo: &Repository) -> Option<SyncStrategy> {
+    match repo.state() {
+        RepositoryState::Merge => Some(SyncStrategy::Merge),
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge
+        | RepositoryState::ApplyMailboxOrRebase => Some(SyncStrategy::Rebase),
+        _ => None,
+    }
+}
+
+fn head_id(repo: &Repository) -> String {
+    repo.head()
+        .ok()
+        .and_then(|h| h.target())
+        .map(|oid| oid.to_string())
+        .unwrap_or_default()
+}
+
+/// Fetch the base branch from its remote and pick the ref to sync onto.
+/// Returns the ref and the fetch error, if fetching failed.
+fn fetch_base(
+    repo: &Repository,
+    worktree_path: &str,
+    base_branch: &str,
+) -> (String, Option<String>) {
+    // `origin/main` names the remote directly; otherwise use the branch's
+    // upstream remote, falling back to origin
+    let (remote, branch) = match base_branch.split_once('/') {
+        Some((remote, branch)) if repo.find_remote(remote).is_ok() => {
+            (Some(remote.to_string()), branch.to_string())
+        }
+        _ => {
+            let upstream = repo
+                .config()
+                .and_then(|c| c.get_string(&format!("branch.{}.remote", base_branch)))
+                .ok()
+                .filter(|r| r != ".");
+            let remote = upstream.or_else(|| {
+                repo.find_remote("origin")
+                    .ok()
+                    .map(|_| "origin".to_string())
+            });
+            (remote, base_branch.to_string())
+        }
+    };
+    let Some(remote) = remote else {
+        return (base_branch.to_string(), None);
+    };
+
+    let fetch_error = match git(worktree_path, &["fetch", &remote, &branch]) {
+        Ok(output) if output.status.success() => None,
+        Ok(output) => Some(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        Err(e) => Some(e),
+    };
+    let tracking = format!("{}/{}", remote, branch);
+    if repo
+        .find_reference(&format!("refs/remotes/{}", tracking))
+        .is_ok()
+    {
+        (tracking, fetch_error)
+    } else {
+        (branch, fetch_error)
+    }
+}
+
+/// Full ref name of the local or remote-tracking branch `name`, so git can't
+/// take it for an option or another kind of ref.
+fn branch_ref(repo: &Repository, name: &str) -> Result<String, String> {
+    repo.find_branch(name, BranchType::Local)
+        .or_else(|_| repo.find_branch(name, BranchType::Remote))
+        .ok()
+        .and_then(|b| b.get().name().map(str::to_string))
+        .ok_or_else(|| format!("'{}' is not a branch", name))
+}
+
+/// Split git's stderr into lines, treating the carriage returns that
+/// redraw progress meters as line ends.
+fn progress_lines(stderr: impl Read) -> impl Iterator<Item = String> {
+    let mut bytes = BufReader::new(stderr).bytes();
+    let mut line = Vec::new();
+    std::iter::from_fn(move || loop {
+        let byte = bytes.next().and_then(Result::ok);
+        match byte {
+            Some(b'\r' | b'\n') | None => {
+                let text = String::from_utf8_lossy(&line).trim().to_string();
+                line.clear();
+                if !text.is_empty() {
+                    return Some(text);
+                }
+                byte?;
+            }
+            Some(byte) => line.push(byte),
+        }
+    })
+}
+
+/// `Writing objects:  45% (9/20)` becomes `("Writing objects", 45)`; server
+/// side progress keeps its `remote: ` prefix out of the phase.
+fn parse_progress(line: &str) -> Option<(String, u8)> {
+    let line = line.strip_prefix("remote: ").unwrap_or(line);
+    let (phase, rest) = line.split_once(':')?;
+    let percent = rest.trim_start().split_once('%')?.0.trim().parse().ok()?;
+    Some((phase.trim().to_string(), percent))
+}
+
+/// Parse `git push --porcelain` ref lines: `<flag>\t<from>:<to>\t<summary>`,
+/// where the summary may end in a parenthesised reason.
+fn parse_push_porcelain(stdout: &str) -> Vec<PushRefUpdate> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let status = match fields.next()? {
+                " " => PushRefStatus::FastForward,
+                "+" => PushRefStatus::Forced,
+                "-" => PushRefStatus::Deleted,
+                "*" => PushRefStatus::New,
+                "=" => PushRefStatus::UpToDate,
+                "!" => PushRefStatus::Rejected,
+                _ => return None,
+            };
+            let (local_ref, remote_ref) = fields.next()?.split_once(':')?;
+            let summary = fields.next().unwrap_or("");
+            let (summary, reason) = match summary.split_once(" (") {
+                Some((summary, reason)) => (summary, Some(reason.trim_end_matches(')'))),
+                None => (summary, None),
+            };
+            Some(PushRefUpdate {
+                local_ref: local_ref.to_string(),
+                remote_ref: remote_ref.to_string(),
+                status,
+                summary: summary.to_string(),
+                reason: reason.map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Interpret the outcome of a rebase, merge, pull or continue. A failure that
+/// leaves conflicts behind is reported as `Conflicted`; any other failure is
+/// an error.
+fn sync_result(worktree_path: &str, command: &str, output: &Output) -> Result<SyncResult, String> {
+    // Reopen so the index written by the git CLI is read fresh
+    let repo =
+        Repository::open(worktree_path).map_err(|e| format!("Failed to open repo: {}", e))?;
+    let conflicts = list_conflicts(&repo)?;
+    let in_progress = operation_in_progress(&repo);
+
+    if !output.status.success() && conflicts.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        return Err(format!(
+            "git {} failed: {}",
+            command,
+            if stderr.trim().is_empty() {
+                stdout
+            } else {
+                stderr
+            }
+        ));
+    }
+
+    Ok(SyncResult {
+        status: if conflicts.is_empty() {
+            SyncStatus::Updated
+        } else {
+            SyncStatus::Conflicted
+        },
+        in_progress,
+        conflicts,
+        base_ref: None,
+        fetch_error: None,
+        head: head_id(&repo),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// A throwaway repository with one commit on `main`.
+    struct TempRepo {
+        dir: PathBuf,
+        repo: Repository,
+    }
+
+    impl TempRepo {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("heroi-sync-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let repo = Repository::init(&dir).unwrap();
+            repo.set_head("refs/heads/main").unwrap();
+            let temp = TempRepo { dir, repo };
+            temp.commit("file.txt", "one\n", "init");
+            temp
+        }
+
+        fn path(&self) -> &str {
+            self.dir.to_str().unwrap()
+        }
+
+        /// Write `file` and commit it on the current branch.
+        fn commit(&self, file: &str, content: &str, message: &str) -> Oid {
+            fs::write(self.dir.join(file), content).unwrap();
+            let mut index = self.repo.index().unwrap();
+            index.add_path(Path::new(file)).unwrap();
+            index.write().unwrap();
+            let tree = self.repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+            let parent = self.repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+            let parents: Vec<&git2::Commit> = parent.iter().collect();
+            self.repo
+                .commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+                .unwrap()
+        }
+
+        fn set_config(&self, key: &str, value: &str) {
+            self.repo.config().unwrap().set_str(key, value).unwrap();
+        }
+    }
+
+    impl Drop for TempRepo {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[test]
+    fn push_targets_the_upstream_branch_on_its_remote() {
+        let temp = TempRepo::new("push-target");
+        temp.repo
+            .remote("origin", "https://example.com/origin.git")
+            .unwrap();
+        temp.repo
+            .remote("fork", "https://example.com/fork.git")
+            .unwrap();
+
+        // No upstream: origin's same-named branch
+        let target = push_target(&temp.repo, "main", None).unwrap();
+        assert_eq!(
+            (target.remote.as_str(), target.refspec.as_str()),
+            ("origin", "HEAD")
+        );
+        assert!(!target.has_upstream);
+
+        temp.set_config("branch.main.remote", "fork");
+        temp.set_config("branch.main.merge", "refs/heads/trunk");
+        let target = push_target(&temp.repo, "main", None).unwrap();
+        assert_eq!(target.remote, "fork");
+        assert_eq!(target.refspec, "HEAD:refs/heads/trunk");
+        assert!(target.has_upstream);
+
+        let target = push_target(&temp.repo, "main", Some("fork")).unwrap();
+        assert_eq!(target.refspec, "HEAD:refs/heads/trunk");
+
+        // Another remote gets the same-named branch
+        let target = push_target(&temp.repo, "main", Some("origin")).unwrap();
+        assert_eq!(
+            (target.remote.as_str(), target.refspec.as_str()),
+            ("origin", "HEAD")
+        );
+    }
+
+    #[test]
+    fn push_rejects_unknown_remotes() {
+        let temp = TempRepo::new("push-remote");
+        assert_eq!(
+            push_target(&temp.repo, "main", None).err().unwrap(),
+            "No remote to push to"
+        );
+
+        temp.repo
+            .remote("origin", "https://example.com/origin.git")
+            .unwrap();
+        for remote in ["upstream", "--upload-pack=touch /tmp/x", "-f"] {
+            let err = push_target(&temp.repo, "main", Some(remote)).err().unwrap();
+            assert!(err.starts_with("Unknown remote"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn parses_porcelain_ref_lines() {
+        let stdout = [
+            "To https://example.com/repo.git",
+            " \trefs/heads/a:refs/heads/a\t1a2b3c4..5d6e7f8",
+            "+\trefs/heads/b:refs/heads/b\t1a2b3c4...5d6e7f8 (forced update)",
+            "*\trefs/heads/c:refs/heads/c\t[new branch]",
+            "=\trefs/heads/d:refs/heads/d\t[up to date]",
+            "-\t:refs/heads/e\t[deleted]",
+            "!\trefs/heads/f:refs/heads/f\t[rejected] (fetch first)",
+            "Done",
+        ]
+        .join("\n");
+        let refs = parse_push_porcelain(&stdout);
+
+        let statuses: Vec<PushRefStatus> = refs.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                PushRefStatus::FastForward,
+                PushRefStatus::Forced,
+                PushRefStatus::New,
+                PushRefStatus::UpToDate,
+                PushRefStatus::Deleted,
+                PushRefStatus::Rejected,
+            ]
+        );
+        assert_eq!(refs[0].local_ref, "refs/heads/a");
+        assert_eq!(refs[0].remote_ref, "refs/heads/a");
+        assert_eq!(refs[0].summary, "1a2b3c4..5d6e7f8");
+        assert_eq!(refs[0].reason, None);
+        assert_eq!(refs[1].reason.as_deref(), Some("forced update"));
+        assert_eq!(refs[4].local_ref, "");
+        assert_eq!(refs[5].summary, "[rejected]");
+        assert_eq!(refs[5].reason.as_deref(), Some("fetch first"));
+    }
+
+    #[test]
+    fn parses_progress_lines() {
+        assert_eq!(
+            parse_progress("Writing objects:  45% (9/20), 1.2 MiB | 3 MiB/s"),
+            Some(("Writing objects".to_string(), 45))
+        );
+        assert_eq!(
+            parse_progress("remote: Resolving deltas: 100% (3/3), done."),
+            Some(("Resolving deltas".to_string(), 100))
+        );
+        assert_eq!(
+            parse_progress("Total 3 (delta 0), reused 0 (delta 0)"),
+            None
+        );
+        assert_eq!(
+            parse_progress("remote: Create a pull request: https://x/y"),
+            None
+        );
+        assert_eq!(parse_progress("To https://example.com/repo.git"), None);
+    }
+
+    #[test]
+    fn splits_progress_on_carriage_returns() {
+        let stderr: &[u8] = b"Counting: 50%\rCounting: 100%\r\nremote: hi\n\n  \r tail";
+        let lines: Vec<String> = progress_lines(stderr).collect();
+        assert_eq!(
+            lines,
+            ["Counting: 50%", "Counting: 100%", "remote: hi", "tail"]
+        );
+    }
+
+    #[test]
+    fn fetch_rejects_unknown_remotes() {
+        let temp = TempRepo::new("fetch");
+        for remote in ["origin", "--upload-pack=touch /tmp/x"] {
+            let err = git_fetch(temp.path().to_string(), Some(remote.to_string()))
+                .err()
+                .unwrap();
+            assert!(err.starts_with("Unknown remote"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn ahead_behind_counts_against_upstream_and_base() {
+        let temp = TempRepo::new("ahead-behind");
+        let base = temp.repo.head().unwrap().target().unwrap();
+
+        // No upstream configured yet
+        assert_eq!(upstream(&temp.repo).unwrap(), None);
+        let counts = ahead_behind(&temp.repo, None).unwrap();
+        assert_eq!(counts.upstream, None);
+        assert_eq!((counts.ahead, counts.behind), (0, 0));
+
+        // origin/main gets one commit the local branch lacks
+        temp.repo
+            .remote("origin", "https://example.com/origin.git")
+            .unwrap();
+        temp.commit("remote.txt", "remote\n", "remote");
+        let remote_head = temp.repo.head().unwrap().target().unwrap();
+        temp.repo
+            .reference("refs/remotes/origin/main", remote_head, true, "test")
+            .unwrap();
+        temp.repo
+            .reference("refs/heads/main", base, true, "test")
+            .unwrap();
+        temp.repo
+            .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+        temp.set_config("branch.main.remote", "origin");
+        temp.set_config("branch.main.merge", "refs/heads/main");
+
+        // ...and the local branch two of its own
+        temp.commit("a.txt", "a\n", "a");
+        temp.commit("b.txt", "b\n", "b");
+        assert_eq!(
+            upstream(&temp.repo).unwrap(),
+            Some(("origin/main".to_string(), remote_head))
+        );
+
+        temp.repo
+            .branch("base", &temp.repo.find_commit(base).unwrap(), false)
+            .unwrap();
+        let counts = ahead_behind(&temp.repo, Some("base")).unwrap();
+        assert_eq!(counts.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((counts.ahead, counts.behind), (2, 1));
+        assert_eq!(counts.base.as_deref(), Some("base"));
+        assert_eq!((counts.base_ahead, counts.base_behind), (2, 0));
+
+        assert!(ahead_behind(&temp.repo, Some("missing")).is_err());
+    }
+
+    #[test]
+    fn upstream_is_none_when_detached_or_unfetched() {
+        let temp = TempRepo::new("upstream");
+        temp.repo
+            .remote("origin", "https://example.com/origin.git")
+            .unwrap();
+        temp.set_config("branch.main.remote", "origin");
+        temp.set_config("branch.main.merge", "refs/heads/main");
+        // Configured but origin/main was never fetched
+        assert_eq!(upstream(&temp.repo).unwrap(), None);
+
+        let head = temp.repo.head().unwrap().target().unwrap();
+        temp.repo.set_head_detached(head).unwrap();
+        assert_eq!(upstream(&temp.repo).unwrap(), None);
+        assert_eq!(ahead_behind(&temp.repo, None).unwrap().upstream, None);
+    }
+
+    /// `main` and `feature` both change file.txt; `feature` is checked out.
+    fn diverged(name: &str) -> TempRepo {
+        let temp = TempRepo::new(name);
+        temp.set_config("user.name", "Test");
+        temp.set_config("user.email", "test@example.com");
+        {
+            let base = temp.repo.head().unwrap().peel_to_commit().unwrap();
+            temp.repo.branch("feature", &base, false).unwrap();
+        }
+        temp.commit("file.txt", "main\n", "main");
+
+        temp.repo.set_head("refs/heads/feature").unwrap();
+        temp.repo
+            .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+        temp.commit("file.txt", "feature\n", "feature");
+        temp
+    }
+
+    fn conflicted_paths(result: &SyncResult) -> Vec<&str> {
+        result.conflicts.iter().map(|c| c.path.as_str()).collect()
+    }
+
+    #[test]
+    fn sync_rejects_bases_that_are_not_branches() {
+        let temp = diverged("sync-base");
+        let head = temp.repo.head().unwrap().peel_to_commit().unwrap();
+        temp.repo
+            .tag_lightweight("v1", head.as_object(), false)
+            .unwrap();
+
+        for base in ["--exec=touch x", "-main", "missing", "v1", "HEAD~1"] {
+            let result = git_sync_with_base(
+                temp.path().to_string(),
+                base.to_string(),
+                SyncStrategy::Merge,
+            );
+            assert!(result.is_err(), "{}", base);
+        }
+        assert_eq!(operation_in_progress(&temp.repo), None);
+    }
+
+    #[test]
+    fn rebase_conflict_is_reported_and_can_be_aborted() {
+        let temp = diverged("sync-rebase");
+        let before = head_id(&temp.repo);
+
+        let result = git_sync_with_base(
+            temp.path().to_string(),
+            "main".to_string(),
+            SyncStrategy::Rebase,
+        )
+        .unwrap();
+        assert_eq!(result.status, SyncStatus::Conflicted);
+        assert_eq!(result.in_progress, Some(SyncStrategy::Rebase));
+        assert_eq!(conflicted_paths(&result), ["file.txt"]);
+        assert_eq!(result.base_ref.as_deref(), Some("main"));
+
+        // Continuing with the conflict unresolved reports it again
+        let result = git_sync_continue(temp.path().to_string()).unwrap();
+        assert_eq!(result.status, SyncStatus::Conflicted);
+        assert_eq!(conflicted_paths(&result), ["file.txt"]);
+
+        git_sync_abort(temp.path().to_string()).unwrap();
+        let repo = Repository::open(temp.path()).unwrap();
+        assert_eq!(operation_in_progress(&repo), None);
+        assert_eq!(head_id(&repo), before);
+        assert!(git_sync_abort(temp.path().to_string()).is_err());
+    }
+
+    #[test]
+    fn merge_conflict_can_be_resolved_and_continued() {
+        let temp = diverged("sync-merge");
+
+        let result = git_sync_with_base(
+            temp.path().to_string(),
+            "main".to_string(),
+            SyncStrategy::Merge,
+        )
+        .unwrap();
+        assert_eq!(result.status, SyncStatus::Conflicted);
+        assert_eq!(result.in_progress, Some(SyncStrategy::Merge));
+        assert_eq!(conflicted_paths(&result), ["file.txt"]);
+
+        fs::write(temp.dir.join("file.txt"), "both\n").unwrap();
+        let output = git(temp.path(), &["add", "file.txt"]).unwrap();
+        assert!(output.status.success());
+
+        let result = git_sync_continue(temp.path().to_string()).unwrap();
+        assert_eq!(result.status, SyncStatus::Updated);
+        assert_eq!(result.in_progress, None);
+        assert!(result.conflicts.is_empty());
+
+        let repo = Repository::open(temp.path()).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 2);
+        assert!(git_sync_continue(temp.path().to_string()).is_err());
+
+        // Now up to date with main
+        let result = git_sync_with_base(
+            temp.path().to_string(),
+            "main".to_string(),
+            SyncStrategy::Merge,
+        )
+        .unwrap();
+        assert_eq!(result.status, SyncStatus::UpToDate);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::TcpListener;
 use std::path::Path;
 use std::process::Command;
@@ -6,19 +6,31 @@ use std::process::Command;
 use tauri::State;
 use tauri_plugin_store::StoreExt;
 
-use crate::models::workspace::{WorkspaceConfig, WorkspaceStatus};
-use crate::state::AppState;
+use crate::commands::checkpoints::delete_workspace_checkpoints;
+use crate::commands::scripts::{
+    load_heroi_config, record_script_error, run_script_steps, stop_workspace_processes,
+};
+use crate::heroi_config;
+use crate::interpolation::resolve_env;
+use crate::models::checkpoint::{AutoCheckpointPolicy, CheckpointRetention};
+use crate::models::scripts::{ArchiveFailurePolicy, ProcessStatus, ScriptPhase, ScriptRunLog};
+use crate::models::workspace::{WorkspaceConfig, WorkspacePortStatus, WorkspaceStatus};
+use crate::state::{AppData, AppState};
 
 const PORT_RANGE_SIZE: u16 = 10;
 const PORT_START: u16 = 3000;
 const PORT_MAX: u16 = 65000;
 
-#[tauri::command]
+/// Create a workspace backed by a new worktree. Unless `run_setup` is
+/// `Some(false)`, the heroi.json `setup` steps are then run in the new
+/// worktree before returning; see `get_script_log` for their results.
+#[tauri::command(async)]
 pub fn create_workspace(
     repo_path: String,
     name: String,
     branch: Option<String>,
     base_branch: Option<String>,
+    run_setup: Option<bool>,
     app: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<WorkspaceConfig, String> {
@@ -31,11 +43,6 @@ pub fn create_workspace(
     git2::Repository::open(&repo_path)
         .map_err(|e| format!("Not a valid git repository: {}", e))?;
 
-    let mut data = state.0.lock().map_err(|e| e.to_string())?;
-
-    // Allocate a port range
-    let port_base = allocate_port_range(&data.allocated_ports)?;
-
     // Create the worktree
     let new_branch = branch.unwrap_or_else(|| name.clone());
     let worktrees_dir = repo_dir.join(".worktrees");
@@ -67,13 +74,35 @@ pub fn create_workspace(
     }
 
     let worktree_path = wt_path.to_string_lossy().to_string();
+    exclude_local_config(&repo_path);
+
+    // Named ports come from the new worktree's heroi.json, loaded the same
+    // way as when its ports are later reassigned
+    let port_offsets = load_heroi_config(worktree_path.clone())
+        .map(|c| c.ports)
+        .unwrap_or_default();
+
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+
+    // Allocate a port range
+    let (port_start, repo_range_size) = repo_port_settings(&data, &repo_path);
+    let port_range_size = required_block_size(repo_range_size, &port_offsets);
+    let port_base = match allocate_port_range(&data.allocated_ports, port_start, port_range_size) {
+        Ok(port_base) => port_base,
+        Err(e) => {
+            drop(data);
+            remove_new_worktree(&repo_path, &worktree_path, &new_branch);
+            return Err(e);
+        }
+    };
 
     // Build env vars
+    let ports = named_ports(port_base, &port_offsets);
     let mut env_vars = HashMap::new();
     env_vars.insert("HEROI_WORKSPACE_PATH".to_string(), worktree_path.clone());
     env_vars.insert("HEROI_ROOT_PATH".to_string(), repo_path.clone());
-    env_vars.insert("HEROI_PORT".to_string(), port_base.to_string());
     env_vars.insert("HEROI_WORKSPACE_NAME".to_string(), name.clone());
+    set_port_env(&mut env_vars, port_base, &ports);
 
     let workspace = WorkspaceConfig {
         id: uuid_v4(),
@@ -84,21 +113,114 @@ pub fn create_workspace(
         is_main_worktree: false,
         env_vars,
         port_base,
+        port_range_size,
+        ports,
+        auto_checkpoints: AutoCheckpointPolicy::default(),
+        checkpoint_retention: CheckpointRetention::default(),
         status: WorkspaceStatus::Active,
         created_at: now_iso8601(),
     };
 
     data.workspaces.push(workspace.clone());
-    data.allocated_ports.insert(port_base);
+    rebuild_allocated_ports(&mut data);
 
     let workspaces = data.workspaces.clone();
     drop(data);
 
     persist_workspaces(&app, &workspaces)?;
 
+    if run_setup.unwrap_or(true) {
+        // The workspace already exists at this point, so a broken heroi.json
+        // must not fail the creation itself; it becomes the setup log instead.
+        if let Err(e) = run_setup_scripts(&app, &workspace) {
+            record_script_error(&app, &workspace.id, ScriptPhase::Setup, e);
+        }
+    }
+
     Ok(workspace)
 }
 
+/// Run the heroi.json `setup` steps for a freshly created workspace with its
+/// env vars plus `HeroiConfig.env`. A failing step does not undo the
+/// workspace; the failure is recorded in the persisted setup log.
+fn run_setup_scripts(app: &tauri::AppHandle, workspace: &WorkspaceConfig) -> Result<(), String> {
+    let config = load_heroi_config(workspace.worktree_path.clone())?;
+    if config.setup.is_empty() {
+        return Ok(());
+    }
+
+    let env = resolve_env(&workspace.env_vars, &config.env)?;
+
+    run_script_steps(
+        app,
+        &workspace.id,
+        ScriptPhase::Setup,
+        &config.setup,
+        &workspace.worktree_path,
+        &env,
+    );
+    Ok(())
+}
+
+/// Run the heroi.json `archive` steps for a workspace that is about to be
+/// archived or deleted, then stop all of its running processes.
+///
+/// With `ArchiveFailurePolicy::Abort` a failing step (or an unreadable
+/// heroi.json) returns an error before anything is stopped, unless `force`
+/// is set. With `Warn` the failure is only reported in the returned log.
+fn run_archive_scripts(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    workspace: &WorkspaceConfig,
+    force: bool,
+) -> Result<Option<ScriptRunLog>, String> {
+    let loaded = load_heroi_config(workspace.worktree_path.clone()).and_then(|config| {
+        let env = resolve_env(&workspace.env_vars, &config.env)?;
+        Ok((config, env))
+    });
+    let (config, env) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) if !force => return Err(format!("{} (use force to skip archive scripts)", e)),
+        Err(_) => Default::default(),
+    };
+
+    let log = if config.archive.is_empty() {
+        None
+    } else {
+        Some(run_script_steps(
+            app,
+            &workspace.id,
+            ScriptPhase::Archive,
+            &config.archive,
+            &workspace.worktree_path,
+            &env,
+        ))
+    };
+
+    if let Some(ref log) = log {
+        if !log.success && config.archive_failure == ArchiveFailurePolicy::Abort && !force {
+            let step = log.steps.last().map(|s| s.name.as_str()).unwrap_or("unknown");
+            return Err(format!(
+                "Archive step '{}' failed; workspace '{}' was left unchanged",
+                step, workspace.name
+            ));
+        }
+    }
+
+    stop_workspace_processes(state, &workspace.id)?;
+
+    Ok(log)
+}
+
+fn find_workspace(state: &AppState, workspace_id: &str) -> Result<WorkspaceConfig, String> {
+    let data = state.0.lock().map_err(|e| e.to_string())?;
+    data.workspaces
+        .iter()
+        .find(|w| w.id == workspace_id)
+        .cloned()
+        .ok_or_else(|| format!("Workspace '{}' not found", workspace_id))
+}
+
 #[tauri::command]
 pub fn create_workspace_for_main(
     repo_path: String,
@@ -124,19 +246,27 @@ pub fn create_workspace_for_main(
         .ok()
         .and_then(|h| h.shorthand().map(|s| s.to_string()))
         .unwrap_or_else(|| "main".to_string());
+    exclude_local_config(&repo_path);
+
+    let port_offsets = load_heroi_config(main_path_clean.clone())
+        .map(|c| c.ports)
+        .unwrap_or_default();
 
     let mut data = state.0.lock().map_err(|e| e.to_string())?;
 
-    let port_base = allocate_port_range(&data.allocated_ports)?;
+    let (port_start, repo_range_size) = repo_port_settings(&data, &repo_path);
+    let port_range_size = required_block_size(repo_range_size, &port_offsets);
+    let port_base = allocate_port_range(&data.allocated_ports, port_start, port_range_size)?;
 
+    let ports = named_ports(port_base, &port_offsets);
     let mut env_vars = HashMap::new();
     env_vars.insert(
         "HEROI_WORKSPACE_PATH".to_string(),
         main_path_clean.clone(),
     );
     env_vars.insert("HEROI_ROOT_PATH".to_string(), repo_path.clone());
-    env_vars.insert("HEROI_PORT".to_string(), port_base.to_string());
     env_vars.insert("HEROI_WORKSPACE_NAME".to_string(), name.clone());
+    set_port_env(&mut env_vars, port_base, &ports);
 
     let workspace = WorkspaceConfig {
         id: uuid_v4(),
@@ -147,12 +277,16 @@ pub fn create_workspace_for_main(
         is_main_worktree: true,
         env_vars,
         port_base,
+        port_range_size,
+        ports,
+        auto_checkpoints: AutoCheckpointPolicy::default(),
+        checkpoint_retention: CheckpointRetention::default(),
         status: WorkspaceStatus::Active,
         created_at: now_iso8601(),
     };
 
     data.workspaces.push(workspace.clone());
-    data.allocated_ports.insert(port_base);
+    rebuild_allocated_ports(&mut data);
 
     let workspaces = data.workspaces.clone();
     drop(data);
@@ -162,20 +296,21 @@ pub fn create_workspace_for_main(
     Ok(workspace)
 }
 
-#[tauri::command]
+/// Delete a workspace and its worktree. The heroi.json `archive` steps run
+/// first and the workspace's processes are stopped; see
+/// `run_archive_scripts` for how failures and `force` are handled. The
+/// workspace's checkpoints and their refs are deleted with it.
+#[tauri::command(async)]
 pub fn delete_workspace(
     workspace_id: String,
+    force: Option<bool>,
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+) -> Result<Option<ScriptRunLog>, String> {
+    let workspace = find_workspace(&state, &workspace_id)?;
+    let archive_log = run_archive_scripts(&app, &state, &workspace, force.unwrap_or(false))?;
 
-    let workspace = data
-        .workspaces
-        .iter()
-        .find(|w| w.id == workspace_id)
-        .cloned()
-        .ok_or_else(|| format!("Workspace '{}' not found", workspace_id))?;
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
 
     // Remove the worktree if it's not the main one
     if !workspace.is_main_worktree {
@@ -224,18 +359,20 @@ pub fn delete_workspace(
             .output();
     }
 
-    // Remove port allocation
-    data.allocated_ports.remove(&workspace.port_base);
-
-    // Remove workspace from list
+    // Remove workspace from list and release its ports
     data.workspaces.retain(|w| w.id != workspace_id);
+    rebuild_allocated_ports(&mut data);
 
     let workspaces = data.workspaces.clone();
     drop(data);
 
     persist_workspaces(&app, &workspaces)?;
 
-    Ok(())
+    if let Err(e) = delete_workspace_checkpoints(&app, &state, &workspace) {
+        eprintln!("Failed to delete checkpoints of '{}': {}", workspace.name, e);
+    }
+
+    Ok(archive_log)
 }
 
 #[tauri::command]
@@ -262,12 +399,18 @@ pub fn list_workspace_configs(
     Ok(data.workspaces.clone())
 }
 
-#[tauri::command]
+/// Archive a workspace after running its heroi.json `archive` steps and
+/// stopping its processes.
+#[tauri::command(async)]
 pub fn archive_workspace(
     workspace_id: String,
+    force: Option<bool>,
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<Option<ScriptRunLog>, String> {
+    let workspace = find_workspace(&state, &workspace_id)?;
+    let archive_log = run_archive_scripts(&app, &state, &workspace, force.unwrap_or(false))?;
+
     let mut data = state.0.lock().map_err(|e| e.to_string())?;
 
     let workspace = data
@@ -282,7 +425,7 @@ pub fn archive_workspace(
     drop(data);
 
     persist_workspaces(&app, &workspaces)?;
-    Ok(())
+    Ok(archive_log)
 }
 
 #[tauri::command]
@@ -318,7 +461,7 @@ pub fn save_workspace_notes(
         .store("heroi-store.json")
         .map_err(|e| e.to_string())?;
     store.set(
-        &format!("workspace_notes_{}", workspace_id),
+        format!("workspace_notes_{}", workspace_id),
         serde_json::Value::String(notes),
     );
     store.save().map_err(|e| e.to_string())?;
@@ -334,31 +477,232 @@ pub fn load_workspace_notes(
         .store("heroi-store.json")
         .map_err(|e| e.to_string())?;
     let notes = store
-        .get(&format!("workspace_notes_{}", workspace_id))
+        .get(format!("workspace_notes_{}", workspace_id))
         .and_then(|v| v.as_str().map(|s| s.to_string()))
         .unwrap_or_default();
     Ok(notes)
 }
 
-fn allocate_port_range(
-    allocated: &std::collections::HashSet<u16>,
-) -> Result<u16, String> {
-    let mut port = PORT_START;
-    while port < PORT_MAX {
-        if !allocated.contains(&port) && is_port_range_available(port) {
+/// Check every active workspace's port block (or just `workspace_id`'s) for
+/// ports already in use and for overlaps with other workspaces.
+#[tauri::command]
+pub fn check_workspace_ports(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<WorkspacePortStatus>, String> {
+    let data = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(port_statuses(&data, workspace_id.as_deref()))
+}
+
+/// Port conflicts found when workspaces were loaded at startup, minus those
+/// since resolved by reassigning ports.
+#[tauri::command]
+pub fn get_startup_port_conflicts(
+    state: State<'_, AppState>,
+) -> Result<Vec<WorkspacePortStatus>, String> {
+    let data = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(data.startup_port_conflicts.clone())
+}
+
+/// Move a workspace to a fresh port block using its repo's current port
+/// settings and heroi.json named ports, updating `HEROI_PORT` and the
+/// `HEROI_PORT_<NAME>` vars. Refused while any of its scripts run.
+#[tauri::command]
+pub fn reassign_workspace_ports(
+    workspace_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<WorkspaceConfig, String> {
+    let current = find_workspace(&state, &workspace_id)?;
+    // Keep the existing layout if heroi.json can't be read
+    let port_offsets = load_heroi_config(current.worktree_path.clone())
+        .map(|c| c.ports)
+        .unwrap_or_else(|_| {
+            current
+                .ports
+                .iter()
+                .map(|(name, port)| (name.clone(), port.saturating_sub(current.port_base)))
+                .collect()
+        });
+
+    let mut data = state.0.lock().map_err(|e| e.to_string())?;
+
+    if data
+        .running_processes
+        .iter()
+        .any(|p| p.workspace_id == workspace_id && p.status == ProcessStatus::Running)
+    {
+        return Err("Stop the workspace's running scripts before reassigning its ports".into());
+    }
+
+    // The old block is still claimed here, so the new one can't reuse it
+    let (port_start, repo_range_size) = repo_port_settings(&data, &current.repo_path);
+    let port_range_size = required_block_size(repo_range_size, &port_offsets);
+    let port_base = allocate_port_range(&data.allocated_ports, port_start, port_range_size)?;
+
+    let workspace = data
+        .workspaces
+        .iter_mut()
+        .find(|w| w.id == workspace_id)
+        .ok_or_else(|| format!("Workspace '{}' not found", workspace_id))?;
+    workspace.port_base = port_base;
+    workspace.port_range_size = port_range_size;
+    workspace.ports = named_ports(port_base, &port_offsets);
+    set_port_env(&mut workspace.env_vars, port_base, &workspace.ports);
+    let updated = workspace.clone();
+
+    rebuild_allocated_ports(&mut data);
+    // The new block is free, so it no longer conflicts with anything
+    data.startup_port_conflicts.retain_mut(|status| {
+        status.overlapping_workspaces.retain(|id| *id != workspace_id);
+        status.workspace_id != workspace_id
+            && (!status.busy_ports.is_empty() || !status.overlapping_workspaces.is_empty())
+    });
+    let workspaces = data.workspaces.clone();
+    drop(data);
+
+    persist_workspaces(&app, &workspaces)?;
+    Ok(updated)
+}
+
+/// Reject per-repo port settings the allocator can't satisfy.
+pub(crate) fn validate_port_settings(
+    port_start: Option<u16>,
+    port_range_size: Option<u16>,
+) -> Result<(), String> {
+    let start = port_start.unwrap_or(PORT_START);
+    let size = port_range_size.unwrap_or(PORT_RANGE_SIZE);
+    if start < 1024 {
+        return Err("Port start must be 1024 or higher".into());
+    }
+    if size == 0 {
+        return Err("Port range size must be at least 1".into());
+    }
+    if start.checked_add(size).is_none_or(|end| end > PORT_MAX) {
+        return Err(format!("Port range must end at or below {}", PORT_MAX));
+    }
+    Ok(())
+}
+
+fn repo_port_settings(data: &AppData, repo_path: &str) -> (u16, u16) {
+    let repo = data.repos.iter().find(|r| r.path == repo_path);
+    (
+        repo.and_then(|r| r.port_start).unwrap_or(PORT_START),
+        repo.and_then(|r| r.port_range_size)
+            .unwrap_or(PORT_RANGE_SIZE),
+    )
+}
+
+/// Block size needed to fit the repo's default size and every named port.
+fn required_block_size(repo_range_size: u16, port_offsets: &HashMap<String, u16>) -> u16 {
+    port_offsets
+        .values()
+        .map(|offset| offset.saturating_add(1))
+        .fold(repo_range_size, u16::max)
+}
+
+fn named_ports(port_base: u16, port_offsets: &HashMap<String, u16>) -> HashMap<String, u16> {
+    port_offsets
+        .iter()
+        .map(|(name, offset)| (name.clone(), port_base.saturating_add(*offset)))
+        .collect()
+}
+
+/// Set `HEROI_PORT` and one `HEROI_PORT_<NAME>` per named port, dropping
+/// named-port vars left over from a previous layout.
+fn set_port_env(
+    env_vars: &mut HashMap<String, String>,
+    port_base: u16,
+    ports: &HashMap<String, u16>,
+) {
+    env_vars.retain(|key, _| !key.starts_with("HEROI_PORT_"));
+    env_vars.insert("HEROI_PORT".to_string(), port_base.to_string());
+    for (name, port) in ports {
+        env_vars.insert(heroi_config::port_env_var(name), port.to_string());
+    }
+}
+
+/// Undo `git worktree add -b` for a workspace that couldn't be created.
+fn remove_new_worktree(repo_path: &str, worktree_path: &str, branch: &str) {
+    let _ = Command::new("git")
+        .current_dir(repo_path)
+        .args(["worktree", "remove", "--force", worktree_path])
+        .output();
+    let _ = Command::new("git")
+        .current_dir(repo_path)
+        .args(["branch", "-D", branch])
+        .output();
+}
+
+fn allocate_port_range(allocated: &HashSet<u16>, start: u16, size: u16) -> Result<u16, String> {
+    let mut port = start;
+    while port.checked_add(size).is_some_and(|end| end <= PORT_MAX) {
+        let claimed = (port..port + size).any(|p| allocated.contains(&p));
+        if !claimed && is_port_range_available(port, size) {
             return Ok(port);
         }
-        port += PORT_RANGE_SIZE;
+        port += size;
     }
-    Err("No available port range found".into())
+    Err(format!(
+        "No free block of {} ports found starting from {}",
+        size, start
+    ))
+}
+
+fn is_port_range_available(base: u16, size: u16) -> bool {
+    (base..base + size).all(|p| TcpListener::bind(("127.0.0.1", p)).is_ok())
 }
 
-fn is_port_range_available(base: u16) -> bool {
-    // Just check the base port — checking all 10 would be slow
-    TcpListener::bind(("127.0.0.1", base)).is_ok()
+fn busy_ports(base: u16, size: u16) -> Vec<u16> {
+    (base..base + size)
+        .filter(|p| TcpListener::bind(("127.0.0.1", *p)).is_err())
+        .collect()
 }
 
-fn persist_workspaces(
+/// Recompute `allocated_ports` as every port of every workspace's block, so
+/// releasing one workspace never frees ports another still overlaps.
+fn rebuild_allocated_ports(data: &mut AppData) {
+    data.allocated_ports = data
+        .workspaces
+        .iter()
+        .flat_map(|w| w.port_base..w.port_base.saturating_add(w.port_range_size))
+        .collect();
+}
+
+fn port_statuses(data: &AppData, workspace_id: Option<&str>) -> Vec<WorkspacePortStatus> {
+    let block = |w: &WorkspaceConfig| w.port_base..w.port_base.saturating_add(w.port_range_size);
+
+    data.workspaces
+        .iter()
+        .filter(|w| w.status == WorkspaceStatus::Active)
+        .filter(|w| workspace_id.is_none_or(|id| w.id == id))
+        .map(|w| {
+            let range = block(w);
+            WorkspacePortStatus {
+                workspace_id: w.id.clone(),
+                port_base: w.port_base,
+                port_range_size: w.port_range_size,
+                busy_ports: busy_ports(w.port_base, w.port_range_size),
+                overlapping_workspaces: data
+                    .workspaces
+                    .iter()
+                    .filter(|other| other.id != w.id)
+                    .filter(|other| {
+                        let theirs = block(other);
+                        theirs.start < range.end && range.start < theirs.end
+                    })
+                    .map(|other| other.id.clone())
+                    .collect(),
+                has_running_processes: data
+                    .running_processes
+                    .iter()
+                    .any(|p| p.workspace_id == w.id && p.status == ProcessStatus::Running),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn persist_workspaces(
     app: &tauri::AppHandle,
     workspaces: &[WorkspaceConfig],
 ) -> Result<(), String> {
@@ -384,15 +728,32 @@ pub fn load_workspace_configs(
         let workspaces: Vec<WorkspaceConfig> =
             serde_json::from_value(val.clone()).map_err(|e| e.to_string())?;
         let mut data = state.0.lock().map_err(|e| e.to_string())?;
-        for w in &workspaces {
-            data.allocated_ports.insert(w.port_base);
-        }
         data.workspaces = workspaces;
+        rebuild_allocated_ports(&mut data);
+
+        // Nothing of ours is running yet, so any busy port belongs to
+        // another program. Kept for the UI, which isn't listening yet.
+        data.startup_port_conflicts = port_statuses(&data, None)
+            .into_iter()
+            .filter(|s| !s.busy_ports.is_empty() || !s.overlapping_workspaces.is_empty())
+            .collect();
     }
     Ok(())
 }
 
-fn uuid_v4() -> String {
+/// Keep heroi.local.json out of the repository's untracked files. Failing
+/// to is not worth failing workspace creation over.
+fn exclude_local_config(repo_path: &str) {
+    if let Err(e) = heroi_config::ensure_local_config_excluded(Path::new(repo_path)) {
+        eprintln!(
+            "Failed to git-ignore {}: {}",
+            heroi_config::LOCAL_CONFIG_FILE,
+            e
+        );
+    }
+}
+
+pub(crate) fn uuid_v4() -> String {
     // Simple UUID v4 using random bytes
     use std::time::{SystemTime, UNIX_EPOCH};
     let now = SystemTime::now()
@@ -413,12 +774,25 @@ pub fn now_iso8601_pub() -> String {
     now_iso8601()
 }
 
+/// Timestamp `secs` seconds ago. Timestamps share a fixed-width format, so
+/// they compare correctly as strings.
+pub(crate) fn iso8601_secs_ago(secs: u64) -> String {
+    iso8601_from_unix(unix_now().saturating_sub(secs))
+}
+
 fn now_iso8601() -> String {
+    iso8601_from_unix(unix_now())
+}
+
+fn unix_now() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
-    let duration = SystemTime::now()
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
-    let secs = duration.as_secs();
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn iso8601_from_unix(secs: u64) -> String {
     // Simple ISO 8601 without external deps
     let days = secs / 86400;
     let time_secs = secs % 86400;
@@ -461,3 +835,160 @@ fn now_iso8601() -> String {
 fn is_leap(y: i64) -> bool {
     (y % 4 == 0 && y % 100 != 0) || y % 400 == 0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::repo::RepoEntry;
+
+    fn workspace(id: &str, port_base: u16, port_range_size: u16) -> WorkspaceConfig {
+        WorkspaceConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            repo_path: "/repo".to_string(),
+            worktree_path: format!("/repo-{}", id),
+            branch: id.to_string(),
+            is_main_worktree: false,
+            env_vars: HashMap::new(),
+            port_base,
+            port_range_size,
+            ports: HashMap::new(),
+            auto_checkpoints: AutoCheckpointPolicy::default(),
+            checkpoint_retention: CheckpointRetention::default(),
+            status: WorkspaceStatus::Active,
+            created_at: String::new(),
+        }
+    }
+
+    fn offsets(entries: &[(&str, u16)]) -> HashMap<String, u16> {
+        entries
+            .iter()
+            .map(|(name, offset)| (name.to_string(), *offset))
+            .collect()
+    }
+
+    #[test]
+    fn allocation_skips_blocks_with_claimed_ports() {
+        let allocated: HashSet<u16> = [41003, 41010].into_iter().collect();
+        assert_eq!(allocate_port_range(&allocated, 41000, 10), Ok(41020));
+        assert_eq!(allocate_port_range(&allocated, 41000, 4), Ok(41004));
+        assert_eq!(allocate_port_range(&HashSet::new(), 41000, 10), Ok(41000));
+    }
+
+    #[test]
+    fn allocation_skips_ports_in_use() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let busy = listener.local_addr().unwrap().port();
+        let base = allocate_port_range(&HashSet::new(), busy, 1).unwrap();
+        assert!(base > busy);
+    }
+
+    #[test]
+    fn allocation_fails_when_the_range_is_full() {
+        let allocated: HashSet<u16> = (PORT_MAX - 20..PORT_MAX).collect();
+        let err = allocate_port_range(&allocated, PORT_MAX - 20, 10).unwrap_err();
+        assert_eq!(
+            err,
+            format!(
+                "No free block of 10 ports found starting from {}",
+                PORT_MAX - 20
+            )
+        );
+        // A block that would run past the end is never handed out
+        assert!(allocate_port_range(&HashSet::new(), PORT_MAX - 5, 10).is_err());
+    }
+
+    #[test]
+    fn block_size_fits_the_highest_named_port() {
+        assert_eq!(required_block_size(10, &HashMap::new()), 10);
+        assert_eq!(
+            required_block_size(10, &offsets(&[("web", 0), ("api", 3)])),
+            10
+        );
+        assert_eq!(
+            required_block_size(10, &offsets(&[("web", 0), ("api", 14)])),
+            15
+        );
+        assert_eq!(
+            required_block_size(4, &offsets(&[("max", u16::MAX)])),
+            u16::MAX
+        );
+    }
+
+    #[test]
+    fn repos_can_override_port_start_and_block_size() {
+        let mut data = AppData::default();
+        data.repos.push(RepoEntry {
+            path: "/custom".to_string(),
+            name: "custom".to_string(),
+            port_start: Some(5000),
+            port_range_size: Some(4),
+        });
+        data.repos.push(RepoEntry {
+            path: "/partial".to_string(),
+            name: "partial".to_string(),
+            port_start: None,
+            port_range_size: Some(20),
+        });
+
+        assert_eq!(repo_port_settings(&data, "/custom"), (5000, 4));
+        assert_eq!(repo_port_settings(&data, "/partial"), (PORT_START, 20));
+        assert_eq!(
+            repo_port_settings(&data, "/unknown"),
+            (PORT_START, PORT_RANGE_SIZE)
+        );
+    }
+
+    #[test]
+    fn port_statuses_report_overlapping_blocks() {
+        let data = AppData {
+            workspaces: vec![
+                workspace("a", 41000, 10),
+                workspace("b", 41005, 10),
+                workspace("c", 41015, 5),
+                workspace("d", 41020, 10),
+                WorkspaceConfig {
+                    status: WorkspaceStatus::Archived,
+                    ..workspace("archived", 41020, 10)
+                },
+            ],
+            ..Default::default()
+        };
+
+        let statuses = port_statuses(&data, None);
+        let overlaps: Vec<(&str, Vec<&str>)> = statuses
+            .iter()
+            .map(|s| {
+                let others = s.overlapping_workspaces.iter().map(String::as_str);
+                (s.workspace_id.as_str(), others.collect())
+            })
+            .collect();
+        assert_eq!(
+            overlaps,
+            [
+                ("a", vec!["b"]),
+                ("b", vec!["a"]),
+                ("c", vec![]),
+                ("d", vec!["archived"]),
+            ]
+        );
+
+        let statuses = port_statuses(&data, Some("b"));
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].overlapping_workspaces, ["a"]);
+    }
+
+    #[test]
+    fn port_statuses_report_busy_ports() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let busy = listener.local_addr().unwrap().port();
+        let data = AppData {
+            workspaces: vec![workspace("a", busy, 1)],
+            ..Default::default()
+        };
+
+        let statuses = port_statuses(&data, None);
+        assert_eq!(statuses[0].busy_ports, [busy]);
+        assert!(!statuses[0].has_running_processes);
+    }
+}
@@ -21,35 +21,31 @@ pub fn list_branches(repo_path: String) -> Result<Vec<BranchInfo>, String> {
 
     // Local branches
     if let Ok(local) = repo.branches(Some(git2::BranchType::Local)) {
-        for item in local {
-            if let Ok((branch, _)) = item {
-                if let Ok(Some(name)) = branch.name() {
-                    let is_head = branch.is_head();
-                    branches.push(BranchInfo {
-                        name: name.to_string(),
-                        is_remote: false,
-                        is_head,
-                    });
-                }
+        for (branch, _) in local.flatten() {
+            if let Ok(Some(name)) = branch.name() {
+                let is_head = branch.is_head();
+                branches.push(BranchInfo {
+                    name: name.to_string(),
+                    is_remote: false,
+                    is_head,
+                });
             }
         }
     }
 
     // Remote branches
     if let Ok(remote) = repo.branches(Some(git2::BranchType::Remote)) {
-        for item in remote {
-            if let Ok((branch, _)) = item {
-                if let Ok(Some(name)) = branch.name() {
-                    // Skip HEAD pointers like origin/HEAD
-                    if name.ends_with("/HEAD") {
-                        continue;
-                    }
-                    branches.push(BranchInfo {
-                        name: name.to_string(),
-                        is_remote: true,
-                        is_head: false,
-                    });
+        for (branch, _) in remote.flatten() {
+            if let Ok(Some(name)) = branch.name() {
+                // Skip HEAD pointers like origin/HEAD
+                if name.ends_with("/HEAD") {
+                    continue;
                 }
+                branches.push(BranchInfo {
+                    name: name.to_string(),
+                    is_remote: true,
+                    is_head: false,
+                });
             }
         }
     }
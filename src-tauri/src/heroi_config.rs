@@ -0,0 +1,788 @@
+//! Loading, migrating and validating heroi.json.
+//!
+//! A worktree's config is `heroi.json`, optionally overridden by an untracked
+//! `heroi.local.json`. Both files are parsed to JSON, migrated to
+//! `CURRENT_VERSION`, merged (local on top) and then deserialized, collecting
+//! diagnostics with file/line/column positions along the way.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::models::scripts::{ConfigDiagnostic, DiagnosticSeverity, HeroiConfig};
+
+pub const CONFIG_FILE: &str = "heroi.json";
+pub const LOCAL_CONFIG_FILE: &str = "heroi.local.json";
+
+/// Upgrades a config object by one version: `MIGRATIONS[i]` turns version
+/// `i + 1` into version `i + 2`.
+type Migration = fn(&mut Map<String, Value>);
+
+const MIGRATIONS: &[Migration] = &[];
+
+/// The format version written by this build. Files without a `version` key
+/// are treated as version 1.
+pub const CURRENT_VERSION: u32 = MIGRATIONS.len() as u32 + 1;
+
+/// Top-level arrays whose entries are merged by `name` instead of replaced.
+const SCRIPT_LISTS: &[&str] = &["setup", "run", "archive"];
+
+/// Result of loading a worktree's config. `config` is `None` when an error
+/// diagnostic prevented it from being built.
+pub struct LoadedConfig {
+    pub config: Option<HeroiConfig>,
+    pub diagnostics: Vec<ConfigDiagnostic>,
+}
+
+/// Load heroi.json and heroi.local.json from `worktree_path`. A missing
+/// heroi.json yields the default config.
+pub fn load(worktree_path: &Path) -> LoadedConfig {
+    let mut diagnostics = Vec::new();
+
+    let base = read_config_file(worktree_path, CONFIG_FILE, &mut diagnostics);
+    let local = read_config_file(worktree_path, LOCAL_CONFIG_FILE, &mut diagnostics);
+
+    if has_errors(&diagnostics) {
+        return LoadedConfig { config: None, diagnostics };
+    }
+
+    let mut merged = base.as_ref().map(|f| f.value.clone()).unwrap_or_default();
+    if let Some(ref local) = local {
+        merge_config(&mut merged, local.value.clone());
+    }
+    merged.insert("version".to_string(), Value::from(CURRENT_VERSION));
+
+    // Diagnostics from the merged value are attributed to the local file when
+    // it defines the path, since its value is the one that won.
+    let locate = |path: &str| -> (String, Option<(usize, usize)>) {
+        if let Some(pos) = local.as_ref().and_then(|f| f.positions.get(path)) {
+            return (LOCAL_CONFIG_FILE.to_string(), Some(*pos));
+        }
+        let pos = base.as_ref().and_then(|f| f.positions.get(path)).copied();
+        (CONFIG_FILE.to_string(), pos)
+    };
+
+    let mut unknown = Vec::new();
+    let mut track_unknown = |path: serde_ignored::Path| unknown.push(format_ignored_path(&path));
+    let de = serde_ignored::Deserializer::new(Value::Object(merged), &mut track_unknown);
+    let result: Result<HeroiConfig, _> = serde_path_to_error::deserialize(de);
+
+    for path in unknown {
+        let (file, pos) = locate(&path);
+        diagnostics.push(diagnostic(
+            DiagnosticSeverity::Warning,
+            file,
+            path.clone(),
+            pos,
+            format!("Unknown key '{}'", path.rsplit('.').next().unwrap_or(&path)),
+        ));
+    }
+
+    let config = match result {
+        Ok(config) => {
+            let collisions = port_name_collisions(&config.ports);
+            for (name, other) in &collisions {
+                let path = format!("ports.{}", name);
+                let (file, pos) = locate(&path);
+                diagnostics.push(diagnostic(
+                    DiagnosticSeverity::Error,
+                    file,
+                    path,
+                    pos,
+                    format!(
+                        "Ports '{}' and '{}' would both be {}",
+                        other,
+                        name,
+                        port_env_var(name)
+                    ),
+                ));
+            }
+            collisions.is_empty().then_some(config)
+        }
+        Err(e) => {
+            let path = format_error_path(e.path());
+            let (file, pos) = locate(&path);
+            diagnostics.push(diagnostic(
+                DiagnosticSeverity::Error,
+                file,
+                path,
+                pos,
+                e.into_inner().to_string(),
+            ));
+            None
+        }
+    };
+
+    LoadedConfig { config, diagnostics }
+}
+
+pub fn has_errors(diagnostics: &[ConfigDiagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity == DiagnosticSeverity::Error)
+}
+
+/// One-line summary of a diagnostic, e.g.
+/// `heroi.json:4:12: run[0].args: invalid type: string "x", expected a sequence`.
+pub fn format_diagnostic(d: &ConfigDiagnostic) -> String {
+    let location = match (d.line, d.column) {
+        (Some(line), Some(column)) => format!("{}:{}:{}", d.file, line, column),
+        _ => d.file.clone(),
+    };
+    if d.path.is_empty() {
+        format!("{}: {}", location, d.message)
+    } else {
+        format!("{}: {}: {}", location, d.path, d.message)
+    }
+}
+
+pub fn diagnostic(
+    severity: DiagnosticSeverity,
+    file: String,
+    path: String,
+    pos: Option<(usize, usize)>,
+    message: String,
+) -> ConfigDiagnostic {
+    ConfigDiagnostic {
+        severity,
+        file,
+        path,
+        line: pos.map(|(line, _)| line),
+        column: pos.map(|(_, column)| column),
+        message,
+    }
+}
+
+/// `web` -> `HEROI_PORT_WEB`, `admin-ui` -> `HEROI_PORT_ADMIN_UI`.
+pub fn port_env_var(name: &str) -> String {
+    let suffix: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("HEROI_PORT_{}", suffix)
+}
+
+/// Named ports whose env var another name already maps to (e.g. `admin_ui`
+/// after `admin-ui`), each paired with that first name. Names are taken in
+/// sorted order so the result is stable.
+fn port_name_collisions(ports: &HashMap<String, u16>) -> Vec<(String, String)> {
+    let mut names: Vec<&String> = ports.keys().collect();
+    names.sort();
+    let mut seen: HashMap<String, &String> = HashMap::new();
+    let mut collisions = Vec::new();
+    for name in names {
+        match seen.get(&port_env_var(name)) {
+            Some(first) => collisions.push((name.clone(), (*first).clone())),
+            None => {
+                seen.insert(port_env_var(name), name);
+            }
+        }
+    }
+    collisions
+}
+
+struct ConfigFile {
+    value: Map<String, Value>,
+    /// Line/column of every key and array element, by diagnostic path.
+    positions: HashMap<String, (usize, usize)>,
+}
+
+/// Read, parse and migrate one config file. Returns `None` if it doesn't
+/// exist or can't be used, in which case a diagnostic explains why.
+fn read_config_file(
+    worktree_path: &Path,
+    name: &str,
+    diagnostics: &mut Vec<ConfigDiagnostic>,
+) -> Option<ConfigFile> {
+    let path = worktree_path.join(name);
+    if !path.exists() {
+        return None;
+    }
+
+    let mut error = |path: &str, pos: Option<(usize, usize)>, message: String| {
+        diagnostics.push(diagnostic(
+            DiagnosticSeverity::Error,
+            name.to_string(),
+            path.to_string(),
+            pos,
+            message,
+        ));
+    };
+
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => {
+            error("", None, format!("Failed to read {}: {}", name, e));
+            return None;
+        }
+    };
+
+    let value = match serde_json::from_str::<Value>(&text) {
+        Ok(Value::Object(map)) => map,
+        Ok(_) => {
+            error("", Some((1, 1)), "Expected a JSON object".to_string());
+            return None;
+        }
+        Err(e) => {
+            // Drop serde_json's " at line X column Y" suffix; the position
+            // is reported separately.
+            let message = e.to_string();
+            let suffix = format!(" at line {} column {}", e.line(), e.column());
+            let message = message.strip_suffix(&suffix).unwrap_or(&message).to_string();
+            error("", Some((e.line(), e.column())), message);
+            return None;
+        }
+    };
+
+    let positions = locate_paths(&text);
+    let mut value = value;
+
+    let version = match value.get("version") {
+        None => 1,
+        Some(v) => match v.as_u64().and_then(|v| u32::try_from(v).ok()) {
+            Some(v) if v >= 1 => v,
+            _ => {
+                let pos = positions.get("version").copied();
+                error("version", pos, "Expected a positive integer".to_string());
+                return None;
+            }
+        },
+    };
+
+    if version > CURRENT_VERSION {
+        let pos = positions.get("version").copied();
+        error(
+            "version",
+            pos,
+            format!(
+                "{} is format version {}, but this version of Heroi only supports up to {}",
+                name, version, CURRENT_VERSION
+            ),
+        );
+        return None;
+    }
+
+    migrate(&mut value, version, MIGRATIONS);
+    Some(ConfigFile { value, positions })
+}
+
+/// Bring a `version` config up to date by running the `migrations` after it.
+fn migrate(value: &mut Map<String, Value>, version: u32, migrations: &[Migration]) {
+    for migration in &migrations[(version - 1) as usize..] {
+        migration(value);
+    }
+}
+
+/// Merge a local override onto the base config. Objects merge recursively,
+/// `setup`/`run`/`archive` entries are matched by `name` (overriding the
+/// fields they set, or appended if new) and everything else is replaced.
+fn merge_config(base: &mut Map<String, Value>, local: Map<String, Value>) {
+    for (key, value) in local {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Array(existing)), Value::Array(overrides))
+                if SCRIPT_LISTS.contains(&key.as_str()) =>
+            {
+                merge_scripts(existing, overrides)
+            }
+            (Some(Value::Object(existing)), Value::Object(overrides)) => {
+                merge_config(existing, overrides)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn merge_scripts(base: &mut Vec<Value>, overrides: Vec<Value>) {
+    for script in overrides {
+        let name = script.get("name").and_then(Value::as_str);
+        let existing = name.and_then(|name| {
+            base.iter_mut()
+                .find(|s| s.get("name").and_then(Value::as_str) == Some(name))
+        });
+        match (existing, script) {
+            (Some(Value::Object(existing)), Value::Object(fields)) => existing.extend(fields),
+            (_, script) => base.push(script),
+        }
+    }
+}
+
+/// Add heroi.local.json to the repository's `info/exclude` so it never shows
+/// up as an untracked change. Worktrees share the main repository's file.
+/// Done when a workspace is created, so loading a config never writes to
+/// the repository.
+pub fn ensure_local_config_excluded(repo_path: &Path) -> Result<(), String> {
+    let repo = git2::Repository::open(repo_path)
+        .map_err(|e| format!("Failed to open repo: {}", e))?;
+    let info_dir = repo.commondir().join("info");
+    let exclude_path = info_dir.join("exclude");
+
+    let existing = std::fs::read_to_string(&exclude_path).unwrap_or_default();
+    let pattern = format!("/{}", LOCAL_CONFIG_FILE);
+    if existing
+        .lines()
+        .any(|l| l.trim() == pattern || l.trim() == LOCAL_CONFIG_FILE)
+    {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(&info_dir).map_err(|e| format!("Failed to create {}: {}", info_dir.display(), e))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&exclude_path)
+        .map_err(|e| format!("Failed to open {}: {}", exclude_path.display(), e))?;
+    let separator = if existing.is_empty() || existing.ends_with('\n') { "" } else { "\n" };
+    writeln!(file, "{}{}", separator, pattern)
+        .map_err(|e| format!("Failed to write {}: {}", exclude_path.display(), e))
+}
+
+fn format_error_path(path: &serde_path_to_error::Path) -> String {
+    let mut out = String::new();
+    for segment in path.iter() {
+        match segment {
+            serde_path_to_error::Segment::Seq { index } => out.push_str(&format!("[{}]", index)),
+            serde_path_to_error::Segment::Map { key } => push_key(&mut out, key),
+            serde_path_to_error::Segment::Enum { variant } => push_key(&mut out, variant),
+            serde_path_to_error::Segment::Unknown => {}
+        }
+    }
+    out
+}
+
+fn format_ignored_path(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => String::new(),
+        Path::Seq { parent, index } => format!("{}[{}]", format_ignored_path(parent), index),
+        Path::Map { parent, key } => {
+            let mut out = format_ignored_path(parent);
+            push_key(&mut out, key);
+            out
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => format_ignored_path(parent),
+    }
+}
+
+fn push_key(out: &mut String, key: &str) {
+    if !out.is_empty() {
+        out.push('.');
+    }
+    out.push_str(key);
+}
+
+/// Map every object key and array element in `text` to the 1-based
+/// line/column where it starts, keyed by the same path format used in
+/// diagnostics (`run[0].args[1]`). `text` must be valid JSON.
+fn locate_paths(text: &str) -> HashMap<String, (usize, usize)> {
+    let mut locator = Locator {
+        chars: text.chars().peekable(),
+        line: 1,
+        column: 1,
+        positions: HashMap::new(),
+    };
+    locator.value(String::new());
+    locator.positions
+}
+
+struct Locator<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+    positions: HashMap<String, (usize, usize)>,
+}
+
+impl Locator<'_> {
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn value(&mut self, path: String) {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('{') => {
+                self.bump();
+                loop {
+                    self.skip_whitespace();
+                    if self.chars.peek() != Some(&'"') {
+                        self.bump(); // '}'
+                        return;
+                    }
+                    let pos = (self.line, self.column);
+                    let mut child = path.clone();
+                    push_key(&mut child, &self.string());
+                    self.positions.insert(child.clone(), pos);
+                    self.skip_whitespace();
+                    self.bump(); // ':'
+                    self.value(child);
+                    self.skip_whitespace();
+                    if self.bump() != Some(',') {
+                        return;
+                    }
+                }
+            }
+            Some('[') => {
+                self.bump();
+                let mut index = 0;
+                loop {
+                    self.skip_whitespace();
+                    if self.chars.peek() == Some(&']') {
+                        self.bump();
+                        return;
+                    }
+                    let child = format!("{}[{}]", path, index);
+                    self.positions.insert(child.clone(), (self.line, self.column));
+                    self.value(child);
+                    index += 1;
+                    self.skip_whitespace();
+                    if self.bump() != Some(',') {
+                        return;
+                    }
+                }
+            }
+            Some('"') => {
+                self.string();
+            }
+            _ => {
+                while self
+                    .chars
+                    .peek()
+                    .is_some_and(|c| !matches!(c, ',' | '}' | ']') && !c.is_whitespace())
+                {
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    /// Consume a string literal and return its (unescaped) contents.
+    fn string(&mut self) -> String {
+        let mut raw = String::from("\"");
+        self.bump();
+        while let Some(c) = self.bump() {
+            raw.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = self.bump() {
+                        raw.push(escaped);
+                    }
+                }
+                '"' => break,
+                _ => {}
+            }
+        }
+        serde_json::from_str(&raw).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    use crate::models::scripts::ScriptDef;
+
+    /// A throwaway worktree directory holding config files.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("heroi-config-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn write(&self, file: &str, content: &str) {
+            std::fs::write(self.0.join(file), content).unwrap();
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("not an object"),
+        }
+    }
+
+    fn script<'a>(scripts: &'a [ScriptDef], name: &str) -> &'a ScriptDef {
+        scripts.iter().find(|s| s.name == name).unwrap()
+    }
+
+    #[test]
+    fn merge_overrides_scripts_by_name_and_objects_by_key() {
+        let mut base = object(json!({
+            "run": [
+                { "name": "web", "command": "npm", "args": ["start"] },
+                { "name": "api", "command": "cargo" }
+            ],
+            "env": { "A": "1", "B": "2" },
+            "$schema": "old.json"
+        }));
+        let local = object(json!({
+            "run": [
+                { "name": "web", "args": ["run", "dev"] },
+                { "name": "docs", "command": "mdbook" },
+                { "command": "unnamed" }
+            ],
+            "env": { "B": "3" },
+            "$schema": "new.json"
+        }));
+        merge_config(&mut base, local);
+
+        assert_eq!(
+            Value::Object(base),
+            json!({
+                "run": [
+                    { "name": "web", "command": "npm", "args": ["run", "dev"] },
+                    { "name": "api", "command": "cargo" },
+                    { "name": "docs", "command": "mdbook" },
+                    { "command": "unnamed" }
+                ],
+                "env": { "A": "1", "B": "3" },
+                "$schema": "new.json"
+            })
+        );
+    }
+
+    #[test]
+    fn merge_replaces_scripts_that_are_not_objects() {
+        let mut base = vec![json!({ "name": "web", "command": "npm" })];
+        merge_scripts(
+            &mut base,
+            vec![json!("web"), json!({ "name": "web", "command": "yarn" })],
+        );
+        assert_eq!(
+            base,
+            vec![json!({ "name": "web", "command": "yarn" }), json!("web")]
+        );
+    }
+
+    #[test]
+    fn locate_paths_maps_keys_and_elements_to_positions() {
+        let text = "{\n  \"run\": [\n    { \"name\": \"a\\\"b\" },\n    [1, {\"x\": {}}]\n  ],\n\t\"port\": 3\n}";
+        let positions = locate_paths(text);
+
+        assert_eq!(positions["run"], (2, 3));
+        assert_eq!(positions["run[0]"], (3, 5));
+        assert_eq!(positions["run[0].name"], (3, 7));
+        assert_eq!(positions["run[1]"], (4, 5));
+        assert_eq!(positions["run[1][0]"], (4, 6));
+        assert_eq!(positions["run[1][1]"], (4, 9));
+        assert_eq!(positions["run[1][1].x"], (4, 10));
+        assert_eq!(positions["port"], (6, 2));
+        assert_eq!(positions.len(), 8);
+    }
+
+    #[test]
+    fn load_merges_local_overrides_and_attributes_diagnostics() {
+        let dir = TempDir::new("load");
+        dir.write(
+            CONFIG_FILE,
+            r#"{
+  "run": [{ "name": "web", "command": "npm", "args": ["start"] }],
+  "colour": "blue"
+}"#,
+        );
+        dir.write(
+            LOCAL_CONFIG_FILE,
+            r#"{
+  "run": [{ "name": "web", "args": ["run", "dev"] }]
+}"#,
+        );
+
+        let loaded = load(&dir.0);
+        let config = loaded.config.unwrap();
+        assert_eq!(config.version, CURRENT_VERSION);
+        assert_eq!(script(&config.run, "web").command, "npm");
+        assert_eq!(script(&config.run, "web").args, ["run", "dev"]);
+
+        assert_eq!(loaded.diagnostics.len(), 1);
+        let warning = &loaded.diagnostics[0];
+        assert_eq!(warning.severity, DiagnosticSeverity::Warning);
+        assert_eq!(warning.file, CONFIG_FILE);
+        assert_eq!((warning.line, warning.column), (Some(3), Some(3)));
+        assert_eq!(warning.message, "Unknown key 'colour'");
+
+        dir.write(
+            LOCAL_CONFIG_FILE,
+            "{\n  \"run\": [{ \"name\": \"web\", \"args\": \"dev\" }]\n}",
+        );
+        let loaded = load(&dir.0);
+        assert!(loaded.config.is_none());
+        let error = loaded
+            .diagnostics
+            .iter()
+            .find(|d| d.severity == DiagnosticSeverity::Error)
+            .unwrap();
+        assert_eq!(error.file, LOCAL_CONFIG_FILE);
+        assert_eq!(error.path, "run[0].args");
+        assert_eq!((error.line, error.column), (Some(2), Some(28)));
+    }
+
+    #[test]
+    fn load_rejects_unsupported_versions() {
+        let dir = TempDir::new("version");
+        dir.write(
+            CONFIG_FILE,
+            &format!("{{\"version\": {}}}", CURRENT_VERSION + 1),
+        );
+        let loaded = load(&dir.0);
+        assert!(loaded.config.is_none());
+        let error = &loaded.diagnostics[0];
+        assert_eq!(error.path, "version");
+        assert_eq!((error.line, error.column), (Some(1), Some(2)));
+        assert!(
+            error.message.contains("only supports up to"),
+            "{}",
+            error.message
+        );
+
+        for version in ["0", "-1", "\"2\"", "1.5"] {
+            dir.write(CONFIG_FILE, &format!("{{\"version\": {}}}", version));
+            let loaded = load(&dir.0);
+            assert!(loaded.config.is_none(), "{}", version);
+            assert_eq!(loaded.diagnostics[0].message, "Expected a positive integer");
+        }
+
+        dir.write(CONFIG_FILE, "{}");
+        assert_eq!(load(&dir.0).config.unwrap().version, CURRENT_VERSION);
+    }
+
+    #[test]
+    fn migrations_run_from_the_file_version() {
+        fn rename_cmd(value: &mut Map<String, Value>) {
+            if let Some(cmd) = value.remove("cmd") {
+                value.insert("command".to_string(), cmd);
+            }
+        }
+        fn wrap_command(value: &mut Map<String, Value>) {
+            if let Some(command) = value.remove("command") {
+                value.insert("commands".to_string(), json!([command]));
+            }
+        }
+        let migrations: &[Migration] = &[rename_cmd, wrap_command];
+
+        let mut v1 = object(json!({ "cmd": "npm" }));
+        migrate(&mut v1, 1, migrations);
+        assert_eq!(Value::Object(v1), json!({ "commands": ["npm"] }));
+
+        let mut v2 = object(json!({ "cmd": "npm", "command": "yarn" }));
+        migrate(&mut v2, 2, migrations);
+        assert_eq!(
+            Value::Object(v2),
+            json!({ "cmd": "npm", "commands": ["yarn"] })
+        );
+
+        let mut current = object(json!({ "command": "npm" }));
+        migrate(&mut current, 3, migrations);
+        assert_eq!(Value::Object(current), json!({ "command": "npm" }));
+    }
+
+    #[test]
+    fn load_does_not_touch_the_repository() {
+        let dir = TempDir::new("exclude");
+        git2::Repository::init(&dir.0).unwrap();
+        dir.write(LOCAL_CONFIG_FILE, "{}");
+        let exclude = dir.0.join(".git/info/exclude");
+        let before = std::fs::read_to_string(&exclude).unwrap_or_default();
+
+        assert!(load(&dir.0).config.is_some());
+        assert_eq!(
+            std::fs::read_to_string(&exclude).unwrap_or_default(),
+            before
+        );
+
+        ensure_local_config_excluded(&dir.0).unwrap();
+        ensure_local_config_excluded(&dir.0).unwrap();
+        let after = std::fs::read_to_string(&exclude).unwrap();
+        assert_eq!(after.matches("/heroi.local.json").count(), 1);
+    }
+
+    #[test]
+    fn schema_file_is_up_to_date() {
+        let generated = serde_json::to_value(schemars::schema_for!(HeroiConfig)).unwrap();
+        let committed: Value =
+            serde_json::from_str(include_str!("../../heroi.schema.json")).unwrap();
+        assert!(
+            generated == committed,
+            "heroi.schema.json is stale; regenerate it from get_heroi_config_schema:\n{}",
+            serde_json::to_string_pretty(&generated).unwrap()
+        );
+    }
+
+    #[test]
+    fn port_names_map_to_env_vars() {
+        assert_eq!(port_env_var("web"), "HEROI_PORT_WEB");
+        assert_eq!(port_env_var("admin-ui"), "HEROI_PORT_ADMIN_UI");
+        assert_eq!(port_env_var("api.v2"), "HEROI_PORT_API_V2");
+        assert_eq!(port_env_var("café"), "HEROI_PORT_CAF_");
+    }
+
+    #[test]
+    fn load_rejects_port_names_with_the_same_env_var() {
+        let dir = TempDir::new("ports");
+        dir.write(
+            CONFIG_FILE,
+            r#"{
+  "ports": { "web": 0, "admin-ui": 1, "admin_ui": 2, "ADMIN.UI": 3 }
+}"#,
+        );
+        let loaded = load(&dir.0);
+        assert!(loaded.config.is_none());
+
+        let errors: Vec<(&str, Option<usize>, &str)> = loaded
+            .diagnostics
+            .iter()
+            .map(|d| (d.path.as_str(), d.column, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (
+                    "ports.admin-ui",
+                    Some(24),
+                    "Ports 'ADMIN.UI' and 'admin-ui' would both be HEROI_PORT_ADMIN_UI"
+                ),
+                (
+                    "ports.admin_ui",
+                    Some(39),
+                    "Ports 'ADMIN.UI' and 'admin_ui' would both be HEROI_PORT_ADMIN_UI"
+                ),
+            ]
+        );
+
+        dir.write(CONFIG_FILE, r#"{ "ports": { "web": 0, "admin-ui": 1 } }"#);
+        assert_eq!(load(&dir.0).config.unwrap().ports.len(), 2);
+    }
+}
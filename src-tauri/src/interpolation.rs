@@ -0,0 +1,210 @@
+//! `${...}` placeholder resolution for heroi.json commands, args and env.
+//!
+//! Supported forms:
+//! - `${NAME}` — a workspace variable (e.g. `HEROI_PORT`, `HEROI_WORKSPACE_NAME`)
+//! - `${NAME+N}` / `${NAME-N}` — a numeric workspace variable plus/minus `N`
+//! - `${env:NAME}` — a variable from Heroi's own process environment
+//! - `${...:-default}` — any of the above, falling back to `default` if unset
+//!
+//! `$${` produces a literal `${`. A `$` not followed by `{` is left alone so
+//! shell syntax such as `$HOME` or `$$` passes through untouched.
+
+use std::collections::HashMap;
+
+use crate::models::scripts::{PortValue, ScriptDef};
+
+/// Replace every placeholder in `input` using `vars`.
+pub fn interpolate(input: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        if let Some(escaped) = after.strip_prefix("${") {
+            out.push_str("${");
+            rest = escaped;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end = body
+                .find('}')
+                .ok_or_else(|| format!("Unterminated placeholder in '{}'", input))?;
+            out.push_str(&resolve_placeholder(&body[..end], vars)?);
+            rest = &body[end + 1..];
+        } else {
+            out.push('$');
+            rest = after;
+        }
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Resolve the values of `overlay` against `base` and merge them on top of
+/// it. Overlay values cannot reference each other, only `base`.
+pub fn resolve_env(
+    base: &HashMap<String, String>,
+    overlay: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    let mut env = base.clone();
+    for (key, value) in overlay {
+        let resolved = interpolate(value, base).map_err(|e| format!("env.{}: {}", key, e))?;
+        env.insert(key.clone(), resolved);
+    }
+    Ok(env)
+}
+
+/// Return a copy of `script` with placeholders resolved in its command, args,
+/// working directory and readiness checks.
+pub fn resolve_script(script: &ScriptDef, vars: &HashMap<String, String>) -> Result<ScriptDef, String> {
+    let resolve = |s: &str| interpolate(s, vars).map_err(|e| format!("Script '{}': {}", script.name, e));
+    let resolve_all = |list: &[String]| list.iter().map(|s| resolve(s)).collect::<Result<Vec<_>, _>>();
+
+    let mut resolved = script.clone();
+    resolved.command = resolve(&script.command)?;
+    resolved.args = resolve_all(&script.args)?;
+    resolved.command_windows = script.command_windows.as_deref().map(resolve).transpose()?;
+    resolved.args_windows = script.args_windows.as_deref().map(resolve_all).transpose()?;
+    resolved.cwd = script.cwd.as_deref().map(resolve).transpose()?;
+    resolved.wait_for_log = script.wait_for_log.as_deref().map(resolve).transpose()?;
+    resolved.wait_for_port = match script.wait_for_port {
+        Some(PortValue::Template(ref template)) => {
+            let value = resolve(template)?;
+            let port = value.trim().parse::<u16>().map_err(|_| {
+                format!(
+                    "Script '{}': wait_for_port '{}' is not a valid port",
+                    script.name, value
+                )
+            })?;
+            Some(PortValue::Number(port))
+        }
+        ref other => other.clone(),
+    };
+    Ok(resolved)
+}
+
+fn resolve_placeholder(body: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+    let (expr, default) = match body.split_once(":-") {
+        Some((expr, default)) => (expr, Some(default)),
+        None => (body, None),
+    };
+
+    let value = if let Some(name) = expr.strip_prefix("env:") {
+        validate_name(name, body)?;
+        match std::env::var(name) {
+            Ok(value) => Some(value),
+            Err(_) if default.is_some() => None,
+            Err(_) => return Err(format!("Environment variable '{}' is not set", name)),
+        }
+    } else {
+        let split = expr.find(['+', '-']).unwrap_or(expr.len());
+        let (name, offset) = expr.split_at(split);
+        validate_name(name, body)?;
+
+        match vars.get(name) {
+            Some(value) if offset.is_empty() => Some(value.clone()),
+            Some(value) => Some(apply_offset(name, value, offset, body)?),
+            None if default.is_some() => None,
+            None => return Err(format!("Unknown variable '{}'", name)),
+        }
+    };
+
+    Ok(value.unwrap_or_else(|| default.unwrap_or_default().to_string()))
+}
+
+fn apply_offset(name: &str, value: &str, offset: &str, body: &str) -> Result<String, String> {
+    let base: i64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Variable '{}' is not a number (value '{}')", name, value))?;
+    let delta: i64 = offset
+        .trim_start_matches('+')
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid offset in placeholder '${{{}}}'", body))?;
+    Ok((base + delta).to_string())
+}
+
+fn validate_name(name: &str, body: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid placeholder '${{{}}}'", body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> HashMap<String, String> {
+        HashMap::from([
+            ("HEROI_PORT".to_string(), "3000".to_string()),
+            ("HEROI_WORKSPACE_NAME".to_string(), "feat".to_string()),
+        ])
+    }
+
+    #[test]
+    fn resolves_plain_and_offset_variables() {
+        let vars = vars();
+        assert_eq!(interpolate("${HEROI_WORKSPACE_NAME}", &vars).unwrap(), "feat");
+        assert_eq!(interpolate("--port=${HEROI_PORT+1}", &vars).unwrap(), "--port=3001");
+        assert_eq!(interpolate("${HEROI_PORT-10}", &vars).unwrap(), "2990");
+        assert!(interpolate("${HEROI_WORKSPACE_NAME+1}", &vars).is_err());
+        assert!(interpolate("${HEROI_PORT+x}", &vars).is_err());
+    }
+
+    #[test]
+    fn resolves_process_environment() {
+        std::env::set_var("HEROI_INTERPOLATION_TEST", "from-env");
+        let vars = vars();
+        assert_eq!(interpolate("${env:HEROI_INTERPOLATION_TEST}", &vars).unwrap(), "from-env");
+        assert!(interpolate("${env:HEROI_INTERPOLATION_UNSET}", &vars).is_err());
+    }
+
+    #[test]
+    fn falls_back_to_default() {
+        let vars = vars();
+        assert_eq!(interpolate("${MISSING:-fallback}", &vars).unwrap(), "fallback");
+        assert_eq!(interpolate("${MISSING:-}", &vars).unwrap(), "");
+        assert_eq!(interpolate("${env:HEROI_INTERPOLATION_UNSET:-x}", &vars).unwrap(), "x");
+        assert_eq!(interpolate("${HEROI_PORT:-1}", &vars).unwrap(), "3000");
+    }
+
+    #[test]
+    fn escapes_and_leaves_shell_syntax_alone() {
+        let vars = vars();
+        assert_eq!(interpolate("$${HEROI_PORT}", &vars).unwrap(), "${HEROI_PORT}");
+        assert_eq!(interpolate("echo $HOME $$ $", &vars).unwrap(), "echo $HOME $$ $");
+    }
+
+    #[test]
+    fn rejects_unknown_and_malformed_placeholders() {
+        let vars = vars();
+        assert_eq!(
+            interpolate("${NOPE}", &vars).unwrap_err(),
+            "Unknown variable 'NOPE'"
+        );
+        assert!(interpolate("${HEROI_PORT", &vars).is_err());
+        assert!(interpolate("${1BAD}", &vars).is_err());
+        assert!(interpolate("${}", &vars).is_err());
+    }
+
+    #[test]
+    fn resolve_env_only_sees_base() {
+        let base = vars();
+        let overlay = HashMap::from([
+            ("URL".to_string(), "http://localhost:${HEROI_PORT}".to_string()),
+            ("OTHER".to_string(), "${URL}".to_string()),
+        ]);
+        let err = resolve_env(&base, &overlay).unwrap_err();
+        assert!(err.starts_with("env.OTHER:"), "{}", err);
+
+        let overlay = HashMap::from([("URL".to_string(), "http://localhost:${HEROI_PORT}".to_string())]);
+        assert_eq!(resolve_env(&base, &overlay).unwrap()["URL"], "http://localhost:3000");
+    }
+}
@@ -1,4 +1,6 @@
 mod commands;
+mod heroi_config;
+mod interpolation;
 mod models;
 mod state;
 
@@ -27,26 +29,54 @@ pub fn run() {
             if let Err(e) = commands::workspace_lifecycle::load_workspace_configs(app.handle(), state.inner()) {
                 eprintln!("Failed to load workspace configs: {}", e);
             }
+            // Checkpoints are reconciled against their workspaces, so load them last
+            if let Err(e) = commands::checkpoints::load_checkpoints(app.handle(), state.inner()) {
+                eprintln!("Failed to load checkpoints: {}", e);
+            }
+            commands::checkpoints::start_auto_checkpoints(app.handle().clone());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::repos::add_repo,
             commands::repos::remove_repo,
             commands::repos::list_repos,
+            commands::repos::set_repo_port_settings,
             commands::worktrees::list_worktrees,
             commands::worktrees::list_branches,
             commands::worktrees::get_default_branch,
             commands::worktrees::create_worktree,
             commands::worktrees::remove_worktree,
             commands::git::git_status,
+            commands::git_watcher::subscribe_git_status,
+            commands::git_watcher::unsubscribe_git_status,
             commands::git::git_diff,
             commands::git::git_diff_file,
             commands::git::git_stage_file,
             commands::git::git_unstage_file,
+            commands::git::git_stage_hunk,
+            commands::git::git_unstage_hunk,
+            commands::git::git_discard_hunk,
+            commands::git::git_stage_lines,
+            commands::git::git_unstage_lines,
+            commands::git::git_discard_lines,
+            commands::git::git_discard_file,
+            commands::git::git_discard_all,
+            commands::git::git_clean_untracked,
+            commands::conflicts::git_list_conflicts,
+            commands::conflicts::git_conflict_details,
+            commands::conflicts::git_resolve_conflict,
+            commands::conflicts::git_mark_resolved,
+            commands::sync::git_sync_with_base,
+            commands::sync::git_sync_continue,
+            commands::sync::git_sync_abort,
+            commands::sync::git_sync_in_progress,
+            commands::sync::git_fetch,
+            commands::sync::git_push,
+            commands::sync::git_pull,
+            commands::sync::git_ahead_behind,
             commands::git::git_stage_all,
             commands::git::git_unstage_all,
             commands::git::git_commit,
-            commands::git::git_push,
             commands::git::git_ahead_count,
             commands::git::git_diff_all,
             commands::git::git_diff_base,
@@ -54,6 +84,9 @@ pub fn run() {
             commands::ide::open_in_ide,
             commands::ide::open_file_in_ide,
             commands::agents::list_agents,
+            commands::agents::register_agent_session,
+            commands::agents::unregister_agent_session,
+            commands::agents::list_agent_sessions,
             commands::settings::save_settings,
             commands::settings::load_settings,
             commands::workspaces::save_workspaces,
@@ -65,14 +98,25 @@ pub fn run() {
             commands::workspace_lifecycle::list_workspace_configs,
             commands::workspace_lifecycle::archive_workspace,
             commands::workspace_lifecycle::restore_workspace,
+            commands::workspace_lifecycle::check_workspace_ports,
+            commands::workspace_lifecycle::reassign_workspace_ports,
+            commands::workspace_lifecycle::get_startup_port_conflicts,
             commands::workspace_lifecycle::save_workspace_notes,
             commands::workspace_lifecycle::load_workspace_notes,
             commands::scripts::load_heroi_config,
             commands::scripts::save_heroi_config,
+            commands::scripts::validate_heroi_config,
+            commands::scripts::get_heroi_config_schema,
             commands::scripts::run_script,
             commands::scripts::stop_process,
             commands::scripts::list_running_processes,
             commands::scripts::cleanup_processes,
+            commands::scripts::get_process_logs,
+            commands::scripts::stop_script_steps,
+            commands::scripts::get_script_log,
+            commands::scripts::preview_script,
+            commands::pipelines::run_pipeline,
+            commands::pipelines::plan_script_pipeline,
             commands::github::check_gh_available,
             commands::github::create_pr,
             commands::github::get_pr_status,
@@ -86,6 +130,10 @@ pub fn run() {
             commands::checkpoints::restore_checkpoint,
             commands::checkpoints::delete_checkpoint,
             commands::checkpoints::diff_checkpoint,
+            commands::checkpoints::restore_checkpoint_paths,
+            commands::checkpoints::set_auto_checkpoint_policy,
+            commands::checkpoints::set_checkpoint_retention,
+            commands::checkpoints::prune_checkpoints,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -8,3 +8,14 @@ pub struct AgentDef {
     pub args: Vec<String>,
     pub description: String,
 }
+
+/// A live agent terminal, registered by the frontend while its PTY runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSession {
+    /// Terminal tab id
+    pub id: String,
+    pub workspace_id: String,
+    pub agent_id: String,
+    pub worktree_path: String,
+    pub started_at: String,
+}
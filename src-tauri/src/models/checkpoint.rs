@@ -4,9 +4,93 @@ use serde::{Deserialize, Serialize};
 pub struct Checkpoint {
     pub id: String,
     pub workspace_id: String,
+    /// Worktree the checkpoint was taken in (empty for checkpoints persisted
+    /// before this was recorded)
+    #[serde(default)]
+    pub worktree_path: String,
     pub label: String,
+    /// Commit id of the snapshot
     pub git_ref: String,
+    /// Private ref keeping the snapshot alive (`None` for legacy checkpoints
+    /// committed onto the branch)
+    #[serde(default)]
+    pub ref_name: Option<String>,
     pub created_at: String,
     pub file_count: u32,
     pub agent_id: Option<String>,
+    /// Taken by the auto-checkpoint policy rather than by the user
+    #[serde(default)]
+    pub auto: bool,
+    /// Taken before a discard or restore so it can be undone. Only the
+    /// recency rules keep these; `keep_manual` doesn't cover them.
+    #[serde(default)]
+    pub system: bool,
+}
+
+/// How `restore_checkpoint` applies a checkpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum RestoreMode {
+    /// Write the checkpoint's files back into the working tree. HEAD, the
+    /// branch and the index are untouched and files created since are kept.
+    #[default]
+    WorkingTree,
+    /// Like `WorkingTree`, limited to the given paths.
+    Paths,
+    /// Move the branch back to the commit the checkpoint was taken on and
+    /// make the working tree match the checkpoint exactly, deleting untracked
+    /// files it doesn't contain (ignored files are kept).
+    Hard,
+}
+
+/// When a workspace is checkpointed automatically. Automatic checkpoints are
+/// skipped when the working tree matches HEAD or the latest checkpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoCheckpointPolicy {
+    /// Checkpoint every N minutes while the working tree has changes
+    pub interval_minutes: Option<u32>,
+    /// Checkpoint when an agent terminal exits or is closed
+    pub on_agent_exit: bool,
+    /// Checkpoint before running a script or pipeline
+    pub before_script: bool,
+    /// Automatic checkpoints kept per workspace on top of the retention
+    /// policy; older ones are deleted
+    pub max_checkpoints: u32,
+}
+
+impl Default for AutoCheckpointPolicy {
+    fn default() -> Self {
+        Self {
+            interval_minutes: None,
+            on_agent_exit: true,
+            before_script: true,
+            max_checkpoints: 20,
+        }
+    }
+}
+
+/// Which checkpoints survive pruning. A checkpoint is kept if any rule keeps
+/// it; everything else is deleted together with its ref.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckpointRetention {
+    /// Keep the N most recent checkpoints
+    pub keep_last: u32,
+    /// Keep the newest checkpoint of each hour within the last N hours
+    pub hourly_for_hours: u32,
+    /// Keep the newest checkpoint of each day within the last N days
+    pub daily_for_days: u32,
+    /// Never prune checkpoints created by hand
+    pub keep_manual: bool,
+}
+
+impl Default for CheckpointRetention {
+    fn default() -> Self {
+        Self {
+            keep_last: 10,
+            hourly_for_hours: 24,
+            daily_for_days: 7,
+            keep_manual: true,
+        }
+    }
 }
@@ -1,12 +1,19 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct GitFileStatus {
     pub path: String,
     pub staged: FileState,
     pub unstaged: FileState,
 }
 
+/// Payload of the `git-status-changed` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitStatusEvent {
+    pub worktree_path: String,
+    pub files: Vec<GitFileStatus>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum FileState {
     Unmodified,
@@ -19,8 +26,274 @@ pub enum FileState {
     Conflicted,
 }
 
+/// One file's changes, as produced by git2 (no text re-parsing needed).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDiff {
+    /// `None` for added files
+    pub old_path: Option<String>,
+    /// `None` for deleted files
+    pub new_path: Option<String>,
+    pub status: FileState,
+    pub binary: bool,
+    pub additions: u32,
+    pub deletions: u32,
+    pub hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Hunk {
+    /// The `@@ -a,b +c,d @@ ...` line
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub old_no: Option<u32>,
+    pub new_no: Option<u32>,
+    /// Line content without its trailing newline
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+    /// "\ No newline at end of file" after the preceding line
+    NoNewline,
+}
+
+/// Changes to one path in the working tree, split by where they live.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DiffOutput {
+pub struct WorkingTreeDiff {
     pub file_path: String,
-    pub diff_text: String,
+    /// HEAD to index
+    pub staged: Option<FileDiff>,
+    /// Index to working tree, including untracked files
+    pub unstaged: Option<FileDiff>,
+}
+
+/// One index stage of a conflicted file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictStage {
+    pub path: String,
+    /// Blob id
+    pub oid: String,
+    pub mode: u32,
+}
+
+/// A conflicted path with its three index stages. A missing stage means that
+/// side doesn't have the file (e.g. deleted on one side, added on both).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictEntry {
+    pub path: String,
+    /// Stage 1, the merge base
+    pub ancestor: Option<ConflictStage>,
+    /// Stage 2, the branch being merged into
+    pub ours: Option<ConflictStage>,
+    /// Stage 3, the branch being merged in
+    pub theirs: Option<ConflictStage>,
+}
+
+/// A stretch of a conflicted working tree file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind")]
+pub enum ConflictRegion {
+    /// Text both sides agree on
+    Common { text: String },
+    /// A `<<<<<<<` ... `>>>>>>>` block; `base` is only present for
+    /// diff3-style markers
+    Conflict {
+        ours_label: String,
+        theirs_label: String,
+        ours: String,
+        base: Option<String>,
+        theirs: String,
+    },
+}
+
+/// Everything needed to resolve one conflicted file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictFile {
+    pub entry: ConflictEntry,
+    /// Set when any stage is binary; only whole-file resolutions apply
+    pub binary: bool,
+    /// Full text of each stage (`None` if that side lacks the file or it is binary)
+    pub ancestor_text: Option<String>,
+    pub ours_text: Option<String>,
+    pub theirs_text: Option<String>,
+    /// The working tree file split at its conflict markers
+    pub regions: Vec<ConflictRegion>,
+}
+
+/// How to resolve a conflict block, or a whole file without markers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum ConflictResolution {
+    Ours,
+    Theirs,
+    /// Ours followed by theirs
+    Both,
+    Custom {
+        text: String,
+    },
+}
+
+/// How `git_sync_with_base` brings in the base branch.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum SyncStrategy {
+    /// Replay the branch's commits on top of the base
+    #[default]
+    Rebase,
+    /// Merge the base into the branch
+    Merge,
+}
+
+impl SyncStrategy {
+    /// The git subcommand for this strategy.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncStrategy::Rebase => "rebase",
+            SyncStrategy::Merge => "merge",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum SyncStatus {
+    /// The branch already contains the base
+    UpToDate,
+    Updated,
+    /// Stopped on conflicts; resolve them and continue, or abort
+    Conflicted,
+}
+
+/// Outcome of syncing with the base branch or continuing a sync.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncResult {
+    pub status: SyncStatus,
+    /// The rebase or merge still in progress, if it stopped
+    pub in_progress: Option<SyncStrategy>,
+    pub conflicts: Vec<ConflictEntry>,
+    /// What was synced onto, e.g. `origin/main` (`None` when continuing)
+    pub base_ref: Option<String>,
+    /// Set when fetching the base failed and the last known base was used
+    pub fetch_error: Option<String>,
+    /// HEAD commit afterwards
+    pub head: String,
+}
+
+/// How the current branch relates to its upstream and to a base branch.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AheadBehind {
+    /// The configured upstream, e.g. `origin/feature` (`None` if the branch
+    /// doesn't track one; the counts are then 0)
+    pub upstream: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    /// The base branch compared against, if one was given
+    pub base: Option<String>,
+    /// Commits on the branch that the base lacks
+    pub base_ahead: usize,
+    /// Commits on the base that the branch lacks
+    pub base_behind: usize,
+}
+
+/// Options for `git_push`; the defaults push the current branch to its
+/// upstream's remote.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PushOptions {
+    /// Remote to push to instead of the upstream's (or `origin`)
+    pub remote: Option<String>,
+    /// Make the pushed branch the upstream; implied when there is none yet
+    pub set_upstream: bool,
+    /// Overwrite the remote branch, unless it moved since it was last fetched
+    pub force_with_lease: bool,
+    /// Also push annotated tags reachable from the pushed commits
+    pub tags: bool,
+}
+
+/// The porcelain flag `git push` reports for a ref.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum PushRefStatus {
+    FastForward,
+    Forced,
+    Deleted,
+    New,
+    UpToDate,
+    Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushRefUpdate {
+    pub local_ref: String,
+    pub remote_ref: String,
+    pub status: PushRefStatus,
+    /// e.g. `a1b2c3..d4e5f6` or `[rejected]`
+    pub summary: String,
+    /// Why a ref was rejected, e.g. `fetch first` or `pre-receive hook declined`
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushResult {
+    pub remote: String,
+    /// Whether every ref was accepted
+    pub success: bool,
+    pub refs: Vec<PushRefUpdate>,
+    /// Non-progress output: hook output, `remote:` messages, warnings
+    pub messages: Vec<String>,
+    /// Set when this push made the branch track the remote
+    pub upstream_set: bool,
+}
+
+/// Payload of the `git-push-progress` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushProgress {
+    pub worktree_path: String,
+    /// e.g. `Writing objects`
+    pub phase: String,
+    pub percent: u8,
+}
+
+/// A commit author or co-author.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitIdentity {
+    pub name: String,
+    pub email: String,
+}
+
+/// Options for `git_commit`. Hooks run and signing follows the repo's
+/// config unless overridden.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct CommitOptions {
+    /// Replace HEAD instead of adding a commit; an empty message keeps HEAD's
+    pub amend: bool,
+    /// Add a `Signed-off-by` trailer for the committer
+    pub signoff: bool,
+    /// Author other than the configured user, e.g. the agent that made the changes
+    pub author: Option<CommitIdentity>,
+    /// Added as `Co-authored-by` trailers
+    pub co_authors: Vec<CommitIdentity>,
+    /// Skip the pre-commit and commit-msg hooks
+    pub no_verify: bool,
+    /// Force signing on or off; `None` follows `commit.gpgsign` and `gpg.format`
+    pub sign: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitResult {
+    pub oid: String,
+    /// Whether the commit carries a GPG or SSH signature
+    pub signed: bool,
+    /// What the hooks (and git) printed; hook stdout goes to stderr too
+    pub hook_output: String,
 }
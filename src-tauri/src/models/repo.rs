@@ -4,6 +4,12 @@ use serde::{Deserialize, Serialize};
 pub struct RepoEntry {
     pub path: String,
     pub name: String,
+    /// First port considered when allocating workspace port blocks (optional)
+    #[serde(default)]
+    pub port_start: Option<u16>,
+    /// Ports reserved per workspace (optional)
+    #[serde(default)]
+    pub port_range_size: Option<u16>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,10 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// The heroi.json configuration file format.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct HeroiConfig {
+    /// JSON Schema reference for editors (ignored by Heroi)
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Format version, used to migrate older files (missing means 1)
+    #[serde(default = "default_config_version")]
+    pub version: u32,
     #[serde(default)]
     pub setup: Vec<ScriptDef>,
     #[serde(default)]
@@ -13,10 +20,32 @@ pub struct HeroiConfig {
     pub archive: Vec<ScriptDef>,
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Named ports as offsets into the workspace's port block, e.g.
+    /// `{ "web": 0, "api": 1 }`, exported as `HEROI_PORT_WEB`, `HEROI_PORT_API`.
+    #[serde(default)]
+    pub ports: HashMap<String, u16>,
+    /// What archive/delete should do when an archive step fails.
+    #[serde(default)]
+    pub archive_failure: ArchiveFailurePolicy,
+}
+
+fn default_config_version() -> u32 {
+    1
+}
+
+/// How a failing `archive` step affects `archive_workspace`/`delete_workspace`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFailurePolicy {
+    /// Leave the workspace untouched and return an error.
+    #[default]
+    Abort,
+    /// Carry on and report the failure in the returned log.
+    Warn,
 }
 
 /// A script definition with optional Windows overrides.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScriptDef {
     pub name: String,
     pub command: String,
@@ -28,6 +57,87 @@ pub struct ScriptDef {
     pub args_windows: Option<Vec<String>>,
     /// Working directory relative to worktree root (optional)
     pub cwd: Option<String>,
+    /// Scripts or `parallel` group names that must be ready before this
+    /// script is started by a pipeline.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Parallel group name. Members of a group start together and can be
+    /// depended on as a unit by naming the group in `depends_on`.
+    pub parallel: Option<String>,
+    /// Ready once this TCP port on 127.0.0.1 accepts connections. May be a
+    /// placeholder such as `"${HEROI_PORT+1}"`.
+    pub wait_for_port: Option<PortValue>,
+    /// Ready once a line of the script's output contains this text.
+    pub wait_for_log: Option<String>,
+    /// How long a pipeline waits for readiness before giving up (optional)
+    pub ready_timeout_secs: Option<u64>,
+    /// How long a setup or archive step may run before it is killed
+    /// (optional, default 600)
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+    /// Restart attempts for `restart: on-failure` (optional, default 3)
+    pub max_restarts: Option<u32>,
+}
+
+/// A port given either literally or as a `${...}` placeholder string.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum PortValue {
+    Number(u16),
+    Template(String),
+}
+
+impl PortValue {
+    /// The port number, if this value has already been resolved.
+    pub fn as_number(&self) -> Option<u16> {
+        match self {
+            PortValue::Number(port) => Some(*port),
+            PortValue::Template(_) => None,
+        }
+    }
+}
+
+/// Whether the supervisor respawns a script after it exits.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+}
+
+/// How serious a heroi.json diagnostic is.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A problem found while loading or validating heroi.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigDiagnostic {
+    pub severity: DiagnosticSeverity,
+    /// `heroi.json` or `heroi.local.json`
+    pub file: String,
+    /// Location in the config, e.g. `run[0].args` (empty for the whole file)
+    pub path: String,
+    /// 1-based line, when the location could be found in the file
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+/// A script with its placeholders resolved, as returned by `preview_script`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedScript {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    /// Absolute working directory
+    pub cwd: String,
+    pub command_line: String,
+    pub env: HashMap<String, String>,
 }
 
 /// Tracks a running process spawned by the scripts system.
@@ -38,6 +148,18 @@ pub struct RunningProcess {
     pub script_name: String,
     pub pid: u32,
     pub status: ProcessStatus,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub duration_ms: Option<u64>,
+    /// Exit code, if the process exited normally.
+    pub exit_code: Option<i32>,
+    /// Terminating signal, if the process was killed by one (Unix only).
+    pub signal: Option<i32>,
+    /// Set when the exit was requested through `stop_process`, so a
+    /// non-zero exit is reported as `Exited` rather than `Failed`.
+    pub stopped_by_user: bool,
+    /// Number of times the process was respawned by `restart: on-failure`.
+    pub restarts: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -46,3 +168,162 @@ pub enum ProcessStatus {
     Exited,
     Failed,
 }
+
+/// Which heroi.json section a batch of sequential steps came from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ScriptPhase {
+    Setup,
+    Archive,
+}
+
+impl ScriptPhase {
+    pub fn as_key(&self) -> &str {
+        match self {
+            ScriptPhase::Setup => "setup",
+            ScriptPhase::Archive => "archive",
+        }
+    }
+}
+
+/// Outcome of one step of a setup/archive run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptStepResult {
+    pub name: String,
+    pub command_line: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    /// Tail of the step's stdout followed by its stderr.
+    pub output: String,
+    /// Set when the step could not be started, timed out or was stopped.
+    pub error: Option<String>,
+}
+
+/// Per-step result log of a setup/archive run. Steps after the first
+/// failure are not executed and do not appear in `steps`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptRunLog {
+    pub workspace_id: String,
+    pub phase: ScriptPhase,
+    pub started_at: String,
+    pub finished_at: String,
+    pub success: bool,
+    pub steps: Vec<ScriptStepResult>,
+}
+
+/// Payload of the `script-step-progress` event. `result` is `None` when the
+/// step is starting and set once it has finished.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptStepEvent {
+    pub workspace_id: String,
+    pub phase: ScriptPhase,
+    pub index: usize,
+    pub total: usize,
+    pub name: String,
+    pub result: Option<ScriptStepResult>,
+}
+
+/// A pipeline started by `run_pipeline`. `stages` lists the scripts grouped
+/// by dependency depth; scripts in the same stage may run concurrently.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineRun {
+    pub id: String,
+    pub workspace_id: String,
+    pub stages: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum PipelineStepState {
+    Started,
+    Ready,
+    Failed,
+    /// Not started because a dependency failed.
+    Skipped,
+}
+
+/// Payload of the `pipeline-progress` event. `script` is `None` for the
+/// final event of a pipeline, whose state is `Ready` if every script became
+/// ready and `Failed` otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineEvent {
+    pub pipeline_id: String,
+    pub workspace_id: String,
+    pub script: Option<String>,
+    pub state: PipelineStepState,
+    pub process_id: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Which output stream a captured log line came from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// A single line of captured process output. `offset` increases monotonically
+/// per process and never resets, even after older lines are evicted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessLogLine {
+    pub offset: u64,
+    pub stream: LogStream,
+    pub text: String,
+}
+
+/// Payload of the `process-output` event, emitted once per captured line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessOutputEvent {
+    pub process_id: String,
+    pub line: ProcessLogLine,
+}
+
+/// A slice of a process log returned by `get_process_logs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessLogChunk {
+    pub lines: Vec<ProcessLogLine>,
+    /// Offset to pass as `since_offset` on the next call.
+    pub next_offset: u64,
+    /// True if lines after the requested offset were already evicted.
+    pub truncated: bool,
+}
+
+/// Bounded ring buffer of output lines for one process.
+#[derive(Debug, Default)]
+pub struct ProcessLog {
+    lines: VecDeque<ProcessLogLine>,
+    next_offset: u64,
+}
+
+impl ProcessLog {
+    pub const MAX_LINES: usize = 5000;
+    /// Longer lines are split into several entries by the log reader.
+    pub const MAX_LINE_BYTES: usize = 8 * 1024;
+
+    pub fn push(&mut self, stream: LogStream, text: String) -> ProcessLogLine {
+        let line = ProcessLogLine {
+            offset: self.next_offset,
+            stream,
+            text,
+        };
+        self.next_offset += 1;
+        if self.lines.len() >= Self::MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.clone());
+        line
+    }
+
+    pub fn since(&self, offset: u64) -> ProcessLogChunk {
+        let first = self.lines.front().map(|l| l.offset).unwrap_or(self.next_offset);
+        ProcessLogChunk {
+            lines: self
+                .lines
+                .iter()
+                .filter(|l| l.offset >= offset)
+                .cloned()
+                .collect(),
+            next_offset: self.next_offset,
+            truncated: offset < first,
+        }
+    }
+}
@@ -2,18 +2,15 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+use crate::models::checkpoint::{AutoCheckpointPolicy, CheckpointRetention};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub enum WorkspaceStatus {
+    #[default]
     Active,
     Archived,
 }
 
-impl Default for WorkspaceStatus {
-    fn default() -> Self {
-        Self::Active
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceConfig {
     pub id: String,
@@ -24,6 +21,35 @@ pub struct WorkspaceConfig {
     pub is_main_worktree: bool,
     pub env_vars: HashMap<String, String>,
     pub port_base: u16,
+    /// Number of consecutive ports reserved from `port_base`
+    #[serde(default = "default_port_range_size")]
+    pub port_range_size: u16,
+    /// heroi.json named ports mapped to their ports in this workspace's block
+    #[serde(default)]
+    pub ports: HashMap<String, u16>,
+    #[serde(default)]
+    pub auto_checkpoints: AutoCheckpointPolicy,
+    #[serde(default)]
+    pub checkpoint_retention: CheckpointRetention,
     pub status: WorkspaceStatus,
     pub created_at: String,
 }
+
+/// Workspaces persisted before the block size was configurable used 10 ports.
+fn default_port_range_size() -> u16 {
+    10
+}
+
+/// Health of a workspace's port block, as reported by `check_workspace_ports`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspacePortStatus {
+    pub workspace_id: String,
+    pub port_base: u16,
+    pub port_range_size: u16,
+    /// Ports in the block that something is already listening on. These may
+    /// belong to the workspace's own scripts if any are running.
+    pub busy_ports: Vec<u16>,
+    /// Other workspaces whose blocks overlap this one
+    pub overlapping_workspaces: Vec<String>,
+    pub has_running_processes: bool,
+}
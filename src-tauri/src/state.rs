@@ -1,10 +1,13 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 
+use crate::commands::git_watcher::GitWatcher;
+use crate::commands::scripts::RunningStep;
+use crate::models::agent::AgentSession;
 use crate::models::checkpoint::Checkpoint;
 use crate::models::repo::RepoEntry;
-use crate::models::scripts::RunningProcess;
-use crate::models::workspace::WorkspaceConfig;
+use crate::models::scripts::{ProcessLog, RunningProcess};
+use crate::models::workspace::{WorkspaceConfig, WorkspacePortStatus};
 
 #[derive(Default)]
 pub struct AppData {
@@ -12,7 +15,15 @@ pub struct AppData {
     pub workspaces: Vec<WorkspaceConfig>,
     pub allocated_ports: HashSet<u16>,
     pub running_processes: Vec<RunningProcess>,
+    pub process_logs: HashMap<String, ProcessLog>,
+    /// Setup or archive step currently running, by workspace id
+    pub running_steps: HashMap<String, RunningStep>,
     pub checkpoints: Vec<Checkpoint>,
+    pub agent_sessions: Vec<AgentSession>,
+    /// Status watchers by worktree path
+    pub git_watchers: HashMap<String, GitWatcher>,
+    /// Port conflicts found when workspaces were loaded at startup
+    pub startup_port_conflicts: Vec<WorkspacePortStatus>,
 }
 
 pub struct AppState(pub Mutex<AppData>);
@@ -1,12 +1,57 @@
 import { useState } from "react";
-import { RotateCcw, Trash2, Plus, Clock, FileText } from "lucide-react";
+import {
+  RotateCcw,
+  Trash2,
+  Plus,
+  Clock,
+  FileText,
+  Settings2,
+  GitCompare,
+  Undo2,
+} from "lucide-react";
 import { Button } from "../ui/Button";
 import { useCheckpoints } from "../../hooks/useCheckpoints";
+import type { FileDiff } from "../../types";
+import { diffPath } from "../../lib/diff";
 
 export function CheckpointTimeline() {
-  const { checkpoints, loading, create, restore, remove } = useCheckpoints();
+  const {
+    checkpoints,
+    policy,
+    retention,
+    loading,
+    create,
+    restore,
+    diff,
+    remove,
+    prune,
+    updatePolicy,
+    updateRetention,
+  } = useCheckpoints();
   const [newLabel, setNewLabel] = useState("");
   const [showCreate, setShowCreate] = useState(false);
+  const [showPolicy, setShowPolicy] = useState(false);
+  const [expanded, setExpanded] = useState<string | null>(null);
+  const [changes, setChanges] = useState<FileDiff[]>([]);
+
+  const toggleChanges = async (gitRef: string) => {
+    if (expanded === gitRef) {
+      setExpanded(null);
+      return;
+    }
+    setExpanded(gitRef);
+    setChanges([]);
+    try {
+      setChanges(await diff(gitRef));
+    } catch {
+      setChanges([]);
+    }
+  };
+
+  const restoreFile = async (gitRef: string, path: string) => {
+    await restore(gitRef, "Paths", [path]);
+    setChanges(await diff(gitRef));
+  };
 
   const handleCreate = async () => {
     if (!newLabel.trim()) return;
@@ -21,16 +66,109 @@ export function CheckpointTimeline() {
         <span className="text-xs font-semibold text-zinc-300">
           Checkpoints
         </span>
-        <Button
-          variant="ghost"
-          size="icon"
-          title="Create checkpoint"
-          onClick={() => setShowCreate(!showCreate)}
-        >
-          <Plus size={12} />
-        </Button>
+        <div className="flex items-center gap-0.5">
+          <Button
+            variant="ghost"
+            size="icon"
+            title="Automatic checkpoints"
+            disabled={!policy}
+            onClick={() => setShowPolicy(!showPolicy)}
+          >
+            <Settings2 size={12} />
+          </Button>
+          <Button
+            variant="ghost"
+            size="icon"
+            title="Create checkpoint"
+            onClick={() => setShowCreate(!showCreate)}
+          >
+            <Plus size={12} />
+          </Button>
+        </div>
       </div>
 
+      {showPolicy && policy && (
+        <div className="space-y-1.5 p-2 rounded bg-zinc-800/50 border border-zinc-700 text-[11px] text-zinc-400">
+          <label className="flex items-center gap-2">
+            <input
+              type="checkbox"
+              checked={policy.on_agent_exit}
+              onChange={(e) => updatePolicy({ ...policy, on_agent_exit: e.target.checked })}
+            />
+            When an agent exits
+          </label>
+          <label className="flex items-center gap-2">
+            <input
+              type="checkbox"
+              checked={policy.before_script}
+              onChange={(e) => updatePolicy({ ...policy, before_script: e.target.checked })}
+            />
+            Before running scripts
+          </label>
+          <label className="flex items-center gap-2">
+            Every
+            <NumberInput
+              value={policy.interval_minutes ?? 0}
+              onChange={(minutes) =>
+                updatePolicy({ ...policy, interval_minutes: minutes > 0 ? minutes : null })
+              }
+            />
+            minutes while changed (0 = off)
+          </label>
+          <label className="flex items-center gap-2">
+            Keep
+            <NumberInput
+              value={policy.max_checkpoints}
+              onChange={(max_checkpoints) => updatePolicy({ ...policy, max_checkpoints })}
+            />
+            automatic checkpoints
+          </label>
+          {retention && (
+            <>
+              <div className="pt-1 text-zinc-300">Retention</div>
+              <label className="flex items-center gap-2">
+                Keep last
+                <NumberInput
+                  value={retention.keep_last}
+                  onChange={(keep_last) => updateRetention({ ...retention, keep_last })}
+                />
+              </label>
+              <label className="flex items-center gap-2">
+                One per hour for
+                <NumberInput
+                  value={retention.hourly_for_hours}
+                  onChange={(hourly_for_hours) =>
+                    updateRetention({ ...retention, hourly_for_hours })
+                  }
+                />
+                hours
+              </label>
+              <label className="flex items-center gap-2">
+                One per day for
+                <NumberInput
+                  value={retention.daily_for_days}
+                  onChange={(daily_for_days) => updateRetention({ ...retention, daily_for_days })}
+                />
+                days
+              </label>
+              <label className="flex items-center gap-2">
+                <input
+                  type="checkbox"
+                  checked={retention.keep_manual}
+                  onChange={(e) =>
+                    updateRetention({ ...retention, keep_manual: e.target.checked })
+                  }
+                />
+                Always keep manual checkpoints
+              </label>
+              <Button variant="ghost" size="sm" disabled={loading} onClick={() => prune()}>
+                Prune now
+              </Button>
+            </>
+          )}
+        </div>
+      )}
+
       {showCreate && (
         <div className="flex gap-2">
           <input
@@ -62,7 +200,11 @@ export function CheckpointTimeline() {
             <div key={cp.id} className="flex items-start gap-2 group">
               {/* Timeline line */}
               <div className="flex flex-col items-center pt-1">
-                <div className="w-2 h-2 rounded-full bg-indigo-500 shrink-0" />
+                <div
+                  className={`w-2 h-2 rounded-full shrink-0 ${
+                    cp.auto || cp.system ? "bg-zinc-500" : "bg-indigo-500"
+                  }`}
+                />
                 {idx < checkpoints.length - 1 && (
                   <div className="w-px flex-1 bg-zinc-700 min-h-[24px]" />
                 )}
@@ -75,6 +217,13 @@ export function CheckpointTimeline() {
                     {cp.label}
                   </span>
                   <div className="flex items-center gap-0.5 opacity-0 group-hover:opacity-100 transition-opacity">
+                    <button
+                      onClick={() => toggleChanges(cp.git_ref)}
+                      className="p-1 text-zinc-500 hover:text-zinc-300 rounded transition-colors"
+                      title="Changes since this checkpoint"
+                    >
+                      <GitCompare size={10} />
+                    </button>
                     <button
                       onClick={() => restore(cp.git_ref)}
                       disabled={loading}
@@ -103,7 +252,45 @@ export function CheckpointTimeline() {
                     {cp.file_count} files
                   </span>
                   <span className="font-mono">{cp.git_ref.slice(0, 7)}</span>
+                  {cp.auto && <span>auto</span>}
+                  {cp.system && <span>safety</span>}
                 </div>
+                {expanded === cp.git_ref && (
+                  <div className="mt-1 space-y-0.5">
+                    {changes.length === 0 ? (
+                      <p className="text-[10px] text-zinc-600">No changes since this checkpoint</p>
+                    ) : (
+                      changes.map((file) => (
+                        <div
+                          key={diffPath(file)}
+                          className="flex items-center gap-1.5 text-[10px] text-zinc-400 group/file"
+                        >
+                          <span className="font-mono w-3 text-zinc-500">{file.status[0]}</span>
+                          <span
+                            className="flex-1 truncate"
+                            title={
+                              file.status === "Renamed"
+                                ? `${file.old_path} → ${file.new_path}`
+                                : diffPath(file)
+                            }
+                          >
+                            {diffPath(file)}
+                          </span>
+                          <span className="text-green-500">+{file.additions}</span>
+                          <span className="text-red-500">-{file.deletions}</span>
+                          <button
+                            onClick={() => restoreFile(cp.git_ref, file.old_path ?? diffPath(file))}
+                            disabled={loading}
+                            className="p-0.5 text-zinc-600 hover:text-amber-400 rounded opacity-0 group-hover/file:opacity-100"
+                            title="Restore this file from the checkpoint"
+                          >
+                            <Undo2 size={9} />
+                          </button>
+                        </div>
+                      ))
+                    )}
+                  </div>
+                )}
               </div>
             </div>
           ))}
@@ -112,3 +299,21 @@ export function CheckpointTimeline() {
     </div>
   );
 }
+
+function NumberInput({
+  value,
+  onChange,
+}: {
+  value: number;
+  onChange: (value: number) => void;
+}) {
+  return (
+    <input
+      type="number"
+      min={0}
+      value={value}
+      onChange={(e) => onChange(Math.max(0, parseInt(e.target.value, 10) || 0))}
+      className="w-12 h-5 px-1 rounded bg-zinc-800 border border-zinc-700 text-zinc-100"
+    />
+  );
+}
@@ -1,18 +1,23 @@
 import { FilePlus, FileMinus, FileEdit } from "lucide-react";
-import type { DiffOutput } from "../../types";
+import type { WorkingTreeDiff } from "../../types";
+import { workingTreeState } from "../../lib/diff";
 
 interface DiffFileTreeProps {
-  diffs: DiffOutput[];
+  diffs: WorkingTreeDiff[];
   selectedFile: string | null;
   onSelectFile: (filePath: string) => void;
 }
 
-function getFileStatus(diffText: string): "added" | "deleted" | "modified" {
-  const hasAdded = diffText.includes("\n+");
-  const hasRemoved = diffText.includes("\n-");
-  if (hasAdded && !hasRemoved) return "added";
-  if (!hasAdded && hasRemoved) return "deleted";
-  return "modified";
+function getFileStatus(diff: WorkingTreeDiff): "added" | "deleted" | "modified" {
+  switch (workingTreeState(diff)) {
+    case "Added":
+    case "Untracked":
+      return "added";
+    case "Deleted":
+      return "deleted";
+    default:
+      return "modified";
+  }
 }
 
 function getStatusIcon(status: "added" | "deleted" | "modified") {
@@ -42,7 +47,7 @@ export function DiffFileTree({
   onSelectFile,
 }: DiffFileTreeProps) {
   // Group files by directory
-  const grouped = new Map<string, DiffOutput[]>();
+  const grouped = new Map<string, WorkingTreeDiff[]>();
   for (const diff of diffs) {
     const dir = getDirectory(diff.file_path);
     const list = grouped.get(dir) ?? [];
@@ -66,7 +71,7 @@ export function DiffFileTree({
               </div>
             )}
             {grouped.get(dir)!.map((diff) => {
-              const status = getFileStatus(diff.diff_text);
+              const status = getFileStatus(diff);
               const isSelected = diff.file_path === selectedFile;
               return (
                 <button
@@ -2,13 +2,13 @@ import { useState, useEffect, useCallback } from "react";
 import { RefreshCw } from "lucide-react";
 import { useAppStore } from "../../stores/useAppStore";
 import { gitDiffAll } from "../../lib/tauri";
-import type { DiffOutput } from "../../types";
+import type { WorkingTreeDiff } from "../../types";
 import { DiffFileTree } from "./DiffFileTree";
-import { UnifiedDiff } from "./UnifiedDiff";
+import { WorkingTreeDiffView } from "./UnifiedDiff";
 
 export function DiffViewer() {
   const selectedWorktree = useAppStore((s) => s.selectedWorktree);
-  const [diffs, setDiffs] = useState<DiffOutput[]>([]);
+  const [diffs, setDiffs] = useState<WorkingTreeDiff[]>([]);
   const [selectedFile, setSelectedFile] = useState<string | null>(null);
   const [loading, setLoading] = useState(false);
 
@@ -74,9 +74,10 @@ export function DiffViewer() {
               {selectedDiff.file_path}
             </div>
             <div className="flex-1 min-h-0 overflow-auto">
-              <UnifiedDiff
-                diffText={selectedDiff.diff_text}
-                filePath={selectedDiff.file_path}
+              <WorkingTreeDiffView
+                diff={selectedDiff}
+                worktreePath={selectedWorktree?.path}
+                onChange={loadDiffs}
               />
             </div>
           </div>
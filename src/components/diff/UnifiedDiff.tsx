@@ -1,59 +1,65 @@
-interface UnifiedDiffProps {
-  diffText: string;
-  filePath: string;
+import { useEffect, useState } from "react";
+import type { DiffLine, FileDiff, Hunk, WorkingTreeDiff } from "../../types";
+import {
+  gitDiscardHunk,
+  gitDiscardLines,
+  gitStageHunk,
+  gitStageLines,
+  gitUnstageHunk,
+  gitUnstageLines,
+} from "../../lib/tauri";
+
+/** A per-hunk button; `lines` holds the selected line indices, if any. */
+export interface HunkAction {
+  label: string;
+  run: (hunk: Hunk, lines: number[]) => Promise<void>;
 }
 
-interface DiffLine {
-  type: "added" | "removed" | "context" | "header";
-  content: string;
-  oldLineNo: number | null;
-  newLineNo: number | null;
+interface UnifiedDiffProps {
+  diff: FileDiff | null;
+  filePath: string;
+  hunkActions?: HunkAction[];
 }
 
-function parseDiffLines(diffText: string): DiffLine[] {
-  const rawLines = diffText.split("\n");
-  const lines: DiffLine[] = [];
-  let oldLine = 0;
-  let newLine = 0;
+const lineColors: Record<DiffLine["kind"], string> = {
+  Added: "bg-green-950/40 text-green-300",
+  Removed: "bg-red-950/40 text-red-300",
+  Context: "text-zinc-400",
+  NoNewline: "text-zinc-600 italic",
+};
 
-  for (const raw of rawLines) {
-    if (raw.startsWith("@@")) {
-      // Parse hunk header: @@ -start,count +start,count @@
-      const match = raw.match(/@@ -(\d+)(?:,\d+)? \+(\d+)(?:,\d+)? @@/);
-      if (match) {
-        oldLine = parseInt(match[1], 10);
-        newLine = parseInt(match[2], 10);
-      }
-      lines.push({ type: "header", content: raw, oldLineNo: null, newLineNo: null });
-    } else if (raw.startsWith("+")) {
-      lines.push({ type: "added", content: raw.slice(1), oldLineNo: null, newLineNo: newLine });
-      newLine++;
-    } else if (raw.startsWith("-")) {
-      lines.push({ type: "removed", content: raw.slice(1), oldLineNo: oldLine, newLineNo: null });
-      oldLine++;
-    } else if (raw.startsWith(" ")) {
-      lines.push({ type: "context", content: raw.slice(1), oldLineNo: oldLine, newLineNo: newLine });
-      oldLine++;
-      newLine++;
-    } else if (raw.startsWith("diff ") || raw.startsWith("index ") || raw.startsWith("---") || raw.startsWith("+++")) {
-      lines.push({ type: "header", content: raw, oldLineNo: null, newLineNo: null });
-    }
-  }
+const lineMarkers: Record<DiffLine["kind"], string> = {
+  Added: "+",
+  Removed: "-",
+  Context: " ",
+  NoNewline: "",
+};
 
-  return lines;
-}
+export function UnifiedDiff({ diff, filePath, hunkActions = [] }: UnifiedDiffProps) {
+  // Selected changed lines, per hunk index
+  const [selected, setSelected] = useState<Map<number, Set<number>>>(new Map());
+  useEffect(() => setSelected(new Map()), [diff]);
 
-const lineColors: Record<DiffLine["type"], string> = {
-  added: "bg-green-950/40 text-green-300",
-  removed: "bg-red-950/40 text-red-300",
-  context: "text-zinc-400",
-  header: "bg-zinc-800/50 text-indigo-400",
-};
+  const toggleLine = (hunkIdx: number, lineIdx: number) => {
+    setSelected((prev) => {
+      const next = new Map(prev);
+      const lines = new Set(next.get(hunkIdx));
+      if (lines.has(lineIdx)) lines.delete(lineIdx);
+      else lines.add(lineIdx);
+      next.set(hunkIdx, lines);
+      return next;
+    });
+  };
 
-export function UnifiedDiff({ diffText, filePath }: UnifiedDiffProps) {
-  const lines = parseDiffLines(diffText);
+  if (diff?.binary) {
+    return (
+      <div className="flex items-center justify-center h-full text-zinc-600 text-sm">
+        Binary file {filePath} changed
+      </div>
+    );
+  }
 
-  if (lines.length === 0) {
+  if (!diff || diff.hunks.length === 0) {
     return (
       <div className="flex items-center justify-center h-full text-zinc-600 text-sm">
         No diff available for {filePath}
@@ -63,18 +69,124 @@ export function UnifiedDiff({ diffText, filePath }: UnifiedDiffProps) {
 
   return (
     <div className="overflow-auto h-full font-mono text-xs leading-5">
-      {lines.map((line, i) => (
-        <div key={i} className={`flex ${lineColors[line.type]}`}>
-          <span className="w-12 text-right pr-2 text-zinc-600 select-none shrink-0 border-r border-zinc-800">
-            {line.oldLineNo ?? ""}
-          </span>
-          <span className="w-12 text-right pr-2 text-zinc-600 select-none shrink-0 border-r border-zinc-800">
-            {line.newLineNo ?? ""}
-          </span>
-          <span className="w-5 text-center select-none shrink-0 text-zinc-600">
-            {line.type === "added" ? "+" : line.type === "removed" ? "-" : line.type === "header" ? "" : " "}
-          </span>
-          <span className="flex-1 whitespace-pre pl-1">{line.content}</span>
+      {diff.hunks.map((hunk, h) => (
+        <div key={h}>
+          <div className="flex items-center bg-zinc-800/50 text-indigo-400">
+            <span className="w-24 shrink-0 border-r border-zinc-800" />
+            <span className="flex-1 whitespace-pre pl-6">{hunk.header}</span>
+            {hunkActions.map((action) => {
+              const lines = [...(selected.get(h) ?? [])].sort((a, b) => a - b);
+              return (
+                <button
+                  key={action.label}
+                  onClick={() => action.run(hunk, lines)}
+                  className="px-2 text-[10px] text-zinc-400 hover:text-zinc-100 font-sans"
+                >
+                  {lines.length > 0 ? `${action.label} lines` : `${action.label} hunk`}
+                </button>
+              );
+            })}
+          </div>
+          {hunk.lines.map((line, i) => (
+            <div
+              key={i}
+              onClick={
+                hunkActions.length > 0 && (line.kind === "Added" || line.kind === "Removed")
+                  ? () => toggleLine(h, i)
+                  : undefined
+              }
+              className={`flex ${lineColors[line.kind]} ${
+                selected.get(h)?.has(i) ? "outline outline-1 -outline-offset-1 outline-indigo-500" : ""
+              } ${hunkActions.length > 0 ? "cursor-pointer" : ""}`}
+            >
+              <span className="w-12 text-right pr-2 text-zinc-600 select-none shrink-0 border-r border-zinc-800">
+                {line.old_no ?? ""}
+              </span>
+              <span className="w-12 text-right pr-2 text-zinc-600 select-none shrink-0 border-r border-zinc-800">
+                {line.new_no ?? ""}
+              </span>
+              <span className="w-5 text-center select-none shrink-0 text-zinc-600">
+                {lineMarkers[line.kind]}
+              </span>
+              <span className="flex-1 whitespace-pre pl-1">{line.text}</span>
+            </div>
+          ))}
+        </div>
+      ))}
+    </div>
+  );
+}
+
+interface WorkingTreeDiffViewProps {
+  diff: WorkingTreeDiff;
+  /** Enables hunk and line staging when set */
+  worktreePath?: string;
+  onChange?: () => void;
+}
+
+/** Staged and unstaged changes of one file, each under its own heading. */
+export function WorkingTreeDiffView({ diff, worktreePath, onChange }: WorkingTreeDiffViewProps) {
+  const path = diff.file_path;
+  const action = (
+    label: string,
+    hunkFn: (wt: string, path: string, hunk: Hunk) => Promise<void>,
+    linesFn: (wt: string, path: string, hunk: Hunk, lines: number[]) => Promise<void>,
+    confirmMessage?: string
+  ): HunkAction => ({
+    label,
+    run: async (hunk, lines) => {
+      if (!worktreePath) return;
+      if (confirmMessage && !window.confirm(confirmMessage)) return;
+      try {
+        if (lines.length > 0) await linesFn(worktreePath, path, hunk, lines);
+        else await hunkFn(worktreePath, path, hunk);
+      } catch (err) {
+        // Usually a stale hunk: refreshing shows the current diff
+        console.error(`Failed to ${label.toLowerCase()}:`, err);
+      }
+      onChange?.();
+    },
+  });
+
+  // Only modified text files can be changed piecewise
+  const actionsFor = (fileDiff: FileDiff | null, actions: HunkAction[]) =>
+    worktreePath && fileDiff?.status === "Modified" && !fileDiff.binary ? actions : [];
+
+  const sections = [
+    {
+      label: "Staged changes",
+      fileDiff: diff.staged,
+      actions: actionsFor(diff.staged, [action("Unstage", gitUnstageHunk, gitUnstageLines)]),
+    },
+    {
+      label: "Changes",
+      fileDiff: diff.unstaged,
+      actions: actionsFor(diff.unstaged, [
+        action("Stage", gitStageHunk, gitStageLines),
+        action(
+          "Discard",
+          gitDiscardHunk,
+          gitDiscardLines,
+          `Discard these changes to ${path}? This cannot be undone.`
+        ),
+      ]),
+    },
+  ].filter((s) => s.fileDiff !== null);
+
+  if (sections.length === 0) {
+    return <UnifiedDiff diff={null} filePath={diff.file_path} />;
+  }
+
+  return (
+    <div className="overflow-auto h-full">
+      {sections.map(({ label, fileDiff, actions }) => (
+        <div key={label}>
+          {sections.length > 1 && (
+            <div className="px-3 py-1 text-[10px] uppercase tracking-wider text-zinc-500 font-semibold border-b border-zinc-800">
+              {label}
+            </div>
+          )}
+          <UnifiedDiff diff={fileDiff} filePath={diff.file_path} hunkActions={actions} />
         </div>
       ))}
     </div>
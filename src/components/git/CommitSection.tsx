@@ -1,11 +1,41 @@
-import { GitCommitHorizontal, ArrowUpFromLine } from "lucide-react";
+import { useState, useRef, useEffect } from "react";
+import {
+  GitCommitHorizontal,
+  ArrowUpFromLine,
+  ArrowDownToLine,
+  CloudDownload,
+  ChevronDown,
+  SlidersHorizontal,
+} from "lucide-react";
+import type {
+  CommitIdentity,
+  CommitOptions,
+  CommitResult,
+  PushOptions,
+  PushProgress,
+  PushResult,
+} from "../../types";
 
 interface CommitSectionProps {
   commitMessage: string;
   onCommitMessageChange: (msg: string) => void;
-  onCommit: () => void;
-  onPush: () => void;
+  /** Resolves to whether the commit was made */
+  onCommit: (options: CommitOptions) => Promise<boolean>;
+  /** Outcome of the last commit, including hook output */
+  commitResult: CommitResult | null;
+  commitError: string | null;
+  onPush: (options?: PushOptions) => void;
+  /** Progress of the running push, if any */
+  pushProgress: PushProgress | null;
+  /** Outcome of the last push */
+  pushResult: PushResult | null;
+  pushError: string | null;
+  onPull: () => void;
+  onFetch: () => void;
   aheadCount: number;
+  behindCount: number;
+  /** Upstream branch, if the branch tracks one */
+  upstream: string | null;
   hasStagedFiles: boolean;
 }
 
@@ -14,47 +44,270 @@ export function CommitSection({
   onCommitMessageChange,
   onCommit,
   onPush,
+  onPull,
+  onFetch,
   aheadCount,
+  behindCount,
+  upstream,
   hasStagedFiles,
+  pushProgress,
+  pushResult,
+  pushError,
+  commitResult,
+  commitError,
 }: CommitSectionProps) {
-  const canCommit = hasStagedFiles && commitMessage.trim().length > 0;
+  const [showOptions, setShowOptions] = useState(false);
+  const [amend, setAmend] = useState(false);
+  const [signoff, setSignoff] = useState(false);
+  const [noVerify, setNoVerify] = useState(false);
+  const [author, setAuthor] = useState("");
+  const [coAuthors, setCoAuthors] = useState("");
+
+  const parsedAuthor = author.trim() ? parseIdentity(author) : null;
+  const parsedCoAuthors = coAuthors
+    .split("\n")
+    .filter((line) => line.trim())
+    .map(parseIdentity);
+  const identitiesValid =
+    (!author.trim() || parsedAuthor !== null) && parsedCoAuthors.every((c) => c !== null);
+  const canCommit =
+    identitiesValid && (amend || (hasStagedFiles && commitMessage.trim().length > 0));
+
+  const handleCommit = async () => {
+    const committed = await onCommit({
+      amend,
+      signoff,
+      no_verify: noVerify,
+      author: parsedAuthor,
+      co_authors: parsedCoAuthors as CommitIdentity[],
+    });
+    if (committed) setAmend(false);
+  };
+  const [pushMenuOpen, setPushMenuOpen] = useState(false);
+  const pushMenuRef = useRef<HTMLDivElement>(null);
+
+  useEffect(() => {
+    const handleClick = (e: MouseEvent) => {
+      if (pushMenuRef.current && !pushMenuRef.current.contains(e.target as Node)) {
+        setPushMenuOpen(false);
+      }
+    };
+    document.addEventListener("mousedown", handleClick);
+    return () => document.removeEventListener("mousedown", handleClick);
+  }, []);
+
+  const pushWith = (options: PushOptions) => {
+    setPushMenuOpen(false);
+    onPush(options);
+  };
 
   return (
     <div className="px-2 py-2 border-b border-[var(--color-panel-border)]">
       <textarea
         value={commitMessage}
         onChange={(e) => onCommitMessageChange(e.target.value)}
-        placeholder="Commit message..."
+        placeholder={amend ? "New message (empty keeps the last one)..." : "Commit message..."}
         className="w-full bg-zinc-900 border border-zinc-700 rounded-md px-2.5 py-1.5 text-xs text-zinc-200 placeholder-zinc-600 resize-none focus:outline-none focus:border-indigo-500/50 transition-colors"
         rows={3}
         onKeyDown={(e) => {
           if (e.key === "Enter" && (e.ctrlKey || e.metaKey) && canCommit) {
-            onCommit();
+            handleCommit();
           }
         }}
       />
+      {showOptions && (
+        <div className="flex flex-col gap-1 mt-1 text-[11px] text-zinc-400">
+          <div className="flex items-center gap-3">
+            <label className="flex items-center gap-1">
+              <input type="checkbox" checked={amend} onChange={(e) => setAmend(e.target.checked)} />
+              Amend
+            </label>
+            <label className="flex items-center gap-1">
+              <input
+                type="checkbox"
+                checked={signoff}
+                onChange={(e) => setSignoff(e.target.checked)}
+              />
+              Sign off
+            </label>
+            <label className="flex items-center gap-1" title="Skip pre-commit and commit-msg hooks">
+              <input
+                type="checkbox"
+                checked={noVerify}
+                onChange={(e) => setNoVerify(e.target.checked)}
+              />
+              Skip hooks
+            </label>
+          </div>
+          <input
+            value={author}
+            onChange={(e) => setAuthor(e.target.value)}
+            placeholder="Author: Name <email> (default: you)"
+            className={`bg-zinc-900 border rounded px-1.5 py-0.5 text-zinc-200 placeholder-zinc-600 focus:outline-none ${
+              author.trim() && !parsedAuthor ? "border-red-500/60" : "border-zinc-700"
+            }`}
+          />
+          <textarea
+            value={coAuthors}
+            onChange={(e) => setCoAuthors(e.target.value)}
+            placeholder="Co-authors: Name <email>, one per line"
+            rows={2}
+            className={`bg-zinc-900 border rounded px-1.5 py-0.5 text-zinc-200 placeholder-zinc-600 resize-none focus:outline-none ${
+              parsedCoAuthors.some((c) => c === null) ? "border-red-500/60" : "border-zinc-700"
+            }`}
+          />
+        </div>
+      )}
       <div className="flex items-center gap-1.5 mt-1.5">
         <button
-          onClick={onCommit}
+          onClick={handleCommit}
           disabled={!canCommit}
           className="flex items-center gap-1.5 px-2.5 py-1 text-[11px] font-medium rounded-md transition-colors bg-indigo-600 text-white hover:bg-indigo-500 disabled:opacity-40 disabled:cursor-not-allowed"
         >
           <GitCommitHorizontal size={12} />
-          Commit
+          {amend ? "Amend" : "Commit"}
         </button>
         <button
-          onClick={onPush}
-          className="flex items-center gap-1.5 px-2.5 py-1 text-[11px] font-medium rounded-md transition-colors bg-zinc-800 text-zinc-300 hover:bg-zinc-700 border border-zinc-700"
+          onClick={() => setShowOptions(!showOptions)}
+          className={`p-1 transition-colors ${
+            showOptions ? "text-indigo-400" : "text-zinc-500 hover:text-zinc-300"
+          }`}
+          title="Commit options"
         >
-          <ArrowUpFromLine size={12} />
-          Push
-          {aheadCount > 0 && (
+          <SlidersHorizontal size={12} />
+        </button>
+        <div ref={pushMenuRef} className="relative flex">
+          <button
+            onClick={() => onPush()}
+            disabled={pushProgress !== null}
+            className="flex items-center gap-1.5 px-2.5 py-1 text-[11px] font-medium rounded-l-md transition-colors bg-zinc-800 text-zinc-300 hover:bg-zinc-700 border border-zinc-700 disabled:opacity-40 disabled:cursor-not-allowed"
+            title={upstream ? `Push to ${upstream}` : "Push and set upstream"}
+          >
+            <ArrowUpFromLine size={12} />
+            Push
+            {aheadCount > 0 && (
+              <span className="inline-flex items-center justify-center min-w-[16px] h-4 px-1 text-[10px] font-bold rounded-full bg-indigo-500/30 text-indigo-300">
+                {aheadCount}
+              </span>
+            )}
+          </button>
+          <button
+            onClick={() => setPushMenuOpen(!pushMenuOpen)}
+            disabled={pushProgress !== null}
+            className="px-1 rounded-r-md transition-colors bg-zinc-800 text-zinc-400 hover:bg-zinc-700 border border-l-0 border-zinc-700 disabled:opacity-40"
+            title="Push options"
+          >
+            <ChevronDown size={11} />
+          </button>
+          {pushMenuOpen && (
+            <div className="absolute top-full left-0 mt-1 z-50 min-w-[180px] bg-zinc-900 border border-zinc-700 rounded-md shadow-xl py-1">
+              <button
+                onClick={() => pushWith({ tags: true })}
+                className="w-full px-3 py-1.5 text-xs text-left text-zinc-300 hover:text-zinc-100 hover:bg-zinc-800"
+              >
+                Push with tags
+              </button>
+              <button
+                onClick={() => pushWith({ force_with_lease: true })}
+                className="w-full px-3 py-1.5 text-xs text-left text-zinc-300 hover:text-zinc-100 hover:bg-zinc-800"
+              >
+                Force push (with lease)
+              </button>
+            </div>
+          )}
+        </div>
+        <button
+          onClick={onPull}
+          disabled={!upstream}
+          className="flex items-center gap-1.5 px-2.5 py-1 text-[11px] font-medium rounded-md transition-colors bg-zinc-800 text-zinc-300 hover:bg-zinc-700 border border-zinc-700 disabled:opacity-40 disabled:cursor-not-allowed"
+          title={upstream ? `Pull from ${upstream}` : "No upstream branch"}
+        >
+          <ArrowDownToLine size={12} />
+          Pull
+          {behindCount > 0 && (
             <span className="inline-flex items-center justify-center min-w-[16px] h-4 px-1 text-[10px] font-bold rounded-full bg-indigo-500/30 text-indigo-300">
-              {aheadCount}
+              {behindCount}
             </span>
           )}
         </button>
+        <button
+          onClick={onFetch}
+          className="p-1 text-zinc-500 hover:text-zinc-300 transition-colors"
+          title="Fetch all remotes"
+        >
+          <CloudDownload size={13} />
+        </button>
       </div>
+      <CommitOutcome result={commitResult} error={commitError} />
+      {pushProgress ? (
+        <div className="mt-1 text-[11px] text-zinc-500">
+          {pushProgress.phase} {pushProgress.percent}%
+        </div>
+      ) : (
+        <PushOutcome result={pushResult} error={pushError} />
+      )}
+    </div>
+  );
+}
+
+function PushOutcome({ result, error }: { result: PushResult | null; error: string | null }) {
+  if (error) {
+    return <div className="mt-1 text-[11px] text-red-400 whitespace-pre-wrap">{error}</div>;
+  }
+  if (!result) return null;
+
+  const rejected = result.refs.filter((r) => r.status === "Rejected");
+  return (
+    <div className="mt-1 text-[11px]">
+      {result.success ? (
+        <div className="text-zinc-500">
+          {result.refs.every((r) => r.status === "UpToDate")
+            ? "Everything up to date"
+            : `Pushed to ${result.remote}`}
+          {result.upstream_set && " (upstream set)"}
+        </div>
+      ) : (
+        rejected.map((r) => (
+          <div key={r.remote_ref} className="text-red-400">
+            {r.remote_ref.replace("refs/heads/", "")} rejected
+            {r.reason && `: ${r.reason}`}
+          </div>
+        ))
+      )}
+      {result.messages.length > 0 && (
+        <pre className="mt-0.5 max-h-24 overflow-auto text-[10px] text-zinc-500 whitespace-pre-wrap">
+          {result.messages.join("\n")}
+        </pre>
+      )}
+    </div>
+  );
+}
+
+/** `Name <email>`, or null if the text isn't one. */
+function parseIdentity(text: string): CommitIdentity | null {
+  const match = text.trim().match(/^([^<>]+?)\s*<([^<>\s]+)>$/);
+  return match ? { name: match[1], email: match[2] } : null;
+}
+
+function CommitOutcome({ result, error }: { result: CommitResult | null; error: string | null }) {
+  if (error) {
+    return (
+      <pre className="mt-1 max-h-32 overflow-auto text-[11px] text-red-400 whitespace-pre-wrap">
+        {error}
+      </pre>
+    );
+  }
+  if (!result) return null;
+
+  return (
+    <div className="mt-1 text-[11px] text-zinc-500">
+      Committed {result.oid.slice(0, 7)}
+      {result.signed && " (signed)"}
+      {result.hook_output && (
+        <pre className="mt-0.5 max-h-24 overflow-auto text-[10px] whitespace-pre-wrap">
+          {result.hook_output}
+        </pre>
+      )}
     </div>
   );
 }
@@ -0,0 +1,184 @@
+import { useCallback, useEffect, useState } from "react";
+import { Check } from "lucide-react";
+import type { ConflictFile, ConflictResolution } from "../../types";
+import { gitConflictDetails, gitMarkResolved, gitResolveConflict } from "../../lib/tauri";
+
+interface ConflictResolverProps {
+  worktreePath: string;
+  filePath: string;
+  onResolved: () => void;
+}
+
+const blockChoices = ["Ours", "Theirs", "Both", "Custom"] as const;
+
+export function ConflictResolver({ worktreePath, filePath, onResolved }: ConflictResolverProps) {
+  const [conflict, setConflict] = useState<ConflictFile | null>(null);
+  // Choice per conflict block, in file order
+  const [choices, setChoices] = useState<(ConflictResolution | null)[]>([]);
+  const [error, setError] = useState<string | null>(null);
+
+  const load = useCallback(async () => {
+    try {
+      const details = await gitConflictDetails(worktreePath, filePath);
+      setConflict(details);
+      setChoices(details.regions.filter((r) => r.kind === "Conflict").map(() => null));
+      setError(null);
+    } catch (err) {
+      setError(String(err));
+    }
+  }, [worktreePath, filePath]);
+
+  useEffect(() => {
+    load();
+  }, [load]);
+
+  const choose = (idx: number, resolution: ConflictResolution) =>
+    setChoices((prev) => prev.map((c, j) => (j === idx ? resolution : c)));
+
+  const resolve = async (resolutions: ConflictResolution[]) => {
+    try {
+      await gitResolveConflict(worktreePath, filePath, resolutions);
+      onResolved();
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const markResolved = async () => {
+    try {
+      await gitMarkResolved(worktreePath, filePath);
+      onResolved();
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  if (!conflict) {
+    return (
+      <div className="flex items-center justify-center h-full text-zinc-600 text-xs py-6">
+        {error ?? "Loading conflict..."}
+      </div>
+    );
+  }
+
+  const { entry } = conflict;
+  const blocks = choices.length;
+  const allChosen = blocks > 0 && choices.every((c) => c !== null);
+  let blockIdx = -1;
+
+  return (
+    <div className="flex flex-col h-full">
+      <div className="flex items-center gap-1 px-3 py-1.5 border-b border-zinc-800 text-[11px] shrink-0">
+        <span className="flex-1 text-orange-400 font-mono truncate">{filePath}</span>
+        <button
+          onClick={() => resolve([{ kind: "Ours" }])}
+          className="px-1.5 py-0.5 rounded text-zinc-400 hover:text-zinc-100 hover:bg-zinc-800"
+          title={entry.ours ? "Take our version of every block" : "Ours deleted this file"}
+        >
+          {entry.ours ? "All ours" : "Delete (ours)"}
+        </button>
+        <button
+          onClick={() => resolve([{ kind: "Theirs" }])}
+          className="px-1.5 py-0.5 rounded text-zinc-400 hover:text-zinc-100 hover:bg-zinc-800"
+          title={entry.theirs ? "Take their version of every block" : "Theirs deleted this file"}
+        >
+          {entry.theirs ? "All theirs" : "Delete (theirs)"}
+        </button>
+        <button
+          onClick={markResolved}
+          className="px-1.5 py-0.5 rounded text-zinc-400 hover:text-zinc-100 hover:bg-zinc-800"
+          title="Stage the file as edited on disk"
+        >
+          Mark resolved
+        </button>
+      </div>
+
+      {error && <div className="px-3 py-1 text-[11px] text-red-400 shrink-0">{error}</div>}
+
+      <div className="flex-1 min-h-0 overflow-auto font-mono text-xs leading-5">
+        {conflict.binary || blocks === 0 ? (
+          <div className="px-3 py-4 text-zinc-500 font-sans">
+            {conflict.binary
+              ? "Binary file: choose a whole version."
+              : "No conflict markers: choose a version, or edit the file and mark it resolved."}
+          </div>
+        ) : (
+          conflict.regions.map((region, i) => {
+            if (region.kind === "Common") {
+              return (
+                <div key={i} className="whitespace-pre px-3 text-zinc-500">
+                  {region.text}
+                </div>
+              );
+            }
+            const idx = ++blockIdx;
+            const choice = choices[idx];
+            return (
+              <div key={i} className="border-y border-orange-900/50 my-1">
+                <div className="flex items-center gap-1 px-3 bg-zinc-800/50 font-sans text-[10px]">
+                  <span className="flex-1 text-orange-400">Conflict {idx + 1}</span>
+                  {blockChoices.map((kind) => (
+                    <button
+                      key={kind}
+                      onClick={() =>
+                        choose(
+                          idx,
+                          kind === "Custom"
+                            ? { kind, text: region.ours + region.theirs }
+                            : { kind }
+                        )
+                      }
+                      className={`px-1.5 rounded ${
+                        choice?.kind === kind
+                          ? "bg-indigo-500/20 text-indigo-300"
+                          : "text-zinc-400 hover:text-zinc-100"
+                      }`}
+                    >
+                      {kind === "Custom" ? "Edit" : kind}
+                    </button>
+                  ))}
+                </div>
+                {choice?.kind === "Custom" && (
+                  <textarea
+                    value={choice.text}
+                    onChange={(e) => choose(idx, { kind: "Custom", text: e.target.value })}
+                    rows={Math.max(3, choice.text.split("\n").length)}
+                    className="w-full bg-zinc-900 border-y border-zinc-700 px-3 text-zinc-200 focus:outline-none resize-y"
+                  />
+                )}
+                <div className="px-3 text-[10px] text-zinc-600 font-sans">{region.ours_label || "ours"}</div>
+                <div className="whitespace-pre px-3 bg-green-950/40 text-green-300">{region.ours}</div>
+                {region.base !== null && (
+                  <>
+                    <div className="px-3 text-[10px] text-zinc-600 font-sans">base</div>
+                    <div className="whitespace-pre px-3 text-zinc-400">{region.base}</div>
+                  </>
+                )}
+                <div className="px-3 text-[10px] text-zinc-600 font-sans">
+                  {region.theirs_label || "theirs"}
+                </div>
+                <div className="whitespace-pre px-3 bg-blue-950/40 text-blue-300">{region.theirs}</div>
+              </div>
+            );
+          })
+        )}
+      </div>
+
+      {blocks > 0 && (
+        <div className="flex items-center px-3 py-1.5 border-t border-zinc-800 shrink-0">
+          <span className="flex-1 text-[11px] text-zinc-500">
+            {choices.filter((c) => c !== null).length}/{blocks} blocks chosen
+          </span>
+          <button
+            onClick={() => resolve(choices as ConflictResolution[])}
+            disabled={!allChosen}
+            className="flex items-center gap-1 px-2 py-0.5 rounded text-[11px] bg-indigo-600 text-white hover:bg-indigo-500 disabled:opacity-40"
+          >
+            <Check size={11} />
+            Resolve
+          </button>
+        </div>
+      )}
+    </div>
+  );
+}
@@ -1,7 +1,8 @@
-import type { DiffOutput } from "../../types";
+import type { WorkingTreeDiff } from "../../types";
+import { WorkingTreeDiffView } from "../diff/UnifiedDiff";
 
 interface DiffPreviewProps {
-  diff: DiffOutput | null;
+  diff: WorkingTreeDiff | null;
   loading: boolean;
 }
 
@@ -14,7 +15,7 @@ export function DiffPreview({ diff, loading }: DiffPreviewProps) {
     );
   }
 
-  if (!diff || !diff.diff_text) {
+  if (!diff || (!diff.staged && !diff.unstaged)) {
     return (
       <div className="flex items-center justify-center p-4">
         <span className="text-xs text-zinc-500">
@@ -24,29 +25,5 @@ export function DiffPreview({ diff, loading }: DiffPreviewProps) {
     );
   }
 
-  const lines = diff.diff_text.split("\n");
-
-  return (
-    <div className="overflow-auto font-mono text-[11px] leading-[18px]">
-      {lines.map((line, i) => {
-        let className = "px-3 whitespace-pre ";
-        if (line.startsWith("+")) {
-          className += "bg-green-950/40 text-green-300";
-        } else if (line.startsWith("-")) {
-          className += "bg-red-950/40 text-red-300";
-        } else if (line.startsWith("@@")) {
-          className += "bg-blue-950/30 text-blue-400";
-        } else if (line.startsWith("diff") || line.startsWith("index")) {
-          className += "bg-zinc-800/50 text-zinc-500";
-        } else {
-          className += "text-zinc-400";
-        }
-        return (
-          <div key={i} className={className}>
-            {line}
-          </div>
-        );
-      })}
-    </div>
-  );
+  return <WorkingTreeDiffView diff={diff} />;
 }
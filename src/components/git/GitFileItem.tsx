@@ -1,4 +1,4 @@
-import { Plus, Minus, FileText, ExternalLink } from "lucide-react";
+import { Plus, Minus, FileText, ExternalLink, Undo2 } from "lucide-react";
 import type { GitFileStatus, FileState } from "../../types";
 import { useAppStore } from "../../stores/useAppStore";
 import { openFileInIde } from "../../lib/tauri";
@@ -9,6 +9,7 @@ interface GitFileItemProps {
   onSelect: (path: string) => void;
   onStage: (path: string) => void;
   onUnstage: (path: string) => void;
+  onDiscard: (path: string) => void;
   worktreePath: string;
 }
 
@@ -57,6 +58,7 @@ export function GitFileItem({
   onSelect,
   onStage,
   onUnstage,
+  onDiscard,
   worktreePath,
 }: GitFileItemProps) {
   const defaultIde = useAppStore((s) => s.settings.defaultIde);
@@ -101,6 +103,18 @@ export function GitFileItem({
         >
           <ExternalLink size={11} />
         </button>
+        {hasUnstaged && (
+          <button
+            onClick={(e) => {
+              e.stopPropagation();
+              onDiscard(file.path);
+            }}
+            className="p-0.5 text-zinc-500 hover:text-red-400"
+            title="Discard changes"
+          >
+            <Undo2 size={11} />
+          </button>
+        )}
         {hasUnstaged && (
           <button
             onClick={(e) => {
@@ -3,10 +3,18 @@ import { GitCommitHorizontal, RefreshCw } from "lucide-react";
 import { useAppStore } from "../../stores/useAppStore";
 import { useGitStatus } from "../../hooks/useGitStatus";
 import { gitDiffAll } from "../../lib/tauri";
-import type { DiffOutput } from "../../types";
+import type {
+  CommitOptions,
+  CommitResult,
+  PushOptions,
+  PushResult,
+  WorkingTreeDiff,
+} from "../../types";
 import { GitStatusList } from "./GitStatusList";
 import { CommitSection } from "./CommitSection";
-import { UnifiedDiff } from "../diff/UnifiedDiff";
+import { ConflictResolver } from "./ConflictResolver";
+import { SyncWithBase } from "./SyncWithBase";
+import { WorkingTreeDiffView } from "../diff/UnifiedDiff";
 import { PrPanel } from "../pr/PrPanel";
 import { CheckpointTimeline } from "../checkpoints/CheckpointTimeline";
 import { WorkspaceNotes } from "../workspace/WorkspaceNotes";
@@ -22,15 +30,23 @@ export function GitSidebar() {
     unstageFile,
     stageAll,
     unstageAll,
+    discardFile,
+    discardAll,
+    cleanUntracked,
     commit,
     push,
+    pushProgress,
+    fetchRemotes,
+    pull,
     aheadCount,
+    behindCount,
+    upstream,
     commitMessage,
     setCommitMessage,
   } = useGitStatus(selectedWorktree?.path ?? null);
 
   // Diff data for all changed files
-  const [diffs, setDiffs] = useState<DiffOutput[]>([]);
+  const [diffs, setDiffs] = useState<WorkingTreeDiff[]>([]);
   const [diffLoading, setDiffLoading] = useState(false);
 
   const loadDiffs = useCallback(async () => {
@@ -56,22 +72,115 @@ export function GitSidebar() {
 
   const stagedFiles = files.filter((f) => f.staged !== "Unmodified");
   const selectedDiff = diffs.find((d) => d.file_path === selectedFile);
+  const selectedConflicted = files.some(
+    (f) => f.path === selectedFile && (f.staged === "Conflicted" || f.unstaged === "Conflicted")
+  );
+
+  const [commitResult, setCommitResult] = useState<CommitResult | null>(null);
+  const [commitError, setCommitError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setCommitResult(null);
+    setCommitError(null);
+  }, [selectedWorktree]);
 
-  const handleCommit = async () => {
-    if (!commitMessage.trim() || stagedFiles.length === 0) return;
+  /** Resolves to whether the commit was made */
+  const handleCommit = async (options: CommitOptions) => {
+    // Amending can reword HEAD with nothing staged, and keeps its message if empty
+    if (!options.amend && (!commitMessage.trim() || stagedFiles.length === 0)) return false;
+    setCommitResult(null);
+    setCommitError(null);
     try {
-      await commit(commitMessage);
+      setCommitResult(await commit(commitMessage, options));
       loadDiffs();
+      return true;
     } catch (err) {
       console.error("Failed to commit:", err);
+      setCommitError(String(err));
+      return false;
     }
   };
 
-  const handlePush = async () => {
+  // Each discard takes a checkpoint first, so it can be undone from the timeline
+  const handleDiscard = async (filePath: string) => {
+    if (!window.confirm(`Discard all changes to ${filePath}? A checkpoint is saved first.`)) return;
     try {
-      await push();
+      await discardFile(filePath);
+      loadDiffs();
+    } catch (err) {
+      console.error("Failed to discard file:", err);
+    }
+  };
+
+  const handleDiscardAll = async () => {
+    const untracked = files.some((f) => f.unstaged === "Untracked");
+    if (!window.confirm("Discard all changes in this worktree? A checkpoint is saved first.")) return;
+    const includeUntracked =
+      untracked && window.confirm("Also delete untracked files?");
+    try {
+      await discardAll(includeUntracked);
+      loadDiffs();
+    } catch (err) {
+      console.error("Failed to discard changes:", err);
+    }
+  };
+
+  const handleCleanUntracked = async () => {
+    if (!window.confirm("Delete all untracked files? A checkpoint is saved first.")) return;
+    try {
+      await cleanUntracked();
+      loadDiffs();
+    } catch (err) {
+      console.error("Failed to delete untracked files:", err);
+    }
+  };
+
+  const handlePull = async () => {
+    try {
+      // Conflicts from a merging pull surface in the sync panel
+      try {
+        await pull(true);
+      } catch (err) {
+        if (!String(err).includes("fast-forward")) throw err;
+        if (!window.confirm("The branch has diverged from its upstream. Merge the upstream into it?")) return;
+        await pull(false);
+      }
+      loadDiffs();
+    } catch (err) {
+      console.error("Failed to pull:", err);
+    }
+  };
+
+  const handleFetch = async () => {
+    try {
+      await fetchRemotes();
+    } catch (err) {
+      console.error("Failed to fetch:", err);
+    }
+  };
+
+  const [pushResult, setPushResult] = useState<PushResult | null>(null);
+  const [pushError, setPushError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setPushResult(null);
+    setPushError(null);
+  }, [selectedWorktree]);
+
+  const handlePush = async (options?: PushOptions) => {
+    if (
+      options?.force_with_lease &&
+      !window.confirm("Force push? Remote commits you haven't fetched will still block it.")
+    ) {
+      return;
+    }
+    setPushResult(null);
+    setPushError(null);
+    try {
+      setPushResult(await push(options));
     } catch (err) {
       console.error("Failed to push:", err);
+      setPushError(String(err));
     }
   };
 
@@ -104,8 +213,23 @@ export function GitSidebar() {
             onCommitMessageChange={setCommitMessage}
             onCommit={handleCommit}
             onPush={handlePush}
+            onPull={handlePull}
+            onFetch={handleFetch}
             aheadCount={aheadCount}
+            behindCount={behindCount}
+            upstream={upstream}
             hasStagedFiles={stagedFiles.length > 0}
+            commitResult={commitResult}
+            commitError={commitError}
+            pushProgress={pushProgress}
+            pushResult={pushResult}
+            pushError={pushError}
+          />
+
+          <SyncWithBase
+            worktreePath={selectedWorktree.path}
+            files={files}
+            onSynced={handleRefresh}
           />
 
           <div
@@ -120,21 +244,32 @@ export function GitSidebar() {
               onUnstage={unstageFile}
               onStageAll={stageAll}
               onUnstageAll={unstageAll}
+              onDiscard={handleDiscard}
+              onDiscardAll={handleDiscardAll}
+              onCleanUntracked={handleCleanUntracked}
               worktreePath={selectedWorktree.path}
             />
           </div>
 
           {/* Diff Viewer */}
           <div className="flex-1 min-h-0 overflow-auto bg-[#09090b] border-b border-[var(--color-panel-border)]">
-            {selectedDiff ? (
+            {selectedFile && selectedConflicted ? (
+              <ConflictResolver
+                key={selectedFile}
+                worktreePath={selectedWorktree.path}
+                filePath={selectedFile}
+                onResolved={handleRefresh}
+              />
+            ) : selectedDiff ? (
               <div className="flex flex-col h-full">
                 <div className="px-3 py-1.5 border-b border-zinc-800 text-[11px] text-zinc-400 font-mono truncate shrink-0">
                   {selectedDiff.file_path}
                 </div>
                 <div className="flex-1 min-h-0 overflow-auto">
-                  <UnifiedDiff
-                    diffText={selectedDiff.diff_text}
-                    filePath={selectedDiff.file_path}
+                  <WorkingTreeDiffView
+                    diff={selectedDiff}
+                    worktreePath={selectedWorktree.path}
+                    onChange={handleRefresh}
                   />
                 </div>
               </div>
@@ -1,4 +1,4 @@
-import { Plus, Minus } from "lucide-react";
+import { Plus, Minus, Undo2, Eraser } from "lucide-react";
 import type { GitFileStatus } from "../../types";
 import { GitFileItem } from "./GitFileItem";
 import { FileTreeGroup } from "./FileTreeGroup";
@@ -11,6 +11,9 @@ interface GitStatusListProps {
   onUnstage: (path: string) => void;
   onStageAll: () => void;
   onUnstageAll: () => void;
+  onDiscard: (path: string) => void;
+  onDiscardAll: () => void;
+  onCleanUntracked: () => void;
   worktreePath: string;
 }
 
@@ -31,6 +34,7 @@ function renderFileGroup(
   onSelectFile: (path: string) => void,
   onStage: (path: string) => void,
   onUnstage: (path: string) => void,
+  onDiscard: (path: string) => void,
   worktreePath: string
 ) {
   const groups = groupByDirectory(files);
@@ -44,6 +48,7 @@ function renderFileGroup(
         onSelect={onSelectFile}
         onStage={onStage}
         onUnstage={onUnstage}
+        onDiscard={onDiscard}
         worktreePath={worktreePath}
       />
     ));
@@ -59,6 +64,7 @@ function renderFileGroup(
           onSelect={onSelectFile}
           onStage={onStage}
           onUnstage={onUnstage}
+          onDiscard={onDiscard}
           worktreePath={worktreePath}
         />
       ))}
@@ -74,10 +80,14 @@ export function GitStatusList({
   onUnstage,
   onStageAll,
   onUnstageAll,
+  onDiscard,
+  onDiscardAll,
+  onCleanUntracked,
   worktreePath,
 }: GitStatusListProps) {
   const staged = files.filter((f) => f.staged !== "Unmodified");
   const unstaged = files.filter((f) => f.unstaged !== "Unmodified");
+  const hasUntracked = unstaged.some((f) => f.unstaged === "Untracked");
 
   if (files.length === 0) {
     return (
@@ -109,6 +119,7 @@ export function GitStatusList({
             onSelectFile,
             onStage,
             onUnstage,
+            onDiscard,
             worktreePath
           )}
         </div>
@@ -119,6 +130,22 @@ export function GitStatusList({
             <span className="text-[10px] uppercase tracking-wider text-yellow-500 font-semibold flex-1">
               Changes ({unstaged.length})
             </span>
+            {hasUntracked && (
+              <button
+                onClick={onCleanUntracked}
+                className="p-0.5 text-zinc-500 hover:text-red-400 transition-colors"
+                title="Delete untracked files"
+              >
+                <Eraser size={12} />
+              </button>
+            )}
+            <button
+              onClick={onDiscardAll}
+              className="p-0.5 text-zinc-500 hover:text-red-400 transition-colors"
+              title="Discard all changes"
+            >
+              <Undo2 size={12} />
+            </button>
             <button
               onClick={onStageAll}
               className="p-0.5 text-zinc-500 hover:text-green-400 transition-colors"
@@ -133,6 +160,7 @@ export function GitStatusList({
             onSelectFile,
             onStage,
             onUnstage,
+            onDiscard,
             worktreePath
           )}
         </div>
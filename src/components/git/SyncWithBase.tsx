@@ -0,0 +1,153 @@
+import { useEffect, useState } from "react";
+import { GitMerge, Loader2 } from "lucide-react";
+import type { AheadBehind, GitFileStatus, SyncResult, SyncStrategy } from "../../types";
+import {
+  getDefaultBranch,
+  gitAheadBehind,
+  gitSyncAbort,
+  gitSyncContinue,
+  gitSyncInProgress,
+  gitSyncWithBase,
+} from "../../lib/tauri";
+
+interface SyncWithBaseProps {
+  worktreePath: string;
+  /** Current status; changes trigger a re-check of divergence and sync state */
+  files: GitFileStatus[];
+  onSynced: () => void;
+}
+
+function describe(result: SyncResult, strategy: SyncStrategy): string {
+  const base = result.base_ref ?? "base";
+  switch (result.status) {
+    case "UpToDate":
+      return `Up to date with ${base}`;
+    case "Updated":
+      return strategy === "Rebase" ? `Rebased onto ${base}` : `Merged ${base}`;
+    case "Conflicted":
+      return `${result.conflicts.length} conflicted file${
+        result.conflicts.length === 1 ? "" : "s"
+      }: resolve them, then continue`;
+  }
+}
+
+/** Rebase onto or merge from the base branch, with continue/abort while stopped. */
+export function SyncWithBase({ worktreePath, files, onSynced }: SyncWithBaseProps) {
+  const [baseBranch, setBaseBranch] = useState("");
+  const [strategy, setStrategy] = useState<SyncStrategy>("Rebase");
+  const [inProgress, setInProgress] = useState<SyncStrategy | null>(null);
+  const [message, setMessage] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [divergence, setDivergence] = useState<AheadBehind | null>(null);
+
+  useEffect(() => {
+    setMessage(null);
+    setError(null);
+    getDefaultBranch(worktreePath)
+      .then(setBaseBranch)
+      .catch(() => setBaseBranch("main"));
+  }, [worktreePath]);
+
+  // A pull or an outside git command can also start or finish a merge
+  useEffect(() => {
+    gitSyncInProgress(worktreePath)
+      .then(setInProgress)
+      .catch(() => setInProgress(null));
+  }, [worktreePath, files]);
+
+  useEffect(() => {
+    if (!baseBranch.trim()) return;
+    const timer = setTimeout(() => {
+      gitAheadBehind(worktreePath, baseBranch)
+        .then(setDivergence)
+        .catch(() => setDivergence(null));
+    }, 300);
+    return () => clearTimeout(timer);
+  }, [worktreePath, baseBranch, files]);
+
+  const run = async (action: () => Promise<SyncResult | void>, op: SyncStrategy) => {
+    setBusy(true);
+    setError(null);
+    try {
+      const result = await action();
+      if (result) {
+        setInProgress(result.in_progress);
+        setMessage(
+          describe(result, op) +
+            (result.fetch_error ? ` (fetch failed: ${result.fetch_error})` : "")
+        );
+      } else {
+        setInProgress(null);
+        setMessage(null);
+      }
+    } catch (err) {
+      setError(String(err));
+      setInProgress(await gitSyncInProgress(worktreePath).catch(() => null));
+    } finally {
+      setBusy(false);
+      onSynced();
+    }
+  };
+
+  return (
+    <div className="px-2 py-1.5 border-b border-[var(--color-panel-border)] text-[11px]">
+      {inProgress ? (
+        <div className="flex items-center gap-1.5">
+          <span className="flex-1 text-orange-400">
+            {inProgress} in progress
+          </span>
+          <button
+            onClick={() => run(() => gitSyncContinue(worktreePath), inProgress)}
+            disabled={busy}
+            className="px-2 py-0.5 rounded-md bg-indigo-600 text-white hover:bg-indigo-500 disabled:opacity-40"
+          >
+            Continue
+          </button>
+          <button
+            onClick={() => run(() => gitSyncAbort(worktreePath), inProgress)}
+            disabled={busy}
+            className="px-2 py-0.5 rounded-md bg-zinc-800 text-zinc-300 hover:bg-zinc-700 border border-zinc-700 disabled:opacity-40"
+          >
+            Abort
+          </button>
+        </div>
+      ) : (
+        <div className="flex items-center gap-1.5">
+          <GitMerge size={12} className="text-zinc-500 shrink-0" />
+          <input
+            value={baseBranch}
+            onChange={(e) => setBaseBranch(e.target.value)}
+            className="flex-1 min-w-0 bg-zinc-900 border border-zinc-700 rounded px-1.5 py-0.5 text-zinc-200 focus:outline-none focus:border-indigo-500/50"
+            title="Base branch"
+          />
+          <select
+            value={strategy}
+            onChange={(e) => setStrategy(e.target.value as SyncStrategy)}
+            className="bg-zinc-900 border border-zinc-700 rounded px-1 py-0.5 text-zinc-300 focus:outline-none"
+          >
+            <option value="Rebase">Rebase</option>
+            <option value="Merge">Merge</option>
+          </select>
+          <button
+            onClick={() => run(() => gitSyncWithBase(worktreePath, baseBranch, strategy), strategy)}
+            disabled={busy || !baseBranch.trim()}
+            className="flex items-center gap-1 px-2 py-0.5 rounded-md bg-zinc-800 text-zinc-300 hover:bg-zinc-700 border border-zinc-700 disabled:opacity-40"
+          >
+            {busy && <Loader2 size={11} className="animate-spin" />}
+            Sync
+          </button>
+        </div>
+      )}
+      {!inProgress && divergence?.base === baseBranch && (
+        <div className="mt-1 text-zinc-500">
+          {divergence.base_behind === 0
+            ? `Contains ${baseBranch}`
+            : `${divergence.base_behind} behind, ${divergence.base_ahead} ahead of ${baseBranch}`}
+        </div>
+      )}
+      {message && <div className="mt-1 text-zinc-500">{message}</div>}
+      {error && <div className="mt-1 text-red-400 whitespace-pre-wrap">{error}</div>}
+    </div>
+  );
+}
@@ -0,0 +1,38 @@
+import { useEffect, useRef } from "react";
+import { useProcessLogs } from "../../hooks/useProcessLogs";
+
+interface ProcessLogViewProps {
+  processId: string;
+}
+
+export function ProcessLogView({ processId }: ProcessLogViewProps) {
+  const { lines, truncated } = useProcessLogs(processId);
+  const bottomRef = useRef<HTMLDivElement>(null);
+
+  useEffect(() => {
+    bottomRef.current?.scrollIntoView({ block: "end" });
+  }, [lines]);
+
+  return (
+    <div className="max-h-64 overflow-auto rounded bg-zinc-950 border border-zinc-800 p-2 font-mono text-[10px] leading-4">
+      {truncated && (
+        <div className="text-zinc-600 italic">Earlier output was discarded.</div>
+      )}
+      {lines.length === 0 ? (
+        <div className="text-zinc-600">No output yet.</div>
+      ) : (
+        lines.map((line) => (
+          <div
+            key={line.offset}
+            className={`whitespace-pre-wrap break-all ${
+              line.stream === "Stderr" ? "text-red-300" : "text-zinc-300"
+            }`}
+          >
+            {line.text}
+          </div>
+        ))
+      )}
+      <div ref={bottomRef} />
+    </div>
+  );
+}
@@ -1,18 +1,123 @@
-import { Play, Square, RefreshCw, Circle } from "lucide-react";
+import { useEffect, useState } from "react";
+import {
+  Play,
+  PlayCircle,
+  Square,
+  RefreshCw,
+  Circle,
+  ScrollText,
+  AlertTriangle,
+} from "lucide-react";
 import { Button } from "../ui/Button";
+import { ProcessLogView } from "./ProcessLogView";
 import { useScripts } from "../../hooks/useScripts";
-import type { ScriptDef } from "../../types";
+import { useAppStore } from "../../stores/useAppStore";
+import { getStartupPortConflicts, reassignWorkspacePorts } from "../../lib/tauri";
+import type { ConfigDiagnostic, ScriptDef, WorkspacePortStatus } from "../../types";
+
+function DiagnosticList({ diagnostics }: { diagnostics: ConfigDiagnostic[] }) {
+  return (
+    <div className="space-y-1">
+      {diagnostics.map((d, i) => (
+        <div
+          key={i}
+          className={`flex items-start gap-1.5 text-[11px] ${
+            d.severity === "Error" ? "text-red-400" : "text-amber-400"
+          }`}
+        >
+          <AlertTriangle size={11} className="shrink-0 mt-0.5" />
+          <span>
+            <span className="font-mono text-zinc-400">
+              {d.file}
+              {d.line != null && `:${d.line}:${d.column}`}
+              {d.path && ` ${d.path}`}
+            </span>{" "}
+            {d.message}
+          </span>
+        </div>
+      ))}
+    </div>
+  );
+}
+
+/** Startup port conflicts of the active workspace, with a fix. */
+function PortConflictBanner() {
+  const activeWorkspaceId = useAppStore((s) => s.activeWorkspaceId);
+  const workspaces = useAppStore((s) => s.workspaces);
+  const [conflict, setConflict] = useState<WorkspacePortStatus | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setError(null);
+    getStartupPortConflicts()
+      .then((all) => setConflict(all.find((c) => c.workspace_id === activeWorkspaceId) ?? null))
+      .catch((err) => console.error("Failed to load port conflicts:", err));
+  }, [activeWorkspaceId]);
+
+  if (!conflict) return null;
+
+  const overlapping = conflict.overlapping_workspaces.map(
+    (id) => workspaces.find((w) => w.id === id)?.name ?? id
+  );
+
+  const handleReassign = async () => {
+    try {
+      const config = await reassignWorkspacePorts(conflict.workspace_id);
+      useAppStore.setState((s) => ({
+        workspaces: s.workspaces.map((w) =>
+          w.id === config.id ? { ...w, portBase: config.port_base, envVars: config.env_vars } : w
+        ),
+      }));
+      setConflict(null);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  return (
+    <div className="flex items-start gap-1.5 text-[11px] text-amber-400">
+      <AlertTriangle size={11} className="shrink-0 mt-0.5" />
+      <div className="flex-1 space-y-0.5">
+        <div>
+          Ports {conflict.port_base}-{conflict.port_base + conflict.port_range_size - 1} had
+          conflicts at startup
+        </div>
+        {conflict.busy_ports.length > 0 && (
+          <div className="text-zinc-500">In use: {conflict.busy_ports.join(", ")}</div>
+        )}
+        {overlapping.length > 0 && (
+          <div className="text-zinc-500">Overlaps: {overlapping.join(", ")}</div>
+        )}
+        {error && <div className="text-red-400">{error}</div>}
+      </div>
+      <Button variant="ghost" size="sm" onClick={handleReassign}>
+        Reassign
+      </Button>
+    </div>
+  );
+}
 
 export function RunPanel() {
   const {
     config,
+    diagnostics,
     processes,
     loading,
     hasConfig,
     executeScript,
+    startPipeline,
     killProcess,
     refreshProcesses,
   } = useScripts();
+  const [logProcessId, setLogProcessId] = useState<string | null>(null);
+
+  if (!hasConfig && diagnostics.length > 0) {
+    return (
+      <div className="p-3">
+        <DiagnosticList diagnostics={diagnostics} />
+      </div>
+    );
+  }
 
   if (!hasConfig) {
     return (
@@ -107,16 +212,30 @@ export function RunPanel() {
     <div className="flex flex-col h-full">
       <div className="flex items-center justify-between px-3 py-2 border-b border-zinc-800">
         <span className="text-xs font-semibold text-zinc-300">Scripts</span>
-        <button
-          onClick={refreshProcesses}
-          className="p-1 text-zinc-500 hover:text-zinc-300 rounded hover:bg-zinc-800 transition-colors"
-          title="Refresh"
-        >
-          <RefreshCw size={11} />
-        </button>
+        <div className="flex items-center gap-1">
+          {(config?.run.length ?? 0) > 0 && (
+            <button
+              onClick={() => startPipeline()}
+              disabled={loading}
+              className="p-1 text-zinc-500 hover:text-green-400 rounded hover:bg-zinc-800 transition-colors disabled:opacity-50"
+              title="Run all (in dependency order)"
+            >
+              <PlayCircle size={11} />
+            </button>
+          )}
+          <button
+            onClick={refreshProcesses}
+            className="p-1 text-zinc-500 hover:text-zinc-300 rounded hover:bg-zinc-800 transition-colors"
+            title="Refresh"
+          >
+            <RefreshCw size={11} />
+          </button>
+        </div>
       </div>
 
       <div className="flex-1 overflow-auto p-3 space-y-4">
+        <PortConflictBanner />
+        {diagnostics.length > 0 && <DiagnosticList diagnostics={diagnostics} />}
         {renderScriptGroup("Setup", config?.setup ?? [], "setup")}
         {renderScriptGroup("Run", config?.run ?? [], "run")}
         {renderScriptGroup("Archive", config?.archive ?? [], "archive")}
@@ -127,38 +246,57 @@ export function RunPanel() {
               Processes
             </div>
             {processes.map((p) => (
-              <div
-                key={p.id}
-                className="flex items-center justify-between py-1 px-2 text-xs"
-              >
-                <div className="flex items-center gap-2 min-w-0">
-                  <Circle
-                    size={6}
-                    className={
-                      p.status === "Running"
-                        ? "fill-green-500 text-green-500"
-                        : p.status === "Failed"
-                          ? "fill-red-500 text-red-500"
-                          : "text-zinc-600"
-                    }
-                  />
-                  <span className="text-zinc-300 truncate">
-                    {p.script_name}
-                  </span>
-                  <span className="text-[10px] text-zinc-600 font-mono">
-                    PID {p.pid}
-                  </span>
+              <div key={p.id}>
+                <div className="flex items-center justify-between py-1 px-2 text-xs">
+                  <div className="flex items-center gap-2 min-w-0">
+                    <Circle
+                      size={6}
+                      className={
+                        p.status === "Running"
+                          ? "fill-green-500 text-green-500"
+                          : p.status === "Failed"
+                            ? "fill-red-500 text-red-500"
+                            : "text-zinc-600"
+                      }
+                    />
+                    <span className="text-zinc-300 truncate">
+                      {p.script_name}
+                    </span>
+                    <span className="text-[10px] text-zinc-600 font-mono">
+                      PID {p.pid}
+                    </span>
+                    {p.status === "Failed" && (
+                      <span className="text-[10px] text-red-400 font-mono">
+                        {p.signal !== null
+                          ? `signal ${p.signal}`
+                          : `exit ${p.exit_code ?? "?"}`}
+                      </span>
+                    )}
+                  </div>
+                  <div className="flex items-center gap-1 shrink-0">
+                    <Button
+                      variant="ghost"
+                      size="icon"
+                      title="Output"
+                      onClick={() =>
+                        setLogProcessId(logProcessId === p.id ? null : p.id)
+                      }
+                    >
+                      <ScrollText size={10} />
+                    </Button>
+                    {p.status === "Running" && (
+                      <Button
+                        variant="ghost"
+                        size="icon"
+                        title="Stop"
+                        onClick={() => killProcess(p.id)}
+                      >
+                        <Square size={10} className="text-red-400" />
+                      </Button>
+                    )}
+                  </div>
                 </div>
-                {p.status === "Running" && (
-                  <Button
-                    variant="ghost"
-                    size="icon"
-                    title="Stop"
-                    onClick={() => killProcess(p.id)}
-                  >
-                    <Square size={10} className="text-red-400" />
-                  </Button>
-                )}
+                {logProcessId === p.id && <ProcessLogView processId={p.id} />}
               </div>
             ))}
           </div>
@@ -80,7 +80,7 @@ export function GeneralSettings({ draft, onChange }: GeneralSettingsProps) {
           className="w-full bg-zinc-800 border border-zinc-700 rounded px-3 py-1.5 text-sm text-zinc-200 focus:outline-none focus:border-indigo-500"
         />
         <p className="text-xs text-zinc-500 mt-1">
-          How often to check for git changes (1000-60000)
+          How often to check for git changes when file watching is unavailable (1000-60000)
         </p>
       </div>
     </div>
@@ -7,6 +7,7 @@ import type { IPty } from "tauri-pty";
 import "@xterm/xterm/css/xterm.css";
 import { useAppStore } from "../../stores/useAppStore";
 import { getAgentById, getAgentArgs } from "../../lib/agents";
+import { registerAgentSession, unregisterAgentSession } from "../../lib/tauri";
 
 // ---------------------------------------------------------------------------
 // Multi-session terminal architecture (keyed by tabId)
@@ -133,10 +134,27 @@ function killSessionPty(session: TerminalSession) {
       /* ignore */
     }
     session.pty = null;
+    trackAgentExit(session);
   }
   session.status = "exited";
 }
 
+// Agent sessions (not plain shells) are reported to the backend so it can
+// refuse destructive operations while an agent is working in the workspace.
+function trackAgentStart(session: TerminalSession) {
+  if (session.agentId === "shell") return;
+  const workspaceId = useAppStore.getState().activeWorkspaceId;
+  if (!workspaceId) return;
+  registerAgentSession(session.key, workspaceId, session.agentId, session.worktreePath).catch(
+    () => {}
+  );
+}
+
+function trackAgentExit(session: TerminalSession) {
+  if (session.agentId === "shell") return;
+  unregisterAgentSession(session.key).catch(() => {});
+}
+
 function getActiveWorkspaceEnv(): Record<string, string> {
   const state = useAppStore.getState();
   const ws = state.workspaces.find((w) => w.id === state.activeWorkspaceId);
@@ -186,6 +204,7 @@ function spawnInSession(
 
     session.pty = pty;
     session.status = "running";
+    trackAgentStart(session);
 
     const dataDisp = pty.onData((data) => {
       session.terminal.write(new Uint8Array(data));
@@ -199,6 +218,7 @@ function spawnInSession(
       if (session.pty === pty) {
         session.pty = null;
         session.status = "exited";
+        trackAgentExit(session);
       }
     });
     session.disposers.push(exitDisp);
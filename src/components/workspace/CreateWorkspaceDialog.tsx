@@ -1,9 +1,10 @@
 import { useState, useEffect, useMemo } from "react";
+import { listen } from "@tauri-apps/api/event";
 import { ChevronDown, Search } from "lucide-react";
 import { Modal } from "../ui/Modal";
 import { Button } from "../ui/Button";
-import type { BranchInfo } from "../../types";
-import { listBranches, getDefaultBranch } from "../../lib/tauri";
+import type { BranchInfo, ScriptStepEvent } from "../../types";
+import { listBranches, getDefaultBranch, stopScriptSteps } from "../../lib/tauri";
 import { useAppStore } from "../../stores/useAppStore";
 
 interface CreateWorkspaceDialogProps {
@@ -28,6 +29,7 @@ export function CreateWorkspaceDialog({
   const [selectedRepoPath, setSelectedRepoPath] = useState("");
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  const [setupStep, setSetupStep] = useState<ScriptStepEvent | null>(null);
 
   // Branch options
   const [showAdvanced, setShowAdvanced] = useState(false);
@@ -67,6 +69,21 @@ export function CreateWorkspaceDialog({
       .catch(() => setBranches([]));
   }, [open, selectedRepoPath]);
 
+  // Follow the setup steps while the workspace is being created
+  useEffect(() => {
+    if (!loading) {
+      setSetupStep(null);
+      return;
+    }
+    const unlisten = listen<ScriptStepEvent>("script-step-progress", (event) => {
+      if (event.payload.phase !== "Setup") return;
+      setSetupStep(event.payload.result ? null : event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loading]);
+
   const filteredBranches = useMemo(() => {
     if (!branchSearch) return branches;
     const q = branchSearch.toLowerCase();
@@ -294,6 +311,27 @@ export function CreateWorkspaceDialog({
           </p>
         )}
 
+        {setupStep && (
+          <div className="flex items-center justify-between gap-2 text-xs text-zinc-400">
+            <span className="truncate">
+              Setup {setupStep.index + 1}/{setupStep.total}:{" "}
+              <span className="text-zinc-300">{setupStep.name}</span>
+            </span>
+            <Button
+              type="button"
+              variant="ghost"
+              size="sm"
+              onClick={() =>
+                stopScriptSteps(setupStep.workspace_id).catch((err) =>
+                  console.error("Failed to stop setup:", err)
+                )
+              }
+            >
+              Stop
+            </Button>
+          </div>
+        )}
+
         <div className="flex justify-end gap-2 pt-1">
           <Button type="button" variant="ghost" size="sm" onClick={onClose}>
             Cancel
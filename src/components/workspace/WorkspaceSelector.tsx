@@ -89,7 +89,17 @@ export function WorkspaceSelector() {
     try {
       await deleteWorkspaceWithWorktree(id);
     } catch (err) {
-      console.error("Failed to delete worktree:", err);
+      // Archive scripts failed under the "abort" policy — let the user force it
+      if (!String(err).includes("not found")) {
+        if (!window.confirm(`${err}\n\nDelete the workspace anyway?`)) return;
+        try {
+          await deleteWorkspaceWithWorktree(id, true);
+        } catch (forceErr) {
+          console.error("Failed to delete worktree:", forceErr);
+        }
+      } else {
+        console.error("Failed to delete worktree:", err);
+      }
     }
 
     deleteWorkspace(id);
@@ -5,19 +5,34 @@ import {
   listCheckpoints,
   restoreCheckpoint,
   deleteCheckpoint,
+  diffCheckpoint,
+  listWorkspaceConfigs,
+  setAutoCheckpointPolicy,
+  setCheckpointRetention,
+  pruneCheckpoints,
 } from "../lib/tauri";
-import type { Checkpoint } from "../types";
+import type {
+  AutoCheckpointPolicy,
+  Checkpoint,
+  FileDiff,
+  CheckpointRetention,
+  RestoreMode,
+} from "../types";
 
 export function useCheckpoints() {
   const activeWorkspaceId = useAppStore((s) => s.activeWorkspaceId);
   const selectedWorktree = useAppStore((s) => s.selectedWorktree);
 
   const [checkpoints, setCheckpoints] = useState<Checkpoint[]>([]);
+  const [policy, setPolicy] = useState<AutoCheckpointPolicy | null>(null);
+  const [retention, setRetention] = useState<CheckpointRetention | null>(null);
   const [loading, setLoading] = useState(false);
 
   const refresh = useCallback(async () => {
     if (!activeWorkspaceId) {
       setCheckpoints([]);
+      setPolicy(null);
+      setRetention(null);
       return;
     }
     try {
@@ -26,6 +41,15 @@ export function useCheckpoints() {
     } catch {
       setCheckpoints([]);
     }
+    try {
+      const configs = await listWorkspaceConfigs();
+      const config = configs.find((w) => w.id === activeWorkspaceId);
+      setPolicy(config?.auto_checkpoints ?? null);
+      setRetention(config?.checkpoint_retention ?? null);
+    } catch {
+      setPolicy(null);
+      setRetention(null);
+    }
   }, [activeWorkspaceId]);
 
   useEffect(() => {
@@ -52,15 +76,40 @@ export function useCheckpoints() {
   );
 
   const restore = useCallback(
-    async (gitRef: string) => {
-      if (!selectedWorktree) return;
+    async (gitRef: string, mode: RestoreMode = "WorkingTree", paths?: string[]) => {
+      if (!activeWorkspaceId || !selectedWorktree) return;
       setLoading(true);
       try {
-        await restoreCheckpoint(selectedWorktree.path, gitRef);
+        try {
+          await restoreCheckpoint(activeWorkspaceId, selectedWorktree.path, gitRef, mode, paths);
+        } catch (err) {
+          // Scripts or agents still running: let the user decide
+          if (!String(err).includes("Workspace is busy")) throw err;
+          if (!window.confirm(`${err}\n\nRestore anyway?`)) return;
+          await restoreCheckpoint(
+            activeWorkspaceId,
+            selectedWorktree.path,
+            gitRef,
+            mode,
+            paths,
+            true
+          );
+        }
+        // The pre-restore checkpoint shows up in the timeline
+        await refresh();
       } finally {
         setLoading(false);
       }
     },
+    [activeWorkspaceId, selectedWorktree, refresh]
+  );
+
+  // Changes from a checkpoint to the current working tree
+  const diff = useCallback(
+    async (gitRef: string): Promise<FileDiff[]> => {
+      if (!selectedWorktree) return [];
+      return diffCheckpoint(selectedWorktree.path, gitRef);
+    },
     [selectedWorktree]
   );
 
@@ -77,12 +126,49 @@ export function useCheckpoints() {
     [refresh]
   );
 
+  const updatePolicy = useCallback(
+    async (next: AutoCheckpointPolicy) => {
+      if (!activeWorkspaceId) return;
+      await setAutoCheckpointPolicy(activeWorkspaceId, next);
+      // A lower limit prunes old automatic checkpoints
+      await refresh();
+    },
+    [activeWorkspaceId, refresh]
+  );
+
+  const updateRetention = useCallback(
+    async (next: CheckpointRetention) => {
+      if (!activeWorkspaceId) return;
+      await setCheckpointRetention(activeWorkspaceId, next);
+      await refresh();
+    },
+    [activeWorkspaceId, refresh]
+  );
+
+  const prune = useCallback(async () => {
+    if (!activeWorkspaceId) return [];
+    setLoading(true);
+    try {
+      const pruned = await pruneCheckpoints(activeWorkspaceId);
+      await refresh();
+      return pruned;
+    } finally {
+      setLoading(false);
+    }
+  }, [activeWorkspaceId, refresh]);
+
   return {
     checkpoints,
+    policy,
+    retention,
     loading,
     create,
     restore,
+    diff,
     remove,
+    prune,
+    updatePolicy,
+    updateRetention,
     refresh,
   };
 }
@@ -1,5 +1,14 @@
 import { useCallback, useEffect, useRef, useState } from "react";
-import type { GitFileStatus, DiffOutput } from "../types";
+import { listen } from "@tauri-apps/api/event";
+import type {
+  AheadBehind,
+  CommitOptions,
+  GitFileStatus,
+  GitStatusEvent,
+  PushOptions,
+  PushProgress,
+  WorkingTreeDiff,
+} from "../types";
 import * as tauri from "../lib/tauri";
 import { useAppStore } from "../stores/useAppStore";
 
@@ -7,38 +16,77 @@ export function useGitStatus(worktreePath: string | null) {
   const gitPollInterval = useAppStore((s) => s.settings.gitPollInterval);
   const [files, setFiles] = useState<GitFileStatus[]>([]);
   const [selectedFile, setSelectedFile] = useState<string | null>(null);
-  const [diff, setDiff] = useState<DiffOutput | null>(null);
+  const [diff, setDiff] = useState<WorkingTreeDiff | null>(null);
   const [loading, setLoading] = useState(false);
-  const [aheadCount, setAheadCount] = useState(0);
+  const [aheadBehind, setAheadBehind] = useState<AheadBehind | null>(null);
   const [commitMessage, setCommitMessage] = useState("");
   const intervalRef = useRef<ReturnType<typeof setInterval> | null>(null);
 
   const refresh = useCallback(async () => {
     if (!worktreePath) {
       setFiles([]);
-      setAheadCount(0);
+      setAheadBehind(null);
       return;
     }
     try {
-      const [status, ahead] = await Promise.all([
+      const [status, counts] = await Promise.all([
         tauri.gitStatus(worktreePath),
-        tauri.gitAheadCount(worktreePath).catch(() => 0),
+        tauri.gitAheadBehind(worktreePath).catch(() => null),
       ]);
       setFiles(status);
-      setAheadCount(ahead);
+      setAheadBehind(counts);
     } catch (err) {
       console.error("Failed to get git status:", err);
     }
   }, [worktreePath]);
 
-  // Poll git status
+  // Follow status changes pushed by the backend watcher; poll if it can't start
   useEffect(() => {
-    refresh();
-    intervalRef.current = setInterval(refresh, gitPollInterval);
+    if (!worktreePath) {
+      refresh();
+      return;
+    }
+    let cancelled = false;
+    let subscribed = false;
+
+    const unlisten = listen<GitStatusEvent>("git-status-changed", (event) => {
+      if (event.payload.worktree_path !== worktreePath) return;
+      setFiles(event.payload.files);
+      tauri
+        .gitAheadBehind(worktreePath)
+        .then(setAheadBehind)
+        .catch(() => setAheadBehind(null));
+    });
+
+    tauri
+      .gitAheadBehind(worktreePath)
+      .then((counts) => !cancelled && setAheadBehind(counts))
+      .catch(() => !cancelled && setAheadBehind(null));
+    tauri
+      .subscribeGitStatus(worktreePath)
+      .then((status) => {
+        subscribed = true;
+        if (cancelled) {
+          tauri.unsubscribeGitStatus(worktreePath);
+          return;
+        }
+        setFiles(status);
+      })
+      .catch((err) => {
+        if (cancelled) return;
+        console.error("Failed to watch git status, polling instead:", err);
+        refresh();
+        intervalRef.current = setInterval(refresh, gitPollInterval);
+      });
+
     return () => {
+      cancelled = true;
+      unlisten.then((fn) => fn());
+      if (subscribed) tauri.unsubscribeGitStatus(worktreePath);
       if (intervalRef.current) clearInterval(intervalRef.current);
+      intervalRef.current = null;
     };
-  }, [refresh, gitPollInterval]);
+  }, [worktreePath, refresh, gitPollInterval]);
 
   // Load diff when file is selected
   useEffect(() => {
@@ -84,22 +132,87 @@ export function useGitStatus(worktreePath: string | null) {
     await refresh();
   }, [worktreePath, refresh]);
 
-  const commit = useCallback(
-    async (message: string) => {
+  const discardFile = useCallback(
+    async (filePath: string) => {
       if (!worktreePath) return;
-      await tauri.gitCommit(worktreePath, message);
+      await tauri.gitDiscardFile(worktreePath, filePath);
+      await refresh();
+    },
+    [worktreePath, refresh]
+  );
+
+  const discardAll = useCallback(
+    async (includeUntracked: boolean) => {
+      if (!worktreePath) return;
+      await tauri.gitDiscardAll(worktreePath, includeUntracked);
+      await refresh();
+    },
+    [worktreePath, refresh]
+  );
+
+  const cleanUntracked = useCallback(async () => {
+    if (!worktreePath) return;
+    await tauri.gitCleanUntracked(worktreePath);
+    await refresh();
+  }, [worktreePath, refresh]);
+
+  const commit = useCallback(
+    async (message: string, options?: CommitOptions) => {
+      if (!worktreePath) return null;
+      const result = await tauri.gitCommit(worktreePath, message, options);
       setCommitMessage("");
       await refresh();
+      return result;
+    },
+    [worktreePath, refresh]
+  );
+
+  const [pushProgress, setPushProgress] = useState<PushProgress | null>(null);
+  // Progress events can arrive after the push command has returned
+  const pushingRef = useRef(false);
+
+  useEffect(() => {
+    const unlisten = listen<PushProgress>("git-push-progress", (event) => {
+      if (pushingRef.current && event.payload.worktree_path === worktreePath) {
+        setPushProgress(event.payload);
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [worktreePath]);
+
+  const push = useCallback(
+    async (options?: PushOptions) => {
+      if (!worktreePath) return null;
+      pushingRef.current = true;
+      try {
+        return await tauri.gitPush(worktreePath, options);
+      } finally {
+        pushingRef.current = false;
+        setPushProgress(null);
+        await refresh();
+      }
     },
     [worktreePath, refresh]
   );
 
-  const push = useCallback(async () => {
+  const fetchRemotes = useCallback(async () => {
     if (!worktreePath) return;
-    await tauri.gitPush(worktreePath);
+    await tauri.gitFetch(worktreePath);
     await refresh();
   }, [worktreePath, refresh]);
 
+  const pull = useCallback(
+    async (ffOnly: boolean) => {
+      if (!worktreePath) return null;
+      const result = await tauri.gitPull(worktreePath, ffOnly);
+      await refresh();
+      return result;
+    },
+    [worktreePath, refresh]
+  );
+
   return {
     files,
     selectedFile,
@@ -111,9 +224,17 @@ export function useGitStatus(worktreePath: string | null) {
     unstageFile,
     stageAll,
     unstageAll,
+    discardFile,
+    discardAll,
+    cleanUntracked,
     commit,
     push,
-    aheadCount,
+    pushProgress,
+    fetchRemotes,
+    pull,
+    aheadCount: aheadBehind?.ahead ?? 0,
+    behindCount: aheadBehind?.behind ?? 0,
+    upstream: aheadBehind?.upstream ?? null,
     commitMessage,
     setCommitMessage,
   };
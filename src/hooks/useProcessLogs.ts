@@ -0,0 +1,57 @@
+import { useState, useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { getProcessLogs } from "../lib/tauri";
+import type { ProcessLogLine, ProcessOutputEvent } from "../types";
+
+const MAX_LINES = 5000;
+
+export function useProcessLogs(processId: string | null) {
+  const [lines, setLines] = useState<ProcessLogLine[]>([]);
+  const [truncated, setTruncated] = useState(false);
+
+  useEffect(() => {
+    setLines([]);
+    setTruncated(false);
+    if (!processId) return;
+
+    let cancelled = false;
+    // Live lines that arrive before the history fetch resolves are held back
+    // so they can't jump ahead of older output.
+    let pending: ProcessLogLine[] | null = [];
+    // Offset of the next line we expect; anything below it is a duplicate.
+    let nextOffset = 0;
+
+    const append = (incoming: ProcessLogLine[]) => {
+      const fresh = incoming.filter((l) => l.offset >= nextOffset);
+      if (fresh.length === 0) return;
+      nextOffset = fresh[fresh.length - 1].offset + 1;
+      setLines((prev) => [...prev, ...fresh].slice(-MAX_LINES));
+    };
+
+    const unlisten = listen<ProcessOutputEvent>("process-output", (event) => {
+      if (cancelled || event.payload.process_id !== processId) return;
+      if (pending) pending.push(event.payload.line);
+      else append([event.payload.line]);
+    });
+
+    getProcessLogs(processId)
+      .then((chunk) => {
+        if (cancelled) return;
+        setTruncated(chunk.truncated);
+        append(chunk.lines);
+      })
+      .catch(() => {})
+      .finally(() => {
+        if (cancelled || !pending) return;
+        append(pending);
+        pending = null;
+      });
+
+    return () => {
+      cancelled = true;
+      unlisten.then((fn) => fn());
+    };
+  }, [processId]);
+
+  return { lines, truncated };
+}
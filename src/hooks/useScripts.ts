@@ -1,13 +1,16 @@
 import { useState, useEffect, useCallback } from "react";
+import { listen } from "@tauri-apps/api/event";
 import { useAppStore } from "../stores/useAppStore";
 import {
   loadHeroiConfig,
+  validateHeroiConfig,
   runScript,
+  runPipeline,
   stopProcess,
   listRunningProcesses,
   getWorkspaceEnv,
 } from "../lib/tauri";
-import type { HeroiConfig, RunningProcess, ScriptDef } from "../types";
+import type { ConfigDiagnostic, HeroiConfig, RunningProcess, ScriptDef } from "../types";
 
 export function useScripts() {
   const activeWorkspaceId = useAppStore((s) => s.activeWorkspaceId);
@@ -17,6 +20,7 @@ export function useScripts() {
   const activeWorkspace = workspaces.find((w) => w.id === activeWorkspaceId);
 
   const [config, setConfig] = useState<HeroiConfig | null>(null);
+  const [diagnostics, setDiagnostics] = useState<ConfigDiagnostic[]>([]);
   const [processes, setProcesses] = useState<RunningProcess[]>([]);
   const [loading, setLoading] = useState(false);
 
@@ -24,6 +28,7 @@ export function useScripts() {
   const loadConfig = useCallback(async () => {
     if (!selectedWorktree) {
       setConfig(null);
+      setDiagnostics([]);
       return;
     }
     try {
@@ -32,6 +37,11 @@ export function useScripts() {
     } catch {
       setConfig(null);
     }
+    try {
+      setDiagnostics(await validateHeroiConfig(selectedWorktree.path));
+    } catch {
+      setDiagnostics([]);
+    }
   }, [selectedWorktree]);
 
   // Refresh running processes
@@ -58,6 +68,18 @@ export function useScripts() {
     return () => clearInterval(interval);
   }, [refreshProcesses]);
 
+  // Pick up exits (clean or crashed) as soon as the backend reaps them
+  useEffect(() => {
+    const unlisten = listen<RunningProcess>("process-status-changed", (event) => {
+      setProcesses((prev) =>
+        prev.map((p) => (p.id === event.payload.id ? event.payload : p))
+      );
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   const executeScript = useCallback(
     async (script: ScriptDef) => {
       if (!activeWorkspaceId || !selectedWorktree) return;
@@ -83,6 +105,29 @@ export function useScripts() {
     [activeWorkspaceId, selectedWorktree, config, refreshProcesses]
   );
 
+  const startPipeline = useCallback(
+    async (targets?: string[]) => {
+      if (!activeWorkspaceId || !selectedWorktree) return;
+      setLoading(true);
+      try {
+        let envVars: Record<string, string> = {};
+        try {
+          envVars = await getWorkspaceEnv(activeWorkspaceId);
+        } catch {
+          // use config env as fallback
+        }
+        if (config?.env) {
+          envVars = { ...envVars, ...config.env };
+        }
+        await runPipeline(activeWorkspaceId, selectedWorktree.path, envVars, targets);
+        await refreshProcesses();
+      } finally {
+        setLoading(false);
+      }
+    },
+    [activeWorkspaceId, selectedWorktree, config, refreshProcesses]
+  );
+
   const killProcess = useCallback(
     async (processId: string) => {
       try {
@@ -99,6 +144,7 @@ export function useScripts() {
 
   return {
     config,
+    diagnostics,
     processes,
     loading,
     hasConfig,
@@ -106,6 +152,7 @@ export function useScripts() {
     loadConfig,
     refreshProcesses,
     executeScript,
+    startPipeline,
     killProcess,
   };
 }
@@ -0,0 +1,11 @@
+import type { FileDiff, FileState, WorkingTreeDiff } from "../types";
+
+/** Path of the file after the change (before it, for deletions). */
+export function diffPath(diff: FileDiff): string {
+  return diff.new_path ?? diff.old_path ?? "";
+}
+
+/** Overall state of a working tree file: staged state wins over unstaged. */
+export function workingTreeState(diff: WorkingTreeDiff): FileState {
+  return diff.staged?.status ?? diff.unstaged?.status ?? "Unmodified";
+}
@@ -4,21 +4,44 @@ import type {
   WorktreeInfo,
   BranchInfo,
   GitFileStatus,
-  DiffOutput,
+  FileDiff,
+  Hunk,
+  WorkingTreeDiff,
+  ConflictEntry,
+  ConflictFile,
+  ConflictResolution,
+  SyncStrategy,
+  SyncResult,
+  AheadBehind,
+  CommitOptions,
+  CommitResult,
+  PushOptions,
+  PushResult,
   AgentDef,
   IdeType,
   Settings,
   Workspace,
   WorkspaceConfig,
+  WorkspacePortStatus,
   HeroiConfig,
+  ConfigDiagnostic,
   ScriptDef,
+  ResolvedScript,
   RunningProcess,
+  ProcessLogChunk,
+  PipelineRun,
+  ScriptPhase,
+  ScriptRunLog,
   PrInfo,
   CheckRun,
   MergeMethod,
   DirEntry,
   FileContent,
   Checkpoint,
+  AutoCheckpointPolicy,
+  CheckpointRetention,
+  RestoreMode,
+  AgentSession,
 } from "../types";
 
 export async function addRepo(path: string): Promise<RepoEntry> {
@@ -33,6 +56,14 @@ export async function listRepos(): Promise<RepoEntry[]> {
   return invoke("list_repos");
 }
 
+export async function setRepoPortSettings(
+  path: string,
+  portStart: number | null,
+  portRangeSize: number | null
+): Promise<RepoEntry> {
+  return invoke("set_repo_port_settings", { path, portStart, portRangeSize });
+}
+
 export async function listWorktrees(repoPath: string): Promise<WorktreeInfo[]> {
   return invoke("list_worktrees", { repoPath });
 }
@@ -67,20 +98,31 @@ export async function gitStatus(
   return invoke("git_status", { worktreePath });
 }
 
+/** Watch a worktree for `git-status-changed` events; returns its current status. */
+export async function subscribeGitStatus(
+  worktreePath: string
+): Promise<GitFileStatus[]> {
+  return invoke("subscribe_git_status", { worktreePath });
+}
+
+export async function unsubscribeGitStatus(worktreePath: string): Promise<void> {
+  return invoke("unsubscribe_git_status", { worktreePath });
+}
+
 export async function gitDiff(worktreePath: string): Promise<string> {
   return invoke("git_diff", { worktreePath });
 }
 
 export async function gitDiffAll(
   worktreePath: string
-): Promise<DiffOutput[]> {
+): Promise<WorkingTreeDiff[]> {
   return invoke("git_diff_all", { worktreePath });
 }
 
 export async function gitDiffBase(
   worktreePath: string,
   baseBranch: string
-): Promise<DiffOutput[]> {
+): Promise<FileDiff[]> {
   return invoke("git_diff_base", { worktreePath, baseBranch });
 }
 
@@ -95,7 +137,7 @@ export async function gitFileContent(
 export async function gitDiffFile(
   worktreePath: string,
   filePath: string
-): Promise<DiffOutput> {
+): Promise<WorkingTreeDiff> {
   return invoke("git_diff_file", { worktreePath, filePath });
 }
 
@@ -113,6 +155,78 @@ export async function gitUnstageFile(
   return invoke("git_unstage_file", { worktreePath, filePath });
 }
 
+export async function gitStageHunk(
+  worktreePath: string,
+  filePath: string,
+  hunk: Hunk
+): Promise<void> {
+  return invoke("git_stage_hunk", { worktreePath, filePath, hunk });
+}
+
+export async function gitUnstageHunk(
+  worktreePath: string,
+  filePath: string,
+  hunk: Hunk
+): Promise<void> {
+  return invoke("git_unstage_hunk", { worktreePath, filePath, hunk });
+}
+
+export async function gitDiscardHunk(
+  worktreePath: string,
+  filePath: string,
+  hunk: Hunk
+): Promise<void> {
+  return invoke("git_discard_hunk", { worktreePath, filePath, hunk });
+}
+
+export async function gitStageLines(
+  worktreePath: string,
+  filePath: string,
+  hunk: Hunk,
+  lines: number[]
+): Promise<void> {
+  return invoke("git_stage_lines", { worktreePath, filePath, hunk, lines });
+}
+
+export async function gitUnstageLines(
+  worktreePath: string,
+  filePath: string,
+  hunk: Hunk,
+  lines: number[]
+): Promise<void> {
+  return invoke("git_unstage_lines", { worktreePath, filePath, hunk, lines });
+}
+
+export async function gitDiscardLines(
+  worktreePath: string,
+  filePath: string,
+  hunk: Hunk,
+  lines: number[]
+): Promise<void> {
+  return invoke("git_discard_lines", { worktreePath, filePath, hunk, lines });
+}
+
+/** Revert a file to HEAD; returns the safety checkpoint taken first. */
+export async function gitDiscardFile(
+  worktreePath: string,
+  filePath: string
+): Promise<Checkpoint> {
+  return invoke("git_discard_file", { worktreePath, filePath });
+}
+
+export async function gitDiscardAll(
+  worktreePath: string,
+  includeUntracked = false
+): Promise<Checkpoint> {
+  return invoke("git_discard_all", { worktreePath, includeUntracked });
+}
+
+export async function gitCleanUntracked(
+  worktreePath: string
+): Promise<Checkpoint> {
+  return invoke("git_clean_untracked", { worktreePath });
+}
+
 export async function gitStageAll(worktreePath: string): Promise<void> {
   return invoke("git_stage_all", { worktreePath });
 }
@@ -121,21 +235,98 @@ export async function gitUnstageAll(worktreePath: string): Promise<void> {
   return invoke("git_unstage_all", { worktreePath });
 }
 
+/** Runs `git commit`, so the repo's hooks and signing config apply. */
 export async function gitCommit(
   worktreePath: string,
-  message: string
-): Promise<string> {
-  return invoke("git_commit", { worktreePath, message });
+  message: string,
+  options?: CommitOptions
+): Promise<CommitResult> {
+  return invoke("git_commit", { worktreePath, message, options });
 }
 
-export async function gitPush(worktreePath: string): Promise<void> {
-  return invoke("git_push", { worktreePath });
+/** Push the current branch; emits `git-push-progress` while sending objects. */
+export async function gitPush(
+  worktreePath: string,
+  options?: PushOptions
+): Promise<PushResult> {
+  return invoke("git_push", { worktreePath, options });
 }
 
 export async function gitAheadCount(worktreePath: string): Promise<number> {
   return invoke("git_ahead_count", { worktreePath });
 }
 
+// Merge conflict commands
+
+export async function gitListConflicts(
+  worktreePath: string
+): Promise<ConflictEntry[]> {
+  return invoke("git_list_conflicts", { worktreePath });
+}
+
+export async function gitConflictDetails(
+  worktreePath: string,
+  filePath: string
+): Promise<ConflictFile> {
+  return invoke("git_conflict_details", { worktreePath, filePath });
+}
+
+/** One resolution per conflict block, or a single one for the whole file. */
+export async function gitResolveConflict(
+  worktreePath: string,
+  filePath: string,
+  resolutions: ConflictResolution[]
+): Promise<void> {
+  return invoke("git_resolve_conflict", { worktreePath, filePath, resolutions });
+}
+
+export async function gitMarkResolved(
+  worktreePath: string,
+  filePath: string
+): Promise<void> {
+  return invoke("git_mark_resolved", { worktreePath, filePath });
+}
+
+// Remote sync commands (fetch, pull, sync with base)
+
+export async function gitSyncWithBase(
+  worktreePath: string,
+  baseBranch: string,
+  strategy: SyncStrategy
+): Promise<SyncResult> {
+  return invoke("git_sync_with_base", { worktreePath, baseBranch, strategy });
+}
+
+export async function gitSyncContinue(worktreePath: string): Promise<SyncResult> {
+  return invoke("git_sync_continue", { worktreePath });
+}
+
+export async function gitSyncAbort(worktreePath: string): Promise<void> {
+  return invoke("git_sync_abort", { worktreePath });
+}
+
+export async function gitFetch(repoPath: string, remote?: string): Promise<void> {
+  return invoke("git_fetch", { repoPath, remote });
+}
+
+/** Pull the upstream; merge conflicts are finished like a sync. */
+export async function gitPull(worktreePath: string, ffOnly: boolean): Promise<SyncResult> {
+  return invoke("git_pull", { worktreePath, ffOnly });
+}
+
+export async function gitAheadBehind(
+  worktreePath: string,
+  baseBranch?: string
+): Promise<AheadBehind> {
+  return invoke("git_ahead_behind", { worktreePath, baseBranch });
+}
+
+export async function gitSyncInProgress(
+  worktreePath: string
+): Promise<SyncStrategy | null> {
+  return invoke("git_sync_in_progress", { worktreePath });
+}
+
 export async function openInIde(
   worktreePath: string,
   ide: IdeType
@@ -183,9 +374,16 @@ export async function createWorkspaceWithWorktree(
   repoPath: string,
   name: string,
   branch?: string,
-  baseBranch?: string
+  baseBranch?: string,
+  runSetup?: boolean
 ): Promise<WorkspaceConfig> {
-  return invoke("create_workspace", { repoPath, name, branch, baseBranch });
+  return invoke("create_workspace", {
+    repoPath,
+    name,
+    branch,
+    baseBranch,
+    runSetup,
+  });
 }
 
 export async function createWorkspaceForMain(
@@ -196,9 +394,10 @@ export async function createWorkspaceForMain(
 }
 
 export async function deleteWorkspaceWithWorktree(
-  workspaceId: string
-): Promise<void> {
-  return invoke("delete_workspace", { workspaceId });
+  workspaceId: string,
+  force?: boolean
+): Promise<ScriptRunLog | null> {
+  return invoke("delete_workspace", { workspaceId, force });
 }
 
 export async function getWorkspaceEnv(
@@ -207,8 +406,28 @@ export async function getWorkspaceEnv(
   return invoke("get_workspace_env", { workspaceId });
 }
 
-export async function archiveWorkspace(workspaceId: string): Promise<void> {
-  return invoke("archive_workspace", { workspaceId });
+export async function checkWorkspacePorts(
+  workspaceId?: string
+): Promise<WorkspacePortStatus[]> {
+  return invoke("check_workspace_ports", { workspaceId: workspaceId ?? null });
+}
+
+export async function reassignWorkspacePorts(
+  workspaceId: string
+): Promise<WorkspaceConfig> {
+  return invoke("reassign_workspace_ports", { workspaceId });
+}
+
+/** Conflicts detected when workspaces were loaded at startup. */
+export async function getStartupPortConflicts(): Promise<WorkspacePortStatus[]> {
+  return invoke("get_startup_port_conflicts");
+}
+
+export async function archiveWorkspace(
+  workspaceId: string,
+  force?: boolean
+): Promise<ScriptRunLog | null> {
+  return invoke("archive_workspace", { workspaceId, force });
 }
 
 export async function restoreWorkspace(workspaceId: string): Promise<void> {
@@ -247,6 +466,16 @@ export async function saveHeroiConfig(
   return invoke("save_heroi_config", { worktreePath, config });
 }
 
+export async function validateHeroiConfig(
+  worktreePath: string
+): Promise<ConfigDiagnostic[]> {
+  return invoke("validate_heroi_config", { worktreePath });
+}
+
+export async function getHeroiConfigSchema(): Promise<Record<string, unknown>> {
+  return invoke("get_heroi_config_schema");
+}
+
 export async function runScript(
   workspaceId: string,
   script: ScriptDef,
@@ -256,6 +485,22 @@ export async function runScript(
   return invoke("run_script", { workspaceId, script, worktreePath, extraEnv });
 }
 
+export async function runPipeline(
+  workspaceId: string,
+  worktreePath: string,
+  extraEnv: Record<string, string>,
+  targets?: string[]
+): Promise<PipelineRun> {
+  return invoke("run_pipeline", { workspaceId, worktreePath, targets, extraEnv });
+}
+
+export async function planScriptPipeline(
+  worktreePath: string,
+  targets?: string[]
+): Promise<string[][]> {
+  return invoke("plan_script_pipeline", { worktreePath, targets });
+}
+
 export async function stopProcess(processId: string): Promise<void> {
   return invoke("stop_process", { processId });
 }
@@ -270,6 +515,31 @@ export async function cleanupProcesses(): Promise<void> {
   return invoke("cleanup_processes");
 }
 
+export async function previewScript(
+  workspaceId: string,
+  script: ScriptDef
+): Promise<ResolvedScript> {
+  return invoke("preview_script", { workspaceId, script });
+}
+
+export async function stopScriptSteps(workspaceId: string): Promise<void> {
+  return invoke("stop_script_steps", { workspaceId });
+}
+
+export async function getScriptLog(
+  workspaceId: string,
+  phase: ScriptPhase
+): Promise<ScriptRunLog | null> {
+  return invoke("get_script_log", { workspaceId, phase });
+}
+
+export async function getProcessLogs(
+  processId: string,
+  sinceOffset?: number
+): Promise<ProcessLogChunk> {
+  return invoke("get_process_logs", { processId, sinceOffset });
+}
+
 // GitHub / PR commands
 
 export async function checkGhAvailable(): Promise<boolean> {
@@ -346,20 +616,81 @@ export async function listCheckpoints(
 }
 
 export async function restoreCheckpoint(
+  workspaceId: string,
   worktreePath: string,
-  gitRef: string
-): Promise<void> {
-  return invoke("restore_checkpoint", { worktreePath, gitRef });
+  gitRef: string,
+  mode: RestoreMode = "WorkingTree",
+  paths?: string[],
+  force?: boolean
+): Promise<Checkpoint> {
+  return invoke("restore_checkpoint", {
+    workspaceId,
+    worktreePath,
+    gitRef,
+    mode,
+    paths: paths ?? null,
+    force: force ?? null,
+  });
+}
+
+export async function registerAgentSession(
+  sessionId: string,
+  workspaceId: string,
+  agentId: string,
+  worktreePath: string
+): Promise<AgentSession> {
+  return invoke("register_agent_session", {
+    sessionId,
+    workspaceId,
+    agentId,
+    worktreePath,
+  });
+}
+
+export async function unregisterAgentSession(sessionId: string): Promise<void> {
+  return invoke("unregister_agent_session", { sessionId });
 }
 
 export async function deleteCheckpoint(checkpointId: string): Promise<void> {
   return invoke("delete_checkpoint", { checkpointId });
 }
 
+export async function setAutoCheckpointPolicy(
+  workspaceId: string,
+  policy: AutoCheckpointPolicy
+): Promise<void> {
+  return invoke("set_auto_checkpoint_policy", { workspaceId, policy });
+}
+
+export async function setCheckpointRetention(
+  workspaceId: string,
+  retention: CheckpointRetention
+): Promise<Checkpoint[]> {
+  return invoke("set_checkpoint_retention", { workspaceId, retention });
+}
+
+export async function pruneCheckpoints(workspaceId: string): Promise<Checkpoint[]> {
+  return invoke("prune_checkpoints", { workspaceId });
+}
+
 export async function diffCheckpoint(
   worktreePath: string,
   fromRef: string,
   toRef?: string
-): Promise<string> {
+): Promise<FileDiff[]> {
   return invoke("diff_checkpoint", { worktreePath, fromRef, toRef });
 }
+
+export async function restoreCheckpointPaths(
+  workspaceId: string,
+  gitRef: string,
+  paths: string[],
+  force?: boolean
+): Promise<Checkpoint> {
+  return invoke("restore_checkpoint_paths", {
+    workspaceId,
+    gitRef,
+    paths,
+    force: force ?? null,
+  });
+}
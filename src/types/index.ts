@@ -1,6 +1,8 @@
 export interface RepoEntry {
   path: string;
   name: string;
+  port_start?: number | null;
+  port_range_size?: number | null;
 }
 
 export interface WorktreeInfo {
@@ -26,9 +28,163 @@ export interface GitFileStatus {
   unstaged: FileState;
 }
 
-export interface DiffOutput {
+/** Payload of the `git-status-changed` event */
+export interface GitStatusEvent {
+  worktree_path: string;
+  files: GitFileStatus[];
+}
+
+export interface FileDiff {
+  old_path: string | null;
+  new_path: string | null;
+  status: FileState;
+  binary: boolean;
+  additions: number;
+  deletions: number;
+  hunks: Hunk[];
+}
+
+export interface Hunk {
+  header: string;
+  old_start: number;
+  old_lines: number;
+  new_start: number;
+  new_lines: number;
+  lines: DiffLine[];
+}
+
+export type DiffLineKind = "Context" | "Added" | "Removed" | "NoNewline";
+
+export interface DiffLine {
+  kind: DiffLineKind;
+  old_no: number | null;
+  new_no: number | null;
+  text: string;
+}
+
+export interface WorkingTreeDiff {
   file_path: string;
-  diff_text: string;
+  staged: FileDiff | null;
+  unstaged: FileDiff | null;
+}
+
+export interface ConflictStage {
+  path: string;
+  oid: string;
+  mode: number;
+}
+
+/** A conflicted path; a missing stage means that side lacks the file */
+export interface ConflictEntry {
+  path: string;
+  ancestor: ConflictStage | null;
+  ours: ConflictStage | null;
+  theirs: ConflictStage | null;
+}
+
+export type ConflictRegion =
+  | { kind: "Common"; text: string }
+  | {
+      kind: "Conflict";
+      ours_label: string;
+      theirs_label: string;
+      ours: string;
+      base: string | null;
+      theirs: string;
+    };
+
+export interface ConflictFile {
+  entry: ConflictEntry;
+  binary: boolean;
+  ancestor_text: string | null;
+  ours_text: string | null;
+  theirs_text: string | null;
+  regions: ConflictRegion[];
+}
+
+export type ConflictResolution =
+  | { kind: "Ours" }
+  | { kind: "Theirs" }
+  | { kind: "Both" }
+  | { kind: "Custom"; text: string };
+
+export type SyncStrategy = "Rebase" | "Merge";
+
+export interface SyncResult {
+  status: "UpToDate" | "Updated" | "Conflicted";
+  /** The rebase or merge still in progress, if it stopped */
+  in_progress: SyncStrategy | null;
+  conflicts: ConflictEntry[];
+  base_ref: string | null;
+  /** Set when fetching failed and the last known base was used */
+  fetch_error: string | null;
+  head: string;
+}
+
+export interface AheadBehind {
+  /** Configured upstream, e.g. "origin/feature" */
+  upstream: string | null;
+  ahead: number;
+  behind: number;
+  base: string | null;
+  base_ahead: number;
+  base_behind: number;
+}
+
+export interface CommitIdentity {
+  name: string;
+  email: string;
+}
+
+export interface CommitOptions {
+  /** Replace HEAD; an empty message keeps its message */
+  amend?: boolean;
+  signoff?: boolean;
+  /** Author other than the configured user, e.g. an agent */
+  author?: CommitIdentity | null;
+  /** Added as `Co-authored-by` trailers */
+  co_authors?: CommitIdentity[];
+  /** Skip pre-commit and commit-msg hooks */
+  no_verify?: boolean;
+  /** Force signing on or off; unset follows `commit.gpgsign` */
+  sign?: boolean | null;
+}
+
+export interface CommitResult {
+  oid: string;
+  signed: boolean;
+  hook_output: string;
+}
+
+export interface PushOptions {
+  remote?: string;
+  set_upstream?: boolean;
+  force_with_lease?: boolean;
+  tags?: boolean;
+}
+
+export interface PushRefUpdate {
+  local_ref: string;
+  remote_ref: string;
+  status: "FastForward" | "Forced" | "Deleted" | "New" | "UpToDate" | "Rejected";
+  summary: string;
+  reason: string | null;
+}
+
+export interface PushResult {
+  remote: string;
+  success: boolean;
+  refs: PushRefUpdate[];
+  /** Hook output, `remote:` messages and warnings */
+  messages: string[];
+  upstream_set: boolean;
+}
+
+/** Payload of the `git-push-progress` event */
+export interface PushProgress {
+  worktree_path: string;
+  phase: string;
+  percent: number;
 }
 
 export interface AgentDef {
@@ -112,10 +268,23 @@ export interface WorkspaceConfig {
   is_main_worktree: boolean;
   env_vars: Record<string, string>;
   port_base: number;
+  port_range_size: number;
+  ports: Record<string, number>;
+  auto_checkpoints: AutoCheckpointPolicy;
+  checkpoint_retention: CheckpointRetention;
   status: "Active" | "Archived";
   created_at: string;
 }
 
+export interface WorkspacePortStatus {
+  workspace_id: string;
+  port_base: number;
+  port_range_size: number;
+  busy_ports: number[];
+  overlapping_workspaces: string[];
+  has_running_processes: boolean;
+}
+
 // Scripts / heroi.json config
 export interface ScriptDef {
   name: string;
@@ -124,23 +293,135 @@ export interface ScriptDef {
   command_windows?: string;
   args_windows?: string[];
   cwd?: string;
+  depends_on?: string[];
+  parallel?: string;
+  /** A port number or a placeholder such as "${HEROI_PORT+1}" */
+  wait_for_port?: number | string;
+  wait_for_log?: string;
+  ready_timeout_secs?: number;
+  /** Setup/archive steps are killed after this long (default 600) */
+  timeout_secs?: number;
+  restart?: RestartPolicy;
+  max_restarts?: number;
+}
+
+export type RestartPolicy = "never" | "on-failure";
+
+export type DiagnosticSeverity = "Error" | "Warning";
+
+export interface ConfigDiagnostic {
+  severity: DiagnosticSeverity;
+  file: string;
+  path: string;
+  line: number | null;
+  column: number | null;
+  message: string;
+}
+
+export interface ResolvedScript {
+  name: string;
+  command: string;
+  args: string[];
+  cwd: string;
+  command_line: string;
+  env: Record<string, string>;
 }
 
+export type ArchiveFailurePolicy = "abort" | "warn";
+
 export interface HeroiConfig {
+  $schema?: string;
+  version?: number;
   setup: ScriptDef[];
   run: ScriptDef[];
   archive: ScriptDef[];
   env: Record<string, string>;
+  /** Named ports as offsets into the workspace's port block */
+  ports?: Record<string, number>;
+  archive_failure?: ArchiveFailurePolicy;
 }
 
 export type ProcessStatus = "Running" | "Exited" | "Failed";
 
+export type ScriptPhase = "Setup" | "Archive";
+
+export interface ScriptStepResult {
+  name: string;
+  command_line: string;
+  success: boolean;
+  exit_code: number | null;
+  duration_ms: number;
+  output: string;
+  error: string | null;
+}
+
+export interface ScriptRunLog {
+  workspace_id: string;
+  phase: ScriptPhase;
+  started_at: string;
+  finished_at: string;
+  success: boolean;
+  steps: ScriptStepResult[];
+}
+
+export interface ScriptStepEvent {
+  workspace_id: string;
+  phase: ScriptPhase;
+  index: number;
+  total: number;
+  name: string;
+  result: ScriptStepResult | null;
+}
+
 export interface RunningProcess {
   id: string;
   workspace_id: string;
   script_name: string;
   pid: number;
   status: ProcessStatus;
+  started_at: string;
+  ended_at: string | null;
+  duration_ms: number | null;
+  exit_code: number | null;
+  signal: number | null;
+  stopped_by_user: boolean;
+  restarts: number;
+}
+
+export interface PipelineRun {
+  id: string;
+  workspace_id: string;
+  stages: string[][];
+}
+
+export type PipelineStepState = "Started" | "Ready" | "Failed" | "Skipped";
+
+export interface PipelineEvent {
+  pipeline_id: string;
+  workspace_id: string;
+  script: string | null;
+  state: PipelineStepState;
+  process_id: string | null;
+  message: string | null;
+}
+
+export type LogStream = "Stdout" | "Stderr";
+
+export interface ProcessLogLine {
+  offset: number;
+  stream: LogStream;
+  text: string;
+}
+
+export interface ProcessOutputEvent {
+  process_id: string;
+  line: ProcessLogLine;
+}
+
+export interface ProcessLogChunk {
+  lines: ProcessLogLine[];
+  next_offset: number;
+  truncated: boolean;
 }
 
 // GitHub / PR types
@@ -188,11 +469,40 @@ export interface FileContent {
 export interface Checkpoint {
   id: string;
   workspace_id: string;
+  worktree_path: string;
   label: string;
   git_ref: string;
+  ref_name: string | null;
   created_at: string;
   file_count: number;
   agent_id: string | null;
+  auto: boolean;
+  /** Taken before a discard or restore */
+  system: boolean;
+}
+
+export interface AutoCheckpointPolicy {
+  interval_minutes: number | null;
+  on_agent_exit: boolean;
+  before_script: boolean;
+  max_checkpoints: number;
+}
+
+export interface CheckpointRetention {
+  keep_last: number;
+  hourly_for_hours: number;
+  daily_for_days: number;
+  keep_manual: boolean;
+}
+
+export type RestoreMode = "WorkingTree" | "Paths" | "Hard";
+
+export interface AgentSession {
+  id: string;
+  workspace_id: string;
+  agent_id: string;
+  worktree_path: string;
+  started_at: string;
 }
 
 // Legacy workspace format (for migration)